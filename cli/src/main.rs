@@ -1,3 +1,12 @@
+use std::{path::Path, str::FromStr};
+
+use cesium_crypto::{
+    keystore::Keystore,
+    mldsa::da::DisplayAddress,
+    secret::Zeroizing,
+    shamir::{self, Share},
+};
+use cesium_nebula::transaction::parse_id;
 use clap::{Parser, Subcommand};
 
 #[derive(Parser)]
@@ -25,6 +34,137 @@ enum Commands {
         to: String,
         amount: u128,
     },
+    /// Split a keystore's secret key into shares for separate custodians
+    SplitKey {
+        keystore: String,
+        #[arg(short, long)]
+        threshold: u8,
+        #[arg(short = 'n', long)]
+        shares: u8,
+        #[arg(short, long, default_value = ".")]
+        out_dir: String,
+    },
+    /// Recombine shares into a new keystore
+    CombineKey {
+        #[arg(required = true)]
+        shares: Vec<String>,
+        #[arg(short, long)]
+        keystore: String,
+    },
+}
+
+fn read_password(prompt: &str, confirm: bool) -> Option<Zeroizing<String>> {
+    let password = Zeroizing::new(rpassword::prompt_password(prompt).ok()?);
+    if confirm {
+        let confirmation =
+            Zeroizing::new(rpassword::prompt_password("Confirm keystore password: ").ok()?);
+        if password != confirmation {
+            println!("Keystore passwords do not match");
+            return None;
+        }
+    }
+    Some(password)
+}
+
+fn split_key(keystore_path: &str, threshold: u8, count: u8, out_dir: &str) {
+    let keystore = match Keystore::load(Path::new(keystore_path)) {
+        Ok(keystore) => keystore,
+        Err(e) => {
+            println!("Unable to read keystore {}: {}", keystore_path, e);
+            return;
+        }
+    };
+
+    let Some(password) = read_password("Keystore password: ", false) else {
+        return;
+    };
+    let sec_key = match keystore.decrypt(password.as_bytes()) {
+        Ok(sec_key) => sec_key,
+        Err(e) => {
+            println!("Unable to unlock keystore: {}", e);
+            return;
+        }
+    };
+
+    let shares = match shamir::split(
+        keystore.scheme(),
+        keystore.pub_key(),
+        sec_key.expose(),
+        threshold,
+        count,
+    ) {
+        Ok(shares) => shares,
+        Err(e) => {
+            println!("Unable to split secret key: {}", e);
+            return;
+        }
+    };
+
+    for share in &shares {
+        let path = Path::new(out_dir).join(format!("share-{}.cshare", share.index()));
+        if let Err(e) = share.save(&path) {
+            println!("Unable to write share {}: {}", path.display(), e);
+            return;
+        }
+        println!("Wrote share {}", path.display());
+    }
+    println!(
+        "Any {} of the {} shares recover the key, hand each one to a different custodian",
+        threshold, count
+    );
+}
+
+fn combine_key(share_paths: &[String], keystore_path: &str) {
+    let path = Path::new(keystore_path);
+    if path.exists() {
+        println!("Keystore {} already exists", keystore_path);
+        return;
+    }
+
+    let mut shares = Vec::with_capacity(share_paths.len());
+    for share_path in share_paths {
+        match Share::load(Path::new(share_path)) {
+            Ok(share) => shares.push(share),
+            Err(e) => {
+                println!("Unable to read share {}: {}", share_path, e);
+                return;
+            }
+        }
+    }
+
+    let sec_key = match shamir::combine(&shares) {
+        Ok(sec_key) => sec_key,
+        Err(e) => {
+            println!("Unable to recombine secret key: {}", e);
+            return;
+        }
+    };
+
+    let Some(password) = read_password("New keystore password: ", true) else {
+        return;
+    };
+    let share = &shares[0];
+    let keystore = match Keystore::encrypt(
+        share.scheme(),
+        share.pub_key(),
+        sec_key.expose(),
+        password.as_bytes(),
+    ) {
+        Ok(keystore) => keystore,
+        Err(e) => {
+            println!("Unable to encrypt keystore: {}", e);
+            return;
+        }
+    };
+
+    if let Err(e) = keystore.save(path) {
+        println!("Unable to write keystore {}: {}", keystore_path, e);
+        return;
+    }
+    match keystore.address() {
+        Ok(address) => println!("Restored account {} to {}", address, keystore_path),
+        Err(_) => println!("Restored account to {}", keystore_path),
+    }
 }
 
 fn main() {
@@ -35,6 +175,11 @@ fn main() {
         }
         Commands::Balance { account } => {
             if let Some(account) = account {
+                if let Err(e) = DisplayAddress::from_str(account) {
+                    println!("Invalid account address {}: {}", account, e);
+                    return;
+                }
+
                 println!("Checking balance for account: {}", account);
             } else {
                 println!("Checking balance for default account");
@@ -44,6 +189,11 @@ fn main() {
             println!("Deploying contract from file: {}", wasm_file);
         }
         Commands::Tx { hash } => {
+            if let Err(e) = parse_id(hash) {
+                println!("Invalid transaction hash {}: {}", hash, e);
+                return;
+            }
+
             println!("Checking transaction data for hash: {}", hash);
         }
         Commands::Send {
@@ -51,7 +201,19 @@ fn main() {
             to,
             amount,
         } => {
+            if let Err(e) = DisplayAddress::from_str(to) {
+                println!("Invalid recipient address {}: {}", to, e);
+                return;
+            }
+
             println!("Sending {} {} to account: {}", amount, currency, to);
         }
+        Commands::SplitKey {
+            keystore,
+            threshold,
+            shares,
+            out_dir,
+        } => split_key(keystore, *threshold, *shares, out_dir),
+        Commands::CombineKey { shares, keystore } => combine_key(shares, keystore),
     }
 }
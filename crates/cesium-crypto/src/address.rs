@@ -0,0 +1,103 @@
+use bech32::{
+    primitives::decode::{CheckedHrpstring, CheckedHrpstringError},
+    Bech32m, Hrp,
+};
+
+use crate::errors::CryptoError;
+
+// Display addresses are written as bech32m with a network prefix
+// (csm1...), the checksum catches any typo of up to four characters
+
+pub const ADDRESS_PREFIX: &str = "csm";
+const HRP: Hrp = Hrp::parse_unchecked(ADDRESS_PREFIX);
+
+pub fn encode(bytes: &[u8]) -> String {
+    // Display addresses are at most 48 bytes, well within the bech32m limit
+    bech32::encode::<Bech32m>(HRP, bytes).unwrap()
+}
+
+/// Strictly decode an address holding exactly `byte_len` bytes
+pub fn decode(address: &str, byte_len: usize) -> Result<Vec<u8>, CryptoError> {
+    let checked = match CheckedHrpstring::new::<Bech32m>(address) {
+        Ok(checked) => checked,
+        Err(CheckedHrpstringError::Checksum(_)) => return Err(CryptoError::InvalidAddressChecksum),
+        Err(_) => return Err(CryptoError::InvalidAddressEncoding),
+    };
+
+    if checked.hrp() != HRP {
+        return Err(CryptoError::InvalidAddressPrefix);
+    }
+
+    let bytes: Vec<u8> = checked.byte_iter().collect();
+    if bytes.len() != byte_len {
+        return Err(CryptoError::InvalidAddressLength);
+    }
+
+    // Reject non-zero padding bits, so every address has a single spelling
+    // (ignoring the upper case form bech32 allows)
+    if encode(&bytes) != address.to_lowercase() {
+        return Err(CryptoError::InvalidAddressEncoding);
+    }
+
+    Ok(bytes)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_round_trip() {
+        for len in [32, 48] {
+            let bytes = (0..len as u8).collect::<Vec<u8>>();
+            let address = encode(&bytes);
+            assert!(address.starts_with("csm1"));
+            assert_eq!(decode(&address, len).unwrap(), bytes);
+            assert_eq!(decode(&address.to_uppercase(), len).unwrap(), bytes);
+        }
+    }
+
+    #[test]
+    fn test_errors() {
+        let address = encode(&[7u8; 32]);
+
+        // Single character typo
+        let mut typo = address.clone().into_bytes();
+        let last = typo.len() - 1;
+        typo[last] = if typo[last] == b'q' { b'p' } else { b'q' };
+        let typo = String::from_utf8(typo).unwrap();
+        assert!(matches!(
+            decode(&typo, 32),
+            Err(CryptoError::InvalidAddressChecksum)
+        ));
+
+        let other_prefix =
+            bech32::encode::<Bech32m>(Hrp::parse("tcsm").unwrap(), &[7u8; 32]).unwrap();
+        assert!(matches!(
+            decode(&other_prefix, 32),
+            Err(CryptoError::InvalidAddressPrefix)
+        ));
+
+        assert!(matches!(
+            decode(&address, 48),
+            Err(CryptoError::InvalidAddressLength)
+        ));
+
+        // Plain bech32 checksums are not accepted
+        let bech32 = bech32::encode::<bech32::Bech32>(HRP, &[7u8; 32]).unwrap();
+        assert!(matches!(
+            decode(&bech32, 32),
+            Err(CryptoError::InvalidAddressChecksum)
+        ));
+
+        let mixed_case = address.replacen("csm", "CSM", 1);
+        assert!(matches!(
+            decode(&mixed_case, 32),
+            Err(CryptoError::InvalidAddressEncoding)
+        ));
+        assert!(matches!(
+            decode("cesium", 32),
+            Err(CryptoError::InvalidAddressEncoding)
+        ));
+    }
+}
@@ -0,0 +1,178 @@
+use std::{
+    num::NonZeroUsize,
+    sync::atomic::{AtomicUsize, Ordering},
+    thread,
+};
+
+use crate::scheme::{AnyPublicKey, AnySignature};
+
+// Below this many signatures spawning workers costs more than it saves
+const MIN_PARALLEL_BATCH: usize = 4;
+
+struct BatchItem {
+    pub_key: AnyPublicKey,
+    msg: Vec<u8>,
+    sig: AnySignature,
+}
+
+/// Collects (public key, message, signature) triples and verifies them on a
+/// pool of worker threads
+pub struct BatchVerifier {
+    items: Vec<BatchItem>,
+    workers: usize,
+}
+
+impl Default for BatchVerifier {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl BatchVerifier {
+    /// Uses one worker per available core
+    pub fn new() -> Self {
+        let workers = thread::available_parallelism()
+            .map(NonZeroUsize::get)
+            .unwrap_or(1);
+        Self::with_workers(workers)
+    }
+
+    pub fn with_workers(workers: usize) -> Self {
+        Self {
+            items: Vec::new(),
+            workers: workers.max(1),
+        }
+    }
+
+    /// Queues a signature and returns its index in the batch
+    pub fn push(&mut self, pub_key: AnyPublicKey, msg: &[u8], sig: AnySignature) -> usize {
+        self.items.push(BatchItem {
+            pub_key,
+            msg: msg.to_vec(),
+            sig,
+        });
+        self.items.len() - 1
+    }
+
+    pub fn len(&self) -> usize {
+        self.items.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.items.is_empty()
+    }
+
+    pub fn verify(&self) -> BatchResult {
+        let workers = self.workers.min(self.items.len());
+        let valid = if workers <= 1 || self.items.len() < MIN_PARALLEL_BATCH {
+            self.items.iter().map(Self::verify_item).collect()
+        } else {
+            self.verify_parallel(workers)
+        };
+
+        BatchResult { valid }
+    }
+
+    fn verify_item(item: &BatchItem) -> bool {
+        item.pub_key.verify(&item.msg, &item.sig).unwrap_or(false)
+    }
+
+    fn verify_parallel(&self, workers: usize) -> Vec<bool> {
+        // Workers take the next unverified item, so a few slow schemes do not
+        // hold up a whole chunk
+        let next = AtomicUsize::new(0);
+        let mut valid = vec![false; self.items.len()];
+
+        let results: Vec<Vec<(usize, bool)>> = thread::scope(|scope| {
+            let handles: Vec<_> = (0..workers)
+                .map(|_| {
+                    scope.spawn(|| {
+                        let mut results = Vec::new();
+                        loop {
+                            let index = next.fetch_add(1, Ordering::Relaxed);
+                            match self.items.get(index) {
+                                Some(item) => results.push((index, Self::verify_item(item))),
+                                None => return results,
+                            }
+                        }
+                    })
+                })
+                .collect();
+            handles.into_iter().map(|h| h.join().unwrap()).collect()
+        });
+
+        for (index, result) in results.into_iter().flatten() {
+            valid[index] = result;
+        }
+        valid
+    }
+}
+
+/// Outcome of a batch, indexed like the pushed signatures
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct BatchResult {
+    valid: Vec<bool>,
+}
+
+impl BatchResult {
+    pub fn all_valid(&self) -> bool {
+        self.valid.iter().all(|&v| v)
+    }
+
+    pub fn is_valid(&self, index: usize) -> bool {
+        self.valid.get(index).copied().unwrap_or(false)
+    }
+
+    /// Indexes of the signatures that did not verify
+    pub fn failed(&self) -> Vec<usize> {
+        self.valid
+            .iter()
+            .enumerate()
+            .filter(|(_, &v)| !v)
+            .map(|(i, _)| i)
+            .collect()
+    }
+}
+
+#[cfg(all(test, feature = "mldsa"))]
+mod tests {
+    use super::*;
+    use crate::{mldsa::keypair::SignerPair, scheme::Signer};
+
+    fn signed_batch(workers: usize, count: usize) -> (BatchVerifier, Vec<SignerPair>) {
+        let signers: Vec<SignerPair> = (0..count).map(|_| SignerPair::create()).collect();
+        let mut batch = BatchVerifier::with_workers(workers);
+        for (i, signer) in signers.iter().enumerate() {
+            let msg = vec![i as u8; 32];
+            batch.push(signer.public_key(), &msg, signer.sign_any(&msg));
+        }
+        (batch, signers)
+    }
+
+    #[test]
+    fn test_batch_verify() {
+        for workers in [1, 4] {
+            let (batch, _) = signed_batch(workers, 16);
+            let result = batch.verify();
+            assert!(result.all_valid());
+            assert!(result.failed().is_empty());
+        }
+        assert!(BatchVerifier::new().verify().all_valid());
+    }
+
+    #[test]
+    fn test_batch_reports_failures() {
+        let (mut batch, signers) = signed_batch(4, 8);
+
+        // Wrong message and wrong key
+        let sig = signers[0].sign_any(b"cesium");
+        let bad_msg = batch.push(signers[0].public_key(), b"other", sig.clone());
+        let bad_key = batch.push(signers[1].public_key(), b"cesium", sig);
+
+        let result = batch.verify();
+        assert!(!result.all_valid());
+        assert_eq!(result.failed(), vec![bad_msg, bad_key]);
+        assert!(result.is_valid(0));
+        assert!(!result.is_valid(batch.len()));
+    }
+}
@@ -0,0 +1,435 @@
+use sha3::{Digest, Sha3_256};
+
+use crate::{
+    errors::CryptoError,
+    scheme::{AnyPublicKey, AnySignature, Scheme, Signer},
+};
+
+// Randomness beacon for leader selection. The seed of epoch n + 1 is the
+// hash of the seed of epoch n and the signatures of the validators over it,
+// so anyone holding the validator keys can recompute and check it.
+//
+// Signatures are not unique for every scheme (ML-DSA signing is hedged), a
+// validator could sign again until it likes the seed. A round therefore has
+// a commit phase, every validator publishes the hash of its contribution
+// before any contribution is revealed. Withholding a reveal is the only
+// influence left, and a seed always needs `threshold` reveals.
+//
+// Beacon layout, integers are little endian:
+// EPOCH (8) | SEED (32)
+// Contribution layout:
+// SIGNER (tagged public key) | SIGNATURE (tagged)
+
+pub const SEED_BYTE_LEN: usize = 32;
+pub const BEACON_BYTE_LEN: usize = 8 + SEED_BYTE_LEN;
+
+const MESSAGE_DOMAIN: &[u8] = b"cesium-beacon";
+const SEED_DOMAIN: &[u8] = b"cesium-beacon-seed";
+const COMMIT_DOMAIN: &[u8] = b"cesium-beacon-commit";
+const SELECT_DOMAIN: &[u8] = b"cesium-beacon-select";
+
+pub type Seed = [u8; SEED_BYTE_LEN];
+pub type Commitment = [u8; 32];
+
+macro_rules! bounds_check {
+    ($bytes:expr, $len:expr) => {
+        if $bytes.len() < $len {
+            return Err(CryptoError::InvalidBeaconContribution);
+        }
+    };
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Beacon {
+    epoch: u64,
+    seed: Seed,
+}
+
+/// A validator's signature over the current beacon, it feeds the next seed
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Contribution {
+    signer: AnyPublicKey,
+    signature: AnySignature,
+}
+
+fn check_policy(validators: &[AnyPublicKey], threshold: usize) -> Result<(), CryptoError> {
+    if threshold == 0 || threshold > validators.len() {
+        return Err(CryptoError::InvalidBeaconThreshold);
+    }
+
+    for (i, validator) in validators.iter().enumerate() {
+        if validators[..i].contains(validator) {
+            return Err(CryptoError::InvalidBeacon);
+        }
+    }
+    Ok(())
+}
+
+fn validator_index(
+    validators: &[AnyPublicKey],
+    signer: &AnyPublicKey,
+) -> Result<usize, CryptoError> {
+    validators
+        .iter()
+        .position(|validator| validator == signer)
+        .ok_or(CryptoError::InvalidBeaconContribution)
+}
+
+impl Beacon {
+    /// The first beacon, its seed has to be agreed on out of band
+    pub fn genesis(seed: Seed) -> Self {
+        Self { epoch: 0, seed }
+    }
+
+    pub fn epoch(&self) -> u64 {
+        self.epoch
+    }
+
+    pub fn seed(&self) -> &Seed {
+        &self.seed
+    }
+
+    /// The message validators sign to contribute to the next epoch
+    pub fn message(&self) -> Vec<u8> {
+        let mut message = Vec::with_capacity(MESSAGE_DOMAIN.len() + BEACON_BYTE_LEN);
+        message.extend_from_slice(MESSAGE_DOMAIN);
+        message.extend_from_slice(&self.to_bytes());
+        message
+    }
+
+    pub fn contribute(&self, signer: &impl Signer) -> Contribution {
+        Contribution {
+            signer: signer.public_key(),
+            signature: signer.sign_any(&self.message()),
+        }
+    }
+
+    /// Derives the next beacon from the revealed contributions. Every
+    /// contribution has to come from a different validator and sign this
+    /// beacon, the order they are passed in does not matter.
+    pub fn next(
+        &self,
+        validators: &[AnyPublicKey],
+        threshold: usize,
+        contributions: &[Contribution],
+    ) -> Result<Beacon, CryptoError> {
+        check_policy(validators, threshold)?;
+
+        let message = self.message();
+        let mut revealed: Vec<Option<&Contribution>> = vec![None; validators.len()];
+        for contribution in contributions {
+            let index = validator_index(validators, &contribution.signer)?;
+            if revealed[index].is_some() || !contribution.verify(&message)? {
+                return Err(CryptoError::InvalidBeaconContribution);
+            }
+            revealed[index] = Some(contribution);
+        }
+
+        if contributions.len() < threshold {
+            return Err(CryptoError::InsufficientContributions);
+        }
+
+        let epoch = self.epoch + 1;
+        let mut hasher = Sha3_256::new();
+        hasher.update(SEED_DOMAIN);
+        hasher.update(epoch.to_le_bytes());
+        hasher.update(self.seed);
+        for (index, contribution) in revealed.iter().enumerate() {
+            if let Some(contribution) = contribution {
+                hasher.update((index as u32).to_le_bytes());
+                hasher.update(Sha3_256::digest(contribution.signature.to_bytes()));
+            }
+        }
+
+        Ok(Beacon {
+            epoch,
+            seed: hasher.finalize().into(),
+        })
+    }
+
+    /// Picks the leader of `slot` among the validators, each with a chance
+    /// proportional to its stake. Returns None when there is no stake.
+    pub fn select(&self, slot: u64, stakes: &[u128]) -> Option<usize> {
+        let total = stakes
+            .iter()
+            .try_fold(0u128, |total, stake| total.checked_add(*stake))?;
+        if total == 0 {
+            return None;
+        }
+
+        // Draws above the last multiple of the total are thrown away, so
+        // every ticket is equally likely
+        let zone = u128::MAX - (u128::MAX - total + 1) % total;
+        let mut counter = 0u32;
+        let ticket = loop {
+            let mut hasher = Sha3_256::new();
+            hasher.update(SELECT_DOMAIN);
+            hasher.update(self.seed);
+            hasher.update(slot.to_le_bytes());
+            hasher.update(counter.to_le_bytes());
+            let hash = hasher.finalize();
+
+            let draw = u128::from_le_bytes(hash[..16].try_into().unwrap());
+            if draw <= zone {
+                break draw % total;
+            }
+            counter += 1;
+        };
+
+        let mut cumulative = 0u128;
+        stakes.iter().position(|stake| {
+            cumulative += stake;
+            ticket < cumulative
+        })
+    }
+
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let mut bytes = Vec::with_capacity(BEACON_BYTE_LEN);
+        bytes.extend_from_slice(&self.epoch.to_le_bytes());
+        bytes.extend_from_slice(&self.seed);
+        bytes
+    }
+
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self, CryptoError> {
+        if bytes.len() != BEACON_BYTE_LEN {
+            return Err(CryptoError::InvalidBeacon);
+        }
+
+        Ok(Self {
+            epoch: u64::from_le_bytes(bytes[..8].try_into().unwrap()),
+            seed: bytes[8..].try_into().unwrap(),
+        })
+    }
+}
+
+impl Contribution {
+    pub fn signer(&self) -> &AnyPublicKey {
+        &self.signer
+    }
+
+    pub fn signature(&self) -> &AnySignature {
+        &self.signature
+    }
+
+    /// Published in the commit phase, before the contribution itself
+    pub fn commitment(&self) -> Commitment {
+        let mut hasher = Sha3_256::new();
+        hasher.update(COMMIT_DOMAIN);
+        hasher.update(self.signer.to_bytes());
+        hasher.update(self.signature.to_bytes());
+        hasher.finalize().into()
+    }
+
+    fn verify(&self, message: &[u8]) -> Result<bool, CryptoError> {
+        match self.signer.verify(message, &self.signature) {
+            Err(CryptoError::SchemeMismatch) => Ok(false),
+            result => result,
+        }
+    }
+
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let mut bytes = self.signer.to_bytes();
+        bytes.extend_from_slice(&self.signature.to_bytes());
+        bytes
+    }
+
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self, CryptoError> {
+        bounds_check!(bytes, 1);
+        let scheme = Scheme::from_u8(bytes[0]).ok_or(CryptoError::UnsupportedScheme(bytes[0]))?;
+        let signer_len = 1 + scheme.pub_byte_len()?;
+
+        bounds_check!(bytes, signer_len);
+        Ok(Self {
+            signer: AnyPublicKey::from_bytes(&bytes[..signer_len])?,
+            signature: AnySignature::from_bytes(&bytes[signer_len..])?,
+        })
+    }
+}
+
+/// Collects the commitments and then the contributions of one epoch
+pub struct BeaconRound {
+    beacon: Beacon,
+    validators: Vec<AnyPublicKey>,
+    threshold: usize,
+    commitments: Vec<Option<Commitment>>,
+    reveals: Vec<Option<Contribution>>,
+}
+
+impl BeaconRound {
+    pub fn new(
+        beacon: &Beacon,
+        validators: Vec<AnyPublicKey>,
+        threshold: usize,
+    ) -> Result<Self, CryptoError> {
+        check_policy(&validators, threshold)?;
+
+        Ok(Self {
+            beacon: *beacon,
+            commitments: vec![None; validators.len()],
+            reveals: vec![None; validators.len()],
+            validators,
+            threshold,
+        })
+    }
+
+    pub fn beacon(&self) -> &Beacon {
+        &self.beacon
+    }
+
+    /// Commitments are only taken until the first contribution is revealed
+    pub fn commit(
+        &mut self,
+        signer: &AnyPublicKey,
+        commitment: Commitment,
+    ) -> Result<(), CryptoError> {
+        let index = validator_index(&self.validators, signer)?;
+        if self.commitments[index].is_some() || self.reveals.iter().any(Option::is_some) {
+            return Err(CryptoError::InvalidBeaconContribution);
+        }
+
+        self.commitments[index] = Some(commitment);
+        Ok(())
+    }
+
+    /// The contribution has to open the validator's commitment
+    pub fn reveal(&mut self, contribution: Contribution) -> Result<(), CryptoError> {
+        let index = validator_index(&self.validators, &contribution.signer)?;
+        if self.commitments[index] != Some(contribution.commitment())
+            || self.reveals[index].is_some()
+            || !contribution.verify(&self.beacon.message())?
+        {
+            return Err(CryptoError::InvalidBeaconContribution);
+        }
+
+        self.reveals[index] = Some(contribution);
+        Ok(())
+    }
+
+    pub fn finish(&self) -> Result<Beacon, CryptoError> {
+        let contributions: Vec<Contribution> = self.reveals.iter().flatten().cloned().collect();
+        self.beacon
+            .next(&self.validators, self.threshold, &contributions)
+    }
+}
+
+#[cfg(all(test, feature = "mldsa"))]
+mod tests {
+    use crate::mldsa::keypair::SignerPair;
+
+    use super::*;
+
+    fn validators(count: usize) -> (Vec<SignerPair>, Vec<AnyPublicKey>) {
+        let signers: Vec<SignerPair> = (0..count).map(|_| SignerPair::create()).collect();
+        let keys = signers.iter().map(|signer| signer.public_key()).collect();
+        (signers, keys)
+    }
+
+    #[test]
+    fn test_beacon_next() {
+        let (signers, keys) = validators(3);
+        let beacon = Beacon::genesis([7; SEED_BYTE_LEN]);
+        let contributions: Vec<Contribution> = signers
+            .iter()
+            .map(|signer| beacon.contribute(signer))
+            .collect();
+
+        let next = beacon.next(&keys, 2, &contributions).unwrap();
+        assert_eq!(next.epoch(), 1);
+        assert_ne!(next.seed(), beacon.seed());
+
+        // Anyone with the keys gets the same seed, whatever the order
+        let reversed: Vec<Contribution> = contributions.iter().rev().cloned().collect();
+        assert_eq!(beacon.next(&keys, 2, &reversed).unwrap(), next);
+
+        let partial = beacon.next(&keys, 2, &contributions[..2]).unwrap();
+        assert_ne!(partial.seed(), next.seed());
+        assert!(matches!(
+            beacon.next(&keys, 2, &contributions[..1]),
+            Err(CryptoError::InsufficientContributions)
+        ));
+        assert!(matches!(
+            beacon.next(&keys, 4, &contributions),
+            Err(CryptoError::InvalidBeaconThreshold)
+        ));
+
+        // Contributions have to sign this beacon and come from a validator once
+        let stale = next.contribute(&signers[0]);
+        let outsider = beacon.contribute(&SignerPair::create());
+        let duplicate = [contributions[0].clone(), contributions[0].clone()];
+        for contributions in [&[stale][..], &[outsider][..], &duplicate[..]] {
+            assert!(matches!(
+                beacon.next(&keys, 1, contributions),
+                Err(CryptoError::InvalidBeaconContribution)
+            ));
+        }
+    }
+
+    #[test]
+    fn test_beacon_round() {
+        let (signers, keys) = validators(3);
+        let beacon = Beacon::genesis([1; SEED_BYTE_LEN]);
+        let contributions: Vec<Contribution> = signers
+            .iter()
+            .map(|signer| beacon.contribute(signer))
+            .collect();
+
+        let mut round = BeaconRound::new(&beacon, keys.clone(), 2).unwrap();
+        for contribution in &contributions[..2] {
+            round
+                .commit(contribution.signer(), contribution.commitment())
+                .unwrap();
+        }
+
+        // A second signature does not open the commitment
+        let resigned = beacon.contribute(&signers[0]);
+        assert!(round.reveal(resigned).is_err());
+        round.reveal(contributions[0].clone()).unwrap();
+
+        // Once contributions are revealed the commit phase is over
+        assert!(round
+            .commit(contributions[2].signer(), contributions[2].commitment())
+            .is_err());
+        assert!(round.reveal(contributions[2].clone()).is_err());
+        assert!(matches!(
+            round.finish(),
+            Err(CryptoError::InsufficientContributions)
+        ));
+
+        round.reveal(contributions[1].clone()).unwrap();
+        assert_eq!(
+            round.finish().unwrap(),
+            beacon.next(&keys, 2, &contributions[..2]).unwrap()
+        );
+    }
+
+    #[test]
+    fn test_beacon_select() {
+        let beacon = Beacon::genesis([3; SEED_BYTE_LEN]);
+        let stakes = [0, 1, 3];
+
+        let mut picks = [0; 3];
+        for slot in 0..400 {
+            picks[beacon.select(slot, &stakes).unwrap()] += 1;
+        }
+        assert_eq!(picks[0], 0);
+        assert!(picks[2] > picks[1]);
+
+        assert_eq!(beacon.select(0, &[0, 0]), None);
+        assert_eq!(beacon.select(0, &[u128::MAX, 1]), None);
+        assert_eq!(beacon.select(0, &[u128::MAX]), Some(0));
+    }
+
+    #[test]
+    fn test_beacon_bytes() {
+        let signer = SignerPair::create();
+        let beacon = Beacon::genesis([9; SEED_BYTE_LEN]);
+        assert_eq!(Beacon::from_bytes(&beacon.to_bytes()).unwrap(), beacon);
+        assert!(Beacon::from_bytes(&beacon.to_bytes()[1..]).is_err());
+
+        let contribution = beacon.contribute(&signer);
+        let bytes = contribution.to_bytes();
+        assert_eq!(Contribution::from_bytes(&bytes).unwrap(), contribution);
+        assert!(Contribution::from_bytes(&bytes[..bytes.len() - 1]).is_err());
+        assert!(Contribution::from_bytes(&bytes[..10]).is_err());
+    }
+}
@@ -48,6 +48,11 @@ impl FieldElement {
         result
     }
 
+    /// Fermat inverse, zero has none and maps to zero
+    pub fn inverse(&self) -> Self {
+        self.exp(FIELD_PRIME - 2)
+    }
+
     pub fn random(rng: &mut impl rand::RngCore) -> Self {
         Self::new(rng.next_u64())
     }
@@ -0,0 +1,57 @@
+use curve25519_dalek::{
+    constants::RISTRETTO_BASEPOINT_POINT, ristretto::RistrettoPoint, scalar::Scalar,
+    traits::MultiscalarMul,
+};
+use sha3::{Digest, Sha3_512};
+
+// All generators besides the base point are hashed to the curve, so nobody
+// knows the discrete log relation between them
+
+fn hash_to_point(label: &[u8], index: u64) -> RistrettoPoint {
+    let mut hasher = Sha3_512::new();
+    hasher.update(label);
+    hasher.update(index.to_le_bytes());
+    RistrettoPoint::from_uniform_bytes(&hasher.finalize().into())
+}
+
+/// Generators of a Pedersen commitment `v * B + r * B_blinding`
+#[derive(Clone, Copy, Debug)]
+pub struct PedersenGens {
+    pub b: RistrettoPoint,
+    pub b_blinding: RistrettoPoint,
+}
+
+impl Default for PedersenGens {
+    fn default() -> Self {
+        Self {
+            b: RISTRETTO_BASEPOINT_POINT,
+            b_blinding: hash_to_point(b"cesium-pedersen-blinding", 0),
+        }
+    }
+}
+
+impl PedersenGens {
+    pub fn commit(&self, value: Scalar, blinding: Scalar) -> RistrettoPoint {
+        RistrettoPoint::multiscalar_mul([value, blinding], [self.b, self.b_blinding])
+    }
+}
+
+/// Vector generators for the bit commitments, one G and H per proven bit
+#[derive(Clone, Debug)]
+pub struct BulletproofGens {
+    pub g_vec: Vec<RistrettoPoint>,
+    pub h_vec: Vec<RistrettoPoint>,
+}
+
+impl BulletproofGens {
+    pub fn new(size: usize) -> Self {
+        Self {
+            g_vec: (0..size as u64)
+                .map(|i| hash_to_point(b"cesium-bulletproofs-g", i))
+                .collect(),
+            h_vec: (0..size as u64)
+                .map(|i| hash_to_point(b"cesium-bulletproofs-h", i))
+                .collect(),
+        }
+    }
+}
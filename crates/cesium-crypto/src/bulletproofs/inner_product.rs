@@ -0,0 +1,145 @@
+use curve25519_dalek::{
+    ristretto::{CompressedRistretto, RistrettoPoint},
+    scalar::Scalar,
+    traits::{MultiscalarMul, VartimeMultiscalarMul},
+};
+
+use super::transcript::Transcript;
+
+pub fn inner_product(a: &[Scalar], b: &[Scalar]) -> Scalar {
+    a.iter().zip(b).map(|(a, b)| a * b).sum()
+}
+
+/// Proof of knowledge of vectors a and b with
+/// P = <a, G> + <b, H> + <a, b> * Q, halving the vectors every round
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct InnerProductProof {
+    pub l_vec: Vec<CompressedRistretto>,
+    pub r_vec: Vec<CompressedRistretto>,
+    pub a: Scalar,
+    pub b: Scalar,
+}
+
+impl InnerProductProof {
+    /// All vectors must have the same power of two length
+    pub fn create(
+        transcript: &mut Transcript,
+        q: &RistrettoPoint,
+        mut g: Vec<RistrettoPoint>,
+        mut h: Vec<RistrettoPoint>,
+        mut a: Vec<Scalar>,
+        mut b: Vec<Scalar>,
+    ) -> Self {
+        let mut n = g.len();
+        let mut l_vec = Vec::with_capacity(n.trailing_zeros() as usize);
+        let mut r_vec = Vec::with_capacity(n.trailing_zeros() as usize);
+
+        while n > 1 {
+            n /= 2;
+            let (a_lo, a_hi) = a.split_at(n);
+            let (b_lo, b_hi) = b.split_at(n);
+            let (g_lo, g_hi) = g.split_at(n);
+            let (h_lo, h_hi) = h.split_at(n);
+
+            let c_l = inner_product(a_lo, b_hi);
+            let c_r = inner_product(a_hi, b_lo);
+
+            let l = RistrettoPoint::multiscalar_mul(
+                a_lo.iter().chain(b_hi).chain([&c_l]),
+                g_hi.iter().chain(h_lo).chain([q]),
+            )
+            .compress();
+            let r = RistrettoPoint::multiscalar_mul(
+                a_hi.iter().chain(b_lo).chain([&c_r]),
+                g_lo.iter().chain(h_hi).chain([q]),
+            )
+            .compress();
+
+            transcript.append_point(b"L", &l);
+            transcript.append_point(b"R", &r);
+            l_vec.push(l);
+            r_vec.push(r);
+
+            let u = transcript.challenge_scalar(b"u");
+            let u_inv = u.invert();
+
+            a = (0..n).map(|i| a_lo[i] * u + a_hi[i] * u_inv).collect();
+            b = (0..n).map(|i| b_lo[i] * u_inv + b_hi[i] * u).collect();
+            g = (0..n).map(|i| g_lo[i] * u_inv + g_hi[i] * u).collect();
+            h = (0..n).map(|i| h_lo[i] * u + h_hi[i] * u_inv).collect();
+        }
+
+        Self {
+            l_vec,
+            r_vec,
+            a: a[0],
+            b: b[0],
+        }
+    }
+
+    pub fn verify(
+        &self,
+        transcript: &mut Transcript,
+        q: &RistrettoPoint,
+        p: &RistrettoPoint,
+        g: &[RistrettoPoint],
+        h: &[RistrettoPoint],
+    ) -> bool {
+        let rounds = self.l_vec.len();
+        let n = g.len();
+        if self.r_vec.len() != rounds || h.len() != n || n != 1 << rounds {
+            return false;
+        }
+
+        let mut u_sq = Vec::with_capacity(rounds);
+        let mut u_inv_sq = Vec::with_capacity(rounds);
+        let mut s_first = Scalar::ONE;
+        for (l, r) in self.l_vec.iter().zip(&self.r_vec) {
+            transcript.append_point(b"L", l);
+            transcript.append_point(b"R", r);
+            let u = transcript.challenge_scalar(b"u");
+            if u == Scalar::ZERO {
+                return false;
+            }
+            let u_inv = u.invert();
+            u_sq.push(u * u);
+            u_inv_sq.push(u_inv * u_inv);
+            s_first *= u_inv;
+        }
+
+        // Folding the generators leaves G_i scaled by s_i, the product of
+        // u or u^-1 depending on the bits of i (most significant bit first)
+        let mut s = Vec::with_capacity(n);
+        s.push(s_first);
+        for i in 1..n {
+            let lg = (usize::BITS - 1 - i.leading_zeros()) as usize;
+            let k = 1 << lg;
+            s.push(s[i - k] * u_sq[rounds - 1 - lg]);
+        }
+
+        let points = match self
+            .l_vec
+            .iter()
+            .chain(&self.r_vec)
+            .map(|point| point.decompress())
+            .collect::<Option<Vec<RistrettoPoint>>>()
+        {
+            Some(points) => points,
+            None => return false,
+        };
+
+        // a * <s, G> + b * <s^-1, H> + ab * Q - sum(u^2 L + u^-2 R) == P
+        let g_scalars = s.iter().map(|s_i| self.a * s_i);
+        let h_scalars = s.iter().rev().map(|s_inv_i| self.b * s_inv_i);
+        let lr_scalars = u_sq.iter().chain(&u_inv_sq).map(|x| -x);
+        let expected = RistrettoPoint::vartime_multiscalar_mul(
+            g_scalars
+                .chain(h_scalars)
+                .chain([self.a * self.b])
+                .chain(lr_scalars),
+            g.iter().chain(h).chain([q]).chain(&points),
+        );
+
+        expected == *p
+    }
+}
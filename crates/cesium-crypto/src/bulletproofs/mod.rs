@@ -1,11 +1,45 @@
 pub mod fields;
+pub mod generators;
+pub mod inner_product;
 pub mod proof;
 pub mod prover;
+pub mod transcript;
 pub mod verifier;
 
+use crate::errors::CryptoError;
+
+use proof::Commitment;
+use transcript::Transcript;
+
+pub const SUPPORTED_BIT_LENGTHS: [usize; 4] = [8, 16, 32, 64];
+pub const MAX_AGGREGATION_SIZE: usize = 16;
+
+// Aggregated proofs pad the bit vectors of all values into one, so the
+// number of values has to be a power of two
+fn check_params(bit_length: usize, count: usize) -> Result<(), CryptoError> {
+    if !SUPPORTED_BIT_LENGTHS.contains(&bit_length) {
+        return Err(CryptoError::RangeProofInvalidBitLength);
+    }
+    if count == 0 || count > MAX_AGGREGATION_SIZE || !count.is_power_of_two() {
+        return Err(CryptoError::RangeProofInvalidAggregation);
+    }
+    Ok(())
+}
+
+fn range_transcript(bit_length: usize, commitments: &[Commitment]) -> Transcript {
+    let mut transcript = Transcript::new(b"cesium-rangeproof");
+    transcript.append_u64(b"n", bit_length as u64);
+    transcript.append_u64(b"m", commitments.len() as u64);
+    for commitment in commitments {
+        transcript.append_point(b"V", &commitment.point);
+    }
+    transcript
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
+    use proof::RangeProof;
     use prover::Prover;
     use verifier::Verifier;
 
@@ -15,10 +49,10 @@ mod tests {
         let bit_length = 8;
 
         let prover = Prover::new(value, bit_length);
-        let proof = prover.prove();
+        let proof = prover.prove().unwrap();
 
         let verifier = Verifier::new(bit_length);
-        let is_valid = verifier.verify(&proof);
+        let is_valid = verifier.verify(&proof, &prover.commitments());
 
         assert!(is_valid);
     }
@@ -29,15 +63,88 @@ mod tests {
         let bit_length = 8;
 
         let prover: Prover = Prover::new(value, bit_length);
-        let mut proof = prover.prove();
+        let mut proof = prover.prove().unwrap();
+        let commitments = prover.commitments();
 
         // Modify the proof to make it invalid
-        assert_ne!(proof.challenge[0], 0);
-        proof.challenge[0] = 0;
+        proof.t_x += curve25519_dalek::scalar::Scalar::ONE;
 
         let verifier = Verifier::new(bit_length);
-        let is_valid = verifier.verify(&proof);
+        let is_valid = verifier.verify(&proof, &commitments);
 
         assert!(!is_valid);
     }
+
+    #[test]
+    fn test_range_proof_bounds() {
+        for (value, bit_length) in [(0, 8), (255, 8), (u32::MAX as u64, 32), (u64::MAX, 64)] {
+            let prover = Prover::new(value, bit_length);
+            let proof = prover.prove().unwrap();
+            assert!(Verifier::new(bit_length).verify(&proof, &prover.commitments()));
+        }
+
+        assert!(matches!(
+            Prover::new(256, 8).prove(),
+            Err(CryptoError::RangeProofValueOutOfRange)
+        ));
+        assert!(matches!(
+            Prover::new(1, 12).prove(),
+            Err(CryptoError::RangeProofInvalidBitLength)
+        ));
+    }
+
+    #[test]
+    fn test_wrong_commitment() {
+        let prover = Prover::new(42, 16);
+        let proof = prover.prove().unwrap();
+
+        let other = Commitment::new(42, &prover::random_scalar(&mut rand::thread_rng()));
+        assert!(!Verifier::new(16).verify(&proof, &[other]));
+        // The proof is bound to its bit length
+        assert!(!Verifier::new(32).verify(&proof, &prover.commitments()));
+    }
+
+    #[test]
+    fn test_aggregated_range_proof() {
+        let values = vec![1, 1000, 65535, 7];
+        let prover = Prover::aggregated(values, 16);
+        let proof = prover.prove().unwrap();
+        let commitments = prover.commitments();
+        let verifier = Verifier::new(16);
+        assert!(verifier.verify(&proof, &commitments));
+
+        // Commitments must be in the proven order
+        let mut swapped = commitments.clone();
+        swapped.swap(0, 1);
+        assert!(!verifier.verify(&proof, &swapped));
+        assert!(!verifier.verify(&proof, &commitments[..2]));
+
+        assert!(matches!(
+            Prover::aggregated(vec![1, 2, 3], 16).prove(),
+            Err(CryptoError::RangeProofInvalidAggregation)
+        ));
+    }
+
+    #[test]
+    fn test_range_proof_bytes() {
+        let prover = Prover::aggregated(vec![5, 6], 32);
+        let proof = prover.prove().unwrap();
+        let bytes = proof.to_bytes();
+
+        let proof2 = RangeProof::from_bytes(&bytes).unwrap();
+        assert_eq!(proof2, proof);
+        assert!(Verifier::new(32).verify(&proof2, &prover.commitments()));
+
+        assert!(RangeProof::from_bytes(&bytes[..bytes.len() - 1]).is_err());
+
+        // Flipping any byte invalidates the proof
+        for i in [0, 100, 200, bytes.len() - 1] {
+            let mut tampered = bytes.clone();
+            tampered[i] ^= 1;
+            let valid = RangeProof::from_bytes(&tampered)
+                .map(|proof| Verifier::new(32).verify(&proof, &prover.commitments()))
+                .unwrap_or(false);
+            assert!(!valid);
+        }
+    }
 }
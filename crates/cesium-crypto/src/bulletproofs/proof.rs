@@ -1,21 +1,128 @@
-/// Commitment to a value using a polynomial
-#[derive(Clone, Debug)]
+use curve25519_dalek::{ristretto::CompressedRistretto, scalar::Scalar};
+
+use crate::errors::CryptoError;
+
+use super::{generators::PedersenGens, inner_product::InnerProductProof};
+
+pub const POINT_BYTE_LEN: usize = 32;
+pub const SCALAR_BYTE_LEN: usize = 32;
+
+// A, S, T1, T2, t_x, t_x_blinding, e_blinding and the final a and b of the
+// inner product argument, followed by one L and R per round
+const FIXED_BYTE_LEN: usize = 4 * POINT_BYTE_LEN + 5 * SCALAR_BYTE_LEN;
+
+/// Pedersen commitment `v * B + r * B_blinding` to a value, it hides the
+/// value and binds the committer to it
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
 pub struct Commitment {
-    pub commitment: Vec<u8>,
-    pub randomness: Vec<u8>,
+    pub point: CompressedRistretto,
+}
+
+impl Commitment {
+    pub fn new(value: u64, blinding: &Scalar) -> Self {
+        let point = PedersenGens::default().commit(Scalar::from(value), *blinding);
+        Self {
+            point: point.compress(),
+        }
+    }
+
+    pub fn to_bytes(&self) -> [u8; POINT_BYTE_LEN] {
+        self.point.to_bytes()
+    }
+
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self, CryptoError> {
+        let point =
+            CompressedRistretto::from_slice(bytes).map_err(|_| CryptoError::ZkInvalidCommitment)?;
+        point.decompress().ok_or(CryptoError::ZkInvalidCommitment)?;
+        Ok(Self { point })
+    }
 }
 
 /// Range proof structure
-#[derive(Clone, Debug)]
+#[derive(Clone, Debug, PartialEq, Eq)]
 pub struct RangeProof {
-    // Commitment to the bits of the value
-    pub a: Vec<u8>,
-    // Commitment to the blinding factors
-    pub s: Vec<u8>,
-    // Vector of L values
-    pub l: Vec<Vec<u8>>,
-    // Vector of R values
-    pub r: Vec<Vec<u8>>,
-    // Challenge hash
-    pub challenge: Vec<u8>,
+    // Commitment to the bits of the values
+    pub a: CompressedRistretto,
+    // Commitment to the blinding vectors
+    pub s: CompressedRistretto,
+    // Commitments to the coefficients of t(x)
+    pub t1: CompressedRistretto,
+    pub t2: CompressedRistretto,
+    // Evaluation of t(x) at the challenge and its blinding
+    pub t_x: Scalar,
+    pub t_x_blinding: Scalar,
+    // Blinding of A + xS
+    pub e_blinding: Scalar,
+    // Proof that t_x is the inner product of l(x) and r(x)
+    pub ipp: InnerProductProof,
+}
+
+impl RangeProof {
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let rounds = self.ipp.l_vec.len();
+        let mut bytes = Vec::with_capacity(FIXED_BYTE_LEN + 2 * rounds * POINT_BYTE_LEN);
+        for point in [&self.a, &self.s, &self.t1, &self.t2] {
+            bytes.extend_from_slice(point.as_bytes());
+        }
+        for scalar in [
+            &self.t_x,
+            &self.t_x_blinding,
+            &self.e_blinding,
+            &self.ipp.a,
+            &self.ipp.b,
+        ] {
+            bytes.extend_from_slice(scalar.as_bytes());
+        }
+        for (l, r) in self.ipp.l_vec.iter().zip(&self.ipp.r_vec) {
+            bytes.extend_from_slice(l.as_bytes());
+            bytes.extend_from_slice(r.as_bytes());
+        }
+        bytes
+    }
+
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self, CryptoError> {
+        if bytes.len() < FIXED_BYTE_LEN
+            || !(bytes.len() - FIXED_BYTE_LEN).is_multiple_of(2 * POINT_BYTE_LEN)
+        {
+            return Err(CryptoError::RangeProofInvalidFormat);
+        }
+
+        // Points are only decompressed by the verifier
+        let point = |i: usize| {
+            CompressedRistretto::from_slice(&bytes[i * POINT_BYTE_LEN..(i + 1) * POINT_BYTE_LEN])
+                .unwrap()
+        };
+        // Only canonical scalars are accepted, so a proof has a single encoding
+        let scalar = |i: usize| {
+            let offset = 4 * POINT_BYTE_LEN + i * SCALAR_BYTE_LEN;
+            let bytes: [u8; SCALAR_BYTE_LEN] =
+                bytes[offset..offset + SCALAR_BYTE_LEN].try_into().unwrap();
+            Option::<Scalar>::from(Scalar::from_canonical_bytes(bytes))
+                .ok_or(CryptoError::RangeProofInvalidFormat)
+        };
+
+        let mut l_vec = Vec::new();
+        let mut r_vec = Vec::new();
+        for pair in bytes[FIXED_BYTE_LEN..].chunks_exact(2 * POINT_BYTE_LEN) {
+            let (l, r) = pair.split_at(POINT_BYTE_LEN);
+            l_vec.push(CompressedRistretto::from_slice(l).unwrap());
+            r_vec.push(CompressedRistretto::from_slice(r).unwrap());
+        }
+
+        Ok(Self {
+            a: point(0),
+            s: point(1),
+            t1: point(2),
+            t2: point(3),
+            t_x: scalar(0)?,
+            t_x_blinding: scalar(1)?,
+            e_blinding: scalar(2)?,
+            ipp: InnerProductProof {
+                l_vec,
+                r_vec,
+                a: scalar(3)?,
+                b: scalar(4)?,
+            },
+        })
+    }
 }
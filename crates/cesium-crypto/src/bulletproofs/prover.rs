@@ -1,99 +1,213 @@
+use curve25519_dalek::{ristretto::RistrettoPoint, scalar::Scalar, traits::MultiscalarMul};
 use rand::{thread_rng, RngCore};
-use sha3::{Digest, Sha3_384};
+
+use crate::errors::CryptoError;
 
 use super::{
-    fields::FieldElement,
+    check_params,
+    generators::{BulletproofGens, PedersenGens},
+    inner_product::{inner_product, InnerProductProof},
     proof::{Commitment, RangeProof},
+    range_transcript,
 };
 
-/// Prover for range proofs
+pub fn random_scalar(rng: &mut impl RngCore) -> Scalar {
+    let mut bytes = [0u8; 64];
+    rng.fill_bytes(&mut bytes);
+    Scalar::from_bytes_mod_order_wide(&bytes)
+}
+
+/// Prover for range proofs, proves that each committed value fits in
+/// `bit_length` bits. Several values are proven at once by a single
+/// aggregated proof.
 pub struct Prover {
-    value: u64,
+    values: Vec<u64>,
+    blindings: Vec<Scalar>,
     bit_length: usize,
 }
 
 impl Prover {
     pub fn new(value: u64, bit_length: usize) -> Self {
-        Self { value, bit_length }
+        Self::aggregated(vec![value], bit_length)
     }
 
-    pub fn prove(&self) -> RangeProof {
+    pub fn aggregated(values: Vec<u64>, bit_length: usize) -> Self {
         let mut rng = thread_rng();
+        let blindings = values.iter().map(|_| random_scalar(&mut rng)).collect();
+        Self::with_blindings(values, blindings, bit_length)
+    }
 
-        // Convert value to binary
-        let bits: Vec<bool> = (0..self.bit_length)
-            .map(|i| ((self.value >> i) & 1) == 1)
-            .collect();
-
-        // Generate randomness for commitments
-        let mut a_rand = vec![0u8; 32];
-        rng.fill_bytes(&mut a_rand);
-
-        let mut s_rand = vec![0u8; 32];
-        rng.fill_bytes(&mut s_rand);
-
-        // Commit to bits
-        let mut a_value = FieldElement::new(0);
-        for (i, &bit) in bits.iter().enumerate() {
-            if bit {
-                a_value = a_value.add(&FieldElement::new(1 << i));
-            }
+    /// Use known blindings, e.g. to balance the commitments of a transfer
+    pub fn with_blindings(values: Vec<u64>, blindings: Vec<Scalar>, bit_length: usize) -> Self {
+        Self {
+            values,
+            blindings,
+            bit_length,
         }
-        let a_comm = self.commit(&a_value, &a_rand);
+    }
 
-        // Generate mask commitment
-        let s_value = FieldElement::random(&mut rng);
-        let s_comm = self.commit(&s_value, &s_rand);
+    pub fn blindings(&self) -> &[Scalar] {
+        &self.blindings
+    }
 
-        // Generate l_val and r_val values for the inner product argument
-        let mut l_val = Vec::new();
-        let mut r_val = Vec::new();
+    pub fn commitments(&self) -> Vec<Commitment> {
+        self.values
+            .iter()
+            .zip(&self.blindings)
+            .map(|(value, blinding)| Commitment::new(*value, blinding))
+            .collect()
+    }
 
-        // For each round of the protocol
-        for _ in 0..self.bit_length.trailing_zeros() {
-            let mut l_rand = vec![0u8; 32];
-            rng.fill_bytes(&mut l_rand);
-            let mut r_rand = vec![0u8; 32];
-            rng.fill_bytes(&mut r_rand);
+    pub fn prove(&self) -> Result<RangeProof, CryptoError> {
+        if self.values.len() != self.blindings.len() {
+            return Err(CryptoError::RangeProofInvalidAggregation);
+        }
+        check_params(self.bit_length, self.values.len())?;
+        if self.bit_length < 64 && self.values.iter().any(|v| v >> self.bit_length != 0) {
+            return Err(CryptoError::RangeProofValueOutOfRange);
+        }
 
-            let l_value = FieldElement::random(&mut rng);
-            let r_value = FieldElement::random(&mut rng);
+        Ok(self.create_proof())
+    }
 
-            let l_comm = self.commit(&l_value, &l_rand);
-            let r_comm = self.commit(&r_value, &r_rand);
+    fn create_proof(&self) -> RangeProof {
+        let mut rng = thread_rng();
+        let n = self.bit_length;
+        let m = self.values.len();
+        let nm = n * m;
 
-            l_val.push(l_comm.commitment);
-            r_val.push(r_comm.commitment);
-        }
+        let pc_gens = PedersenGens::default();
+        let bp_gens = BulletproofGens::new(nm);
+        let mut transcript = range_transcript(n, &self.commitments());
 
-        // Generate challenge
-        let mut hasher = Sha3_384::new();
-        hasher.update(&a_comm.commitment);
-        hasher.update(&s_comm.commitment);
-        for l in &l_val {
-            hasher.update(l);
+        // a_L holds the bits of every value, a_R = a_L - 1
+        let a_l: Vec<Scalar> = (0..nm)
+            .map(|i| Scalar::from((self.values[i / n] >> (i % n)) & 1))
+            .collect();
+        let a_r: Vec<Scalar> = a_l.iter().map(|bit| bit - Scalar::ONE).collect();
+
+        let alpha = random_scalar(&mut rng);
+        let a = RistrettoPoint::multiscalar_mul(
+            a_l.iter().chain(&a_r).chain([&alpha]),
+            bp_gens
+                .g_vec
+                .iter()
+                .chain(&bp_gens.h_vec)
+                .chain([&pc_gens.b_blinding]),
+        )
+        .compress();
+
+        let s_l: Vec<Scalar> = (0..nm).map(|_| random_scalar(&mut rng)).collect();
+        let s_r: Vec<Scalar> = (0..nm).map(|_| random_scalar(&mut rng)).collect();
+        let rho = random_scalar(&mut rng);
+        let s = RistrettoPoint::multiscalar_mul(
+            s_l.iter().chain(&s_r).chain([&rho]),
+            bp_gens
+                .g_vec
+                .iter()
+                .chain(&bp_gens.h_vec)
+                .chain([&pc_gens.b_blinding]),
+        )
+        .compress();
+
+        transcript.append_point(b"A", &a);
+        transcript.append_point(b"S", &s);
+        let y = transcript.challenge_scalar(b"y");
+        let z = transcript.challenge_scalar(b"z");
+
+        // l(x) = l0 + l1 * x and r(x) = r0 + r1 * x, the constant term of
+        // <l(x), r(x)> only depends on the values if every bit is 0 or 1
+        let mut l0 = Vec::with_capacity(nm);
+        let mut r0 = Vec::with_capacity(nm);
+        let mut r1 = Vec::with_capacity(nm);
+        let mut exp_y = Scalar::ONE;
+        let mut exp_z = z * z;
+        let mut exp_2 = Scalar::ONE;
+        for i in 0..nm {
+            if i > 0 && i % n == 0 {
+                exp_z *= z;
+                exp_2 = Scalar::ONE;
+            }
+            l0.push(a_l[i] - z);
+            r0.push(exp_y * (a_r[i] + z) + exp_z * exp_2);
+            r1.push(exp_y * s_r[i]);
+            exp_y *= y;
+            exp_2 += exp_2;
         }
-        for r in &r_val {
-            hasher.update(r);
+        let l1 = s_l;
+
+        let t1 = inner_product(&l0, &r1) + inner_product(&l1, &r0);
+        let t2 = inner_product(&l1, &r1);
+        let tau1 = random_scalar(&mut rng);
+        let tau2 = random_scalar(&mut rng);
+        let t1_comm = pc_gens.commit(t1, tau1).compress();
+        let t2_comm = pc_gens.commit(t2, tau2).compress();
+
+        transcript.append_point(b"T1", &t1_comm);
+        transcript.append_point(b"T2", &t2_comm);
+        let x = transcript.challenge_scalar(b"x");
+
+        let l: Vec<Scalar> = l0.iter().zip(&l1).map(|(l0, l1)| l0 + l1 * x).collect();
+        let r: Vec<Scalar> = r0.iter().zip(&r1).map(|(r0, r1)| r0 + r1 * x).collect();
+        let t_x = inner_product(&l, &r);
+
+        let mut t_x_blinding = tau2 * x * x + tau1 * x;
+        let mut exp_z = z * z;
+        for blinding in &self.blindings {
+            t_x_blinding += exp_z * blinding;
+            exp_z *= z;
         }
+        let e_blinding = alpha + rho * x;
+
+        transcript.append_scalar(b"t_x", &t_x);
+        transcript.append_scalar(b"t_x_blinding", &t_x_blinding);
+        transcript.append_scalar(b"e_blinding", &e_blinding);
+        let w = transcript.challenge_scalar(b"w");
+        let q = pc_gens.b * w;
+
+        // H'_i = y^-i * H_i turns r(x) into a plain inner product
+        let y_inv = y.invert();
+        let mut exp_y_inv = Scalar::ONE;
+        let h_prime = bp_gens
+            .h_vec
+            .iter()
+            .map(|h| {
+                let point = h * exp_y_inv;
+                exp_y_inv *= y_inv;
+                point
+            })
+            .collect();
+
+        let ipp = InnerProductProof::create(&mut transcript, &q, bp_gens.g_vec, h_prime, l, r);
 
         RangeProof {
-            a: a_comm.commitment,
-            s: s_comm.commitment,
-            l: l_val,
-            r: r_val,
-            challenge: hasher.finalize().to_vec(),
+            a,
+            s,
+            t1: t1_comm,
+            t2: t2_comm,
+            t_x,
+            t_x_blinding,
+            e_blinding,
+            ipp,
         }
     }
+}
 
-    fn commit(&self, value: &FieldElement, randomness: &[u8]) -> Commitment {
-        let mut hasher = Sha3_384::new();
-        hasher.update(value.value.to_le_bytes());
-        hasher.update(randomness);
-
-        Commitment {
-            commitment: hasher.finalize().to_vec(),
-            randomness: randomness.to_vec(),
-        }
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::bulletproofs::verifier::Verifier;
+
+    #[test]
+    fn test_out_of_range_value() {
+        let prover = Prover::new(300, 8);
+        assert!(matches!(
+            prover.prove(),
+            Err(CryptoError::RangeProofValueOutOfRange)
+        ));
+
+        // A dishonest prover skipping the check still can not convince the verifier
+        let proof = prover.create_proof();
+        assert!(!Verifier::new(8).verify(&proof, &prover.commitments()));
     }
 }
@@ -0,0 +1,48 @@
+use curve25519_dalek::{ristretto::CompressedRistretto, scalar::Scalar};
+use sha3::{Digest, Sha3_512};
+
+/// Fiat-Shamir transcript, every challenge depends on all messages appended
+/// before it, so the prover can not pick them after the fact
+#[derive(Clone)]
+pub struct Transcript {
+    hasher: Sha3_512,
+}
+
+impl Transcript {
+    pub fn new(label: &[u8]) -> Self {
+        let mut transcript = Self {
+            hasher: Sha3_512::new(),
+        };
+        transcript.append_message(b"dom-sep", label);
+        transcript
+    }
+
+    /// Messages are length prefixed, so different splits of the same bytes
+    /// give different transcripts
+    pub fn append_message(&mut self, label: &[u8], message: &[u8]) {
+        self.hasher.update((label.len() as u64).to_le_bytes());
+        self.hasher.update(label);
+        self.hasher.update((message.len() as u64).to_le_bytes());
+        self.hasher.update(message);
+    }
+
+    pub fn append_u64(&mut self, label: &[u8], value: u64) {
+        self.append_message(label, &value.to_le_bytes());
+    }
+
+    pub fn append_point(&mut self, label: &[u8], point: &CompressedRistretto) {
+        self.append_message(label, point.as_bytes());
+    }
+
+    pub fn append_scalar(&mut self, label: &[u8], scalar: &Scalar) {
+        self.append_message(label, scalar.as_bytes());
+    }
+
+    pub fn challenge_scalar(&mut self, label: &[u8]) -> Scalar {
+        self.append_message(b"challenge", label);
+        let output: [u8; 64] = self.hasher.clone().finalize().into();
+        // Feed the challenge back so the next one differs
+        self.hasher.update(output);
+        Scalar::from_bytes_mod_order_wide(&output)
+    }
+}
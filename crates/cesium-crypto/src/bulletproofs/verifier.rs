@@ -1,6 +1,15 @@
-use sha3::{Digest, Sha3_384};
+use curve25519_dalek::{
+    ristretto::RistrettoPoint,
+    scalar::Scalar,
+    traits::{IsIdentity, VartimeMultiscalarMul},
+};
 
-use super::proof::RangeProof;
+use super::{
+    check_params,
+    generators::{BulletproofGens, PedersenGens},
+    proof::{Commitment, RangeProof},
+    range_transcript,
+};
 
 /// Verifier for range proofs
 pub struct Verifier {
@@ -12,31 +21,114 @@ impl Verifier {
         Self { bit_length }
     }
 
-    pub fn verify(&self, proof: &RangeProof) -> bool {
-        // Verify format
-        if proof.l.len() != self.bit_length.trailing_zeros() as usize
-            || proof.r.len() != proof.l.len()
+    /// Checks that every commitment holds a value of at most `bit_length`
+    /// bits, the commitments must be given in the order they were proven
+    pub fn verify(&self, proof: &RangeProof, commitments: &[Commitment]) -> bool {
+        let n = self.bit_length;
+        let m = commitments.len();
+        if check_params(n, m).is_err() || proof.ipp.l_vec.len() != (n * m).trailing_zeros() as usize
         {
             return false;
         }
 
-        // Verify challenge computation
-        let mut hasher = Sha3_384::new();
-        hasher.update(&proof.a);
-        hasher.update(&proof.s);
-        for l in &proof.l {
-            hasher.update(l);
-        }
-        for r in &proof.r {
-            hasher.update(r);
-        }
+        let decompressed = [proof.a, proof.s, proof.t1, proof.t2]
+            .iter()
+            .chain(commitments.iter().map(|c| &c.point))
+            .map(|point| point.decompress())
+            .collect::<Option<Vec<RistrettoPoint>>>();
+        let points = match decompressed {
+            Some(points) => points,
+            None => return false,
+        };
+        let (a, s, t1, t2) = (points[0], points[1], points[2], points[3]);
+        let v = &points[4..];
+
+        let nm = n * m;
+        let pc_gens = PedersenGens::default();
+        let bp_gens = BulletproofGens::new(nm);
+        let mut transcript = range_transcript(n, commitments);
+
+        transcript.append_point(b"A", &proof.a);
+        transcript.append_point(b"S", &proof.s);
+        let y = transcript.challenge_scalar(b"y");
+        let z = transcript.challenge_scalar(b"z");
+
+        transcript.append_point(b"T1", &proof.t1);
+        transcript.append_point(b"T2", &proof.t2);
+        let x = transcript.challenge_scalar(b"x");
+
+        transcript.append_scalar(b"t_x", &proof.t_x);
+        transcript.append_scalar(b"t_x_blinding", &proof.t_x_blinding);
+        transcript.append_scalar(b"e_blinding", &proof.e_blinding);
+        let w = transcript.challenge_scalar(b"w");
+        let q = pc_gens.b * w;
 
-        let computed_challenge = hasher.finalize();
-        if computed_challenge.as_slice() != proof.challenge.as_slice() {
+        // z^2, z^3, ... one power per value
+        let zz = z * z;
+        let exp_z: Vec<Scalar> = (0..m)
+            .scan(zz, |acc, _| {
+                let current = *acc;
+                *acc *= z;
+                Some(current)
+            })
+            .collect();
+
+        // delta(y, z) = (z - z^2) * <1, y^nm> - sum_j z^(3 + j) * <1, 2^n>
+        let sum_y: Scalar = (0..nm)
+            .scan(Scalar::ONE, |acc, _| {
+                let current = *acc;
+                *acc *= y;
+                Some(current)
+            })
+            .sum();
+        let sum_2 = Scalar::from(u64::MAX >> (64 - n));
+        let sum_z: Scalar = exp_z.iter().sum();
+        let delta = (z - zz) * sum_y - z * sum_2 * sum_z;
+
+        // t_x * B + t_x_blinding * B_blinding == sum_j z^(2 + j) V_j + delta * B + x * T1 + x^2 * T2
+        let check = RistrettoPoint::vartime_multiscalar_mul(
+            exp_z
+                .iter()
+                .copied()
+                .chain([delta - proof.t_x, -proof.t_x_blinding, x, x * x]),
+            v.iter().chain([&pc_gens.b, &pc_gens.b_blinding, &t1, &t2]),
+        );
+        if !check.is_identity() {
             return false;
         }
 
-        // The verification passed
-        true
+        // P = A + x * S - e_blinding * B_blinding + t_x * Q - z * <1, G>
+        //     + sum_i (z * y^i + z^(2 + j) * 2^(i mod n)) * H'_i
+        let y_inv = y.invert();
+        let mut h_prime = Vec::with_capacity(nm);
+        let mut h_scalars = Vec::with_capacity(nm);
+        let mut exp_y = Scalar::ONE;
+        let mut exp_y_inv = Scalar::ONE;
+        let mut exp_2 = Scalar::ONE;
+        for (i, h) in bp_gens.h_vec.iter().enumerate() {
+            if i > 0 && i % n == 0 {
+                exp_2 = Scalar::ONE;
+            }
+            h_prime.push(h * exp_y_inv);
+            h_scalars.push(z * exp_y + exp_z[i / n] * exp_2);
+            exp_y *= y;
+            exp_y_inv *= y_inv;
+            exp_2 += exp_2;
+        }
+
+        let p = RistrettoPoint::vartime_multiscalar_mul(
+            [Scalar::ONE, x, -proof.e_blinding, proof.t_x]
+                .into_iter()
+                .chain(std::iter::repeat_n(-z, nm))
+                .chain(h_scalars),
+            [&a, &s, &pc_gens.b_blinding, &q]
+                .into_iter()
+                .chain(&bp_gens.g_vec)
+                .chain(&h_prime),
+        );
+
+        proof
+            .ipp
+            .verify(&mut transcript, &q, &p, &bp_gens.g_vec, &h_prime)
     }
 }
@@ -0,0 +1,185 @@
+use std::str::FromStr;
+
+use hmac::{Hmac, Mac};
+use sha2::Sha512;
+
+use crate::errors::CryptoError;
+
+// Derivation follows SLIP-0010 with hardened children only, the derived
+// 32 byte key is then used as the seed of a scheme specific keygen
+// (e.g. `mldsa::keypair::SignerPair::from_seed`)
+
+pub const MASTER_KEY: &[u8] = b"Cesium seed";
+pub const PURPOSE: u32 = 44;
+pub const COIN_TYPE: u32 = 1729;
+pub const HARDENED_OFFSET: u32 = 0x8000_0000;
+
+pub const KEY_BYTE_LEN: usize = 32;
+pub type KeyBytes = [u8; KEY_BYTE_LEN];
+
+type HmacSha512 = Hmac<Sha512>;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DerivationPath {
+    indexes: Vec<u32>,
+}
+
+impl DerivationPath {
+    /// Path of the n-th account, m/44'/1729'/n'/0'
+    pub fn account(index: u32) -> Self {
+        Self {
+            indexes: vec![PURPOSE, COIN_TYPE, index, 0],
+        }
+    }
+
+    pub fn indexes(&self) -> &[u32] {
+        &self.indexes
+    }
+
+    pub fn as_str(&self) -> String {
+        let mut path = String::from("m");
+        for index in &self.indexes {
+            path.push_str(&format!("/{}'", index));
+        }
+        path
+    }
+}
+
+impl FromStr for DerivationPath {
+    type Err = CryptoError;
+
+    /// Parse a path such as `m/44'/1729'/0'/0'`, every index must be hardened
+    fn from_str(path: &str) -> Result<Self, CryptoError> {
+        let mut parts = path.split('/');
+        if parts.next() != Some("m") {
+            return Err(CryptoError::InvalidDerivationPath);
+        }
+
+        let mut indexes = Vec::new();
+        for part in parts {
+            let index = match part.strip_suffix('\'') {
+                Some(index) => index,
+                None => return Err(CryptoError::InvalidDerivationPath),
+            };
+
+            let index = match index.parse::<u32>() {
+                Ok(index) if index < HARDENED_OFFSET => index,
+                _ => return Err(CryptoError::InvalidDerivationPath),
+            };
+            indexes.push(index);
+        }
+
+        Ok(Self { indexes })
+    }
+}
+
+pub struct ExtendedKey {
+    key: KeyBytes,
+    chain_code: KeyBytes,
+}
+
+impl ExtendedKey {
+    pub fn master(seed: &[u8]) -> Self {
+        Self::from_hmac(MASTER_KEY, &[seed])
+    }
+
+    pub fn derive_child(&self, index: u32) -> Self {
+        let index = (index | HARDENED_OFFSET).to_be_bytes();
+        Self::from_hmac(&self.chain_code, &[&[0u8], &self.key, &index])
+    }
+
+    pub fn derive_path(&self, path: &DerivationPath) -> Self {
+        let mut key = Self {
+            key: self.key,
+            chain_code: self.chain_code,
+        };
+        for index in path.indexes() {
+            key = key.derive_child(*index);
+        }
+        key
+    }
+
+    pub fn key(&self) -> &KeyBytes {
+        &self.key
+    }
+
+    pub fn chain_code(&self) -> &KeyBytes {
+        &self.chain_code
+    }
+
+    fn from_hmac(hmac_key: &[u8], data: &[&[u8]]) -> Self {
+        // HMAC accepts keys of any length
+        let mut mac = HmacSha512::new_from_slice(hmac_key).unwrap();
+        for d in data {
+            mac.update(d);
+        }
+        let result = mac.finalize().into_bytes();
+
+        let mut key = [0u8; KEY_BYTE_LEN];
+        let mut chain_code = [0u8; KEY_BYTE_LEN];
+        key.copy_from_slice(&result[..KEY_BYTE_LEN]);
+        chain_code.copy_from_slice(&result[KEY_BYTE_LEN..]);
+        Self { key, chain_code }
+    }
+}
+
+/// Derive the keygen seed found at `path` from a mnemonic seed
+pub fn derive_key(seed: &[u8], path: &DerivationPath) -> KeyBytes {
+    *ExtendedKey::master(seed).derive_path(path).key()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_path_parse() {
+        let path = DerivationPath::from_str("m/44'/1729'/3'/0'").unwrap();
+        assert_eq!(path, DerivationPath::account(3));
+        assert_eq!(path.as_str(), "m/44'/1729'/3'/0'");
+
+        assert!(DerivationPath::from_str("m").unwrap().indexes().is_empty());
+        assert!(DerivationPath::from_str("44'/1729'").is_err());
+        assert!(DerivationPath::from_str("m/44/1729'").is_err());
+        assert!(DerivationPath::from_str("m/2147483648'").is_err());
+        assert!(DerivationPath::from_str("m/x'").is_err());
+    }
+
+    #[test]
+    fn test_derive_deterministic() {
+        let seed = [7u8; 64];
+        let a = derive_key(&seed, &DerivationPath::account(0));
+        let b = derive_key(&seed, &DerivationPath::account(0));
+        let c = derive_key(&seed, &DerivationPath::account(1));
+
+        assert_eq!(a, b);
+        assert_ne!(a, c);
+        assert_ne!(a, derive_key(&[8u8; 64], &DerivationPath::account(0)));
+    }
+
+    #[test]
+    fn test_derive_step_by_step() {
+        let seed = [1u8; 64];
+        let master = ExtendedKey::master(&seed);
+        let child = master.derive_child(PURPOSE).derive_child(COIN_TYPE);
+
+        let path = DerivationPath::from_str("m/44'/1729'").unwrap();
+        assert_eq!(master.derive_path(&path).key(), child.key());
+        assert_eq!(master.derive_path(&path).chain_code(), child.chain_code());
+    }
+
+    #[cfg(feature = "mldsa")]
+    #[test]
+    fn test_restore_account() {
+        use crate::{mldsa::keypair::SignerPair, mnemonic::Mnemonic};
+
+        let mnemonic = Mnemonic::generate();
+        let seed = mnemonic.to_seed("");
+        let account = SignerPair::from_seed(&derive_key(&seed, &DerivationPath::account(0)));
+
+        let restored = Mnemonic::from_phrase(&mnemonic.phrase()).unwrap();
+        let seed = restored.to_seed("");
+        let restored = SignerPair::from_seed(&derive_key(&seed, &DerivationPath::account(0)));
+        assert_eq!(account.to_bytes(), restored.to_bytes());
+    }
+}
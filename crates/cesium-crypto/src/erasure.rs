@@ -0,0 +1,359 @@
+use sha3::{Digest, Sha3_256};
+
+use crate::{bulletproofs::fields::FieldElement, errors::CryptoError, polynomial::Polynomial};
+
+// Systematic Reed-Solomon erasure coding over the Mersenne-61 field. The
+// payload is cut into 7 byte symbols and spread over the data shards, row j
+// of the code is the j-th symbol of every shard. The data shards are the
+// values of a polynomial at x = 1..=K, parity shard i holds its value at
+// x = i + 1, so any K shards pin the polynomial down and rebuild the rest.
+//
+// Shard layout, integers are little endian:
+// MAGIC (4) | VERSION (1) | DATA_SHARDS (1) | TOTAL_SHARDS (1) | INDEX (1)
+// | PAYLOAD_LEN (4) | DIGEST (32) | SYMBOLS (8 each)
+// The digest is the SHA3-256 of the payload, it groups the shards of one
+// payload and checks the rebuilt payload.
+
+pub const MAGIC: &[u8; 4] = b"CSEC";
+pub const VERSION: u8 = 1;
+pub const SYMBOL_BYTE_LEN: usize = 7;
+pub const DIGEST_BYTE_LEN: usize = 32;
+pub const HEADER_BYTE_LEN: usize = 4 + 1 + 1 + 1 + 1 + 4 + DIGEST_BYTE_LEN;
+
+pub type PayloadDigest = [u8; DIGEST_BYTE_LEN];
+
+macro_rules! bounds_check {
+    ($bytes:expr, $len:expr) => {
+        if $bytes.len() < $len {
+            return Err(CryptoError::InvalidShard);
+        }
+    };
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct Shard {
+    data_shards: u8,
+    total_shards: u8,
+    index: u8,
+    payload_len: u32,
+    digest: PayloadDigest,
+    symbols: Vec<FieldElement>,
+}
+
+fn payload_digest(payload: &[u8]) -> PayloadDigest {
+    Sha3_256::digest(payload).into()
+}
+
+fn symbols_per_shard(payload_len: usize, data_shards: usize) -> usize {
+    payload_len
+        .div_ceil(SYMBOL_BYTE_LEN)
+        .div_ceil(data_shards)
+        .max(1)
+}
+
+fn shard_point(index: usize) -> FieldElement {
+    FieldElement::new(index as u64 + 1)
+}
+
+// For every target, the weights of the known values in the value at the target
+fn weights(known: &[usize], targets: &[usize]) -> Vec<Vec<FieldElement>> {
+    let points: Vec<FieldElement> = known.iter().map(|&i| shard_point(i)).collect();
+    let basis = Polynomial::lagrange_basis(&points);
+
+    targets
+        .iter()
+        .map(|&target| {
+            let x = shard_point(target);
+            basis.iter().map(|poly| poly.evaluate(&x)).collect()
+        })
+        .collect()
+}
+
+fn combine_row(values: &[FieldElement], weights: &[FieldElement]) -> FieldElement {
+    values
+        .iter()
+        .zip(weights)
+        .fold(FieldElement::new(0), |acc, (value, weight)| {
+            acc.add(&value.mul(weight))
+        })
+}
+
+/// Splits the payload into `total_shards` shards, any `data_shards` of
+/// them rebuild it
+pub fn encode(
+    payload: &[u8],
+    data_shards: u8,
+    total_shards: u8,
+) -> Result<Vec<Shard>, CryptoError> {
+    if data_shards == 0 || data_shards > total_shards {
+        return Err(CryptoError::InvalidShardCount);
+    }
+    let payload_len = u32::try_from(payload.len()).map_err(|_| CryptoError::InvalidShard)?;
+
+    let k = data_shards as usize;
+    let n = total_shards as usize;
+    let per_shard = symbols_per_shard(payload.len(), k);
+
+    let mut symbols = payload.chunks(SYMBOL_BYTE_LEN).map(|chunk| {
+        let mut word = [0u8; 8];
+        word[..chunk.len()].copy_from_slice(chunk);
+        FieldElement::new(u64::from_le_bytes(word))
+    });
+
+    let digest = payload_digest(payload);
+    let mut shards: Vec<Shard> = (0..n)
+        .map(|index| Shard {
+            data_shards,
+            total_shards,
+            index: index as u8,
+            payload_len,
+            digest,
+            symbols: Vec::with_capacity(per_shard),
+        })
+        .collect();
+
+    for shard in shards.iter_mut().take(k) {
+        shard
+            .symbols
+            .extend((0..per_shard).map(|_| symbols.next().unwrap_or(FieldElement::new(0))));
+    }
+
+    let known: Vec<usize> = (0..k).collect();
+    let parity: Vec<usize> = (k..n).collect();
+    let parity_weights = weights(&known, &parity);
+    for row in 0..per_shard {
+        let values: Vec<FieldElement> = shards[..k].iter().map(|s| s.symbols[row]).collect();
+        for (index, weights) in parity.iter().zip(&parity_weights) {
+            let value = combine_row(&values, weights);
+            shards[*index].symbols.push(value);
+        }
+    }
+
+    Ok(shards)
+}
+
+/// Rebuilds the payload from at least `data_shards` distinct shards of it
+pub fn decode(shards: &[Shard]) -> Result<Vec<u8>, CryptoError> {
+    let first = shards.first().ok_or(CryptoError::InsufficientShards)?;
+    let k = first.data_shards as usize;
+
+    // The first K distinct shards are used, duplicates are skipped
+    let mut picked: Vec<&Shard> = Vec::with_capacity(k);
+    for shard in shards {
+        if !first.same_payload(shard) {
+            return Err(CryptoError::InvalidShard);
+        }
+        if picked.len() < k && !picked.iter().any(|other| other.index == shard.index) {
+            picked.push(shard);
+        }
+    }
+    if picked.len() < k {
+        return Err(CryptoError::InsufficientShards);
+    }
+    picked.sort_by_key(|shard| shard.index);
+
+    let known: Vec<usize> = picked.iter().map(|shard| shard.index as usize).collect();
+    let missing: Vec<usize> = (0..k).filter(|index| !known.contains(index)).collect();
+    let missing_weights = weights(&known, &missing);
+
+    let per_shard = first.symbols.len();
+    let mut data: Vec<Vec<FieldElement>> = vec![Vec::new(); k];
+    for shard in picked.iter().filter(|shard| (shard.index as usize) < k) {
+        data[shard.index as usize] = shard.symbols.clone();
+    }
+    for row in 0..per_shard {
+        let values: Vec<FieldElement> = picked.iter().map(|s| s.symbols[row]).collect();
+        for (index, weights) in missing.iter().zip(&missing_weights) {
+            let value = combine_row(&values, weights);
+            data[*index].push(value);
+        }
+    }
+
+    let mut payload = Vec::with_capacity(k * per_shard * SYMBOL_BYTE_LEN);
+    for symbol in data.iter().flatten() {
+        let word = symbol.value.to_le_bytes();
+        // Symbols are 7 bytes, a set high byte means corrupted shards
+        if word[SYMBOL_BYTE_LEN] != 0 {
+            return Err(CryptoError::InvalidShard);
+        }
+        payload.extend_from_slice(&word[..SYMBOL_BYTE_LEN]);
+    }
+    payload.truncate(first.payload_len as usize);
+
+    if payload_digest(&payload) != first.digest {
+        return Err(CryptoError::InvalidShard);
+    }
+    Ok(payload)
+}
+
+impl Shard {
+    fn same_payload(&self, other: &Shard) -> bool {
+        self.data_shards == other.data_shards
+            && self.total_shards == other.total_shards
+            && self.payload_len == other.payload_len
+            && self.digest == other.digest
+            && self.symbols.len() == other.symbols.len()
+    }
+
+    pub fn data_shards(&self) -> u8 {
+        self.data_shards
+    }
+
+    pub fn total_shards(&self) -> u8 {
+        self.total_shards
+    }
+
+    pub fn index(&self) -> u8 {
+        self.index
+    }
+
+    pub fn payload_len(&self) -> u32 {
+        self.payload_len
+    }
+
+    pub fn is_parity(&self) -> bool {
+        self.index >= self.data_shards
+    }
+
+    pub fn digest(&self) -> &PayloadDigest {
+        &self.digest
+    }
+
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let mut bytes = Vec::with_capacity(HEADER_BYTE_LEN + self.symbols.len() * 8);
+        bytes.extend_from_slice(MAGIC);
+        bytes.push(VERSION);
+        bytes.push(self.data_shards);
+        bytes.push(self.total_shards);
+        bytes.push(self.index);
+        bytes.extend(self.payload_len.to_le_bytes());
+        bytes.extend_from_slice(&self.digest);
+        for symbol in &self.symbols {
+            bytes.extend(symbol.value.to_le_bytes());
+        }
+        bytes
+    }
+
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self, CryptoError> {
+        bounds_check!(bytes, HEADER_BYTE_LEN);
+        if &bytes[0..4] != MAGIC || bytes[4] != VERSION {
+            return Err(CryptoError::InvalidShard);
+        }
+
+        let data_shards = bytes[5];
+        let total_shards = bytes[6];
+        let index = bytes[7];
+        if data_shards == 0 || data_shards > total_shards || index >= total_shards {
+            return Err(CryptoError::InvalidShard);
+        }
+
+        let payload_len = u32::from_le_bytes(bytes[8..12].try_into().unwrap());
+        let mut digest = [0u8; DIGEST_BYTE_LEN];
+        digest.copy_from_slice(&bytes[12..HEADER_BYTE_LEN]);
+
+        let per_shard = symbols_per_shard(payload_len as usize, data_shards as usize);
+        if bytes.len() != HEADER_BYTE_LEN + per_shard * 8 {
+            return Err(CryptoError::InvalidShard);
+        }
+
+        let symbols = bytes[HEADER_BYTE_LEN..]
+            .chunks_exact(8)
+            .map(|symbol| FieldElement::new(u64::from_le_bytes(symbol.try_into().unwrap())))
+            .collect();
+
+        Ok(Self {
+            data_shards,
+            total_shards,
+            index,
+            payload_len,
+            digest,
+            symbols,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn test_payload(len: usize) -> Vec<u8> {
+        (0..len).map(|i| (i * 31 % 251) as u8).collect()
+    }
+
+    #[test]
+    fn test_encode_decode() {
+        let payload = test_payload(1000);
+        let shards = encode(&payload, 4, 7).unwrap();
+        assert_eq!(shards.len(), 7);
+        assert_eq!(shards.iter().filter(|s| s.is_parity()).count(), 3);
+
+        // Every subset of K shards rebuilds the payload
+        for a in 0..7 {
+            for b in a + 1..7 {
+                for c in b + 1..7 {
+                    let subset: Vec<Shard> = (0..7)
+                        .filter(|i| ![a, b, c].contains(i))
+                        .map(|i| Shard::from_bytes(&shards[i].to_bytes()).unwrap())
+                        .collect();
+                    assert_eq!(decode(&subset).unwrap(), payload);
+                }
+            }
+        }
+
+        assert!(matches!(
+            decode(&shards[..3]),
+            Err(CryptoError::InsufficientShards)
+        ));
+    }
+
+    #[test]
+    fn test_edge_sizes() {
+        for len in [0, 1, 6, 7, 8, 27, 28, 29] {
+            let payload = test_payload(len);
+            let shards = encode(&payload, 4, 6).unwrap();
+            assert_eq!(decode(&shards[2..]).unwrap(), payload);
+        }
+
+        let payload = test_payload(100);
+        let shards = encode(&payload, 1, 3).unwrap();
+        assert_eq!(decode(&shards[2..]).unwrap(), payload);
+        let shards = encode(&payload, 3, 3).unwrap();
+        assert_eq!(decode(&shards).unwrap(), payload);
+
+        assert!(matches!(
+            encode(&payload, 0, 3),
+            Err(CryptoError::InvalidShardCount)
+        ));
+        assert!(matches!(
+            encode(&payload, 4, 3),
+            Err(CryptoError::InvalidShardCount)
+        ));
+    }
+
+    #[test]
+    fn test_invalid_shards() {
+        let payload = test_payload(200);
+        let shards = encode(&payload, 2, 4).unwrap();
+
+        // Duplicates do not count towards K
+        let dup = [shards[3].clone(), shards[3].clone()];
+        assert!(matches!(decode(&dup), Err(CryptoError::InsufficientShards)));
+
+        // Shards of another payload do not mix
+        let other = encode(&test_payload(201), 2, 4).unwrap();
+        let mixed = [shards[0].clone(), other[1].clone()];
+        assert!(matches!(decode(&mixed), Err(CryptoError::InvalidShard)));
+
+        // A corrupted symbol fails the digest
+        let mut bytes = shards[3].to_bytes();
+        bytes[HEADER_BYTE_LEN] ^= 1;
+        let corrupted = [shards[0].clone(), Shard::from_bytes(&bytes).unwrap()];
+        assert!(matches!(decode(&corrupted), Err(CryptoError::InvalidShard)));
+
+        let bytes = shards[0].to_bytes();
+        assert!(Shard::from_bytes(&bytes[..bytes.len() - 1]).is_err());
+        let mut bad_magic = bytes.clone();
+        bad_magic[0] = b'X';
+        assert!(Shard::from_bytes(&bad_magic).is_err());
+    }
+}
@@ -6,10 +6,39 @@ pub enum CryptoError {
     BaseDecodeError(bs58::decode::Error),
     InvalidSignature,
     InvalidDisplayAddress,
+    InvalidAddressPrefix,
+    InvalidAddressChecksum,
+    InvalidAddressLength,
+    InvalidAddressEncoding,
+    InvalidKeyLength,
+    UnsupportedScheme(u8),
+    SchemeMismatch,
+    InvalidMnemonic,
+    InvalidDerivationPath,
+    InvalidKeystore,
+    KeystoreDecryptionError,
+    IoError(std::io::Error),
     ZkInvalidSecret,
     ZkInvalidCommitment,
+    ZkInvalidChallenge,
     ZkInvalidResponse,
     ZkKeyGenerationError,
+    RangeProofValueOutOfRange,
+    RangeProofInvalidBitLength,
+    RangeProofInvalidAggregation,
+    RangeProofInvalidFormat,
+    InvalidMerkleProof,
+    InvalidCiphertext,
+    InvalidShare,
+    InvalidShareThreshold,
+    InsufficientShares,
+    InvalidShard,
+    InvalidShardCount,
+    InsufficientShards,
+    InvalidBeacon,
+    InvalidBeaconContribution,
+    InvalidBeaconThreshold,
+    InsufficientContributions,
 }
 
 impl std::fmt::Display for CryptoError {
@@ -21,10 +50,45 @@ impl std::fmt::Display for CryptoError {
             CryptoError::BaseDecodeError(e) => e.fmt(f),
             CryptoError::InvalidSignature => write!(f, "Invalid signature"),
             CryptoError::InvalidDisplayAddress => write!(f, "Invalid display address"),
+            CryptoError::InvalidAddressPrefix => write!(f, "Invalid address prefix"),
+            CryptoError::InvalidAddressChecksum => write!(f, "Invalid address checksum"),
+            CryptoError::InvalidAddressLength => write!(f, "Invalid address length"),
+            CryptoError::InvalidAddressEncoding => write!(f, "Invalid address encoding"),
+            CryptoError::InvalidKeyLength => write!(f, "Invalid key length"),
+            CryptoError::UnsupportedScheme(s) => write!(f, "Unsupported signature scheme: {}", s),
+            CryptoError::SchemeMismatch => write!(f, "Signature scheme mismatch"),
+            CryptoError::InvalidMnemonic => write!(f, "Invalid mnemonic phrase"),
+            CryptoError::InvalidDerivationPath => write!(f, "Invalid derivation path"),
+            CryptoError::InvalidKeystore => write!(f, "Invalid keystore"),
+            CryptoError::KeystoreDecryptionError => {
+                write!(f, "Unable to decrypt keystore, wrong password?")
+            }
+            CryptoError::IoError(e) => e.fmt(f),
             CryptoError::ZkInvalidSecret => write!(f, "Invalid secret"),
             CryptoError::ZkInvalidCommitment => write!(f, "Invalid commitment"),
+            CryptoError::ZkInvalidChallenge => write!(f, "Invalid challenge"),
             CryptoError::ZkInvalidResponse => write!(f, "Invalid response"),
             CryptoError::ZkKeyGenerationError => write!(f, "Key generation error"),
+            CryptoError::RangeProofValueOutOfRange => write!(f, "Value out of range"),
+            CryptoError::RangeProofInvalidBitLength => write!(f, "Invalid range proof bit length"),
+            CryptoError::RangeProofInvalidAggregation => {
+                write!(f, "Invalid range proof aggregation size")
+            }
+            CryptoError::RangeProofInvalidFormat => write!(f, "Invalid range proof format"),
+            CryptoError::InvalidMerkleProof => write!(f, "Invalid merkle proof"),
+            CryptoError::InvalidCiphertext => write!(f, "Invalid ciphertext"),
+            CryptoError::InvalidShare => write!(f, "Invalid secret share"),
+            CryptoError::InvalidShareThreshold => write!(f, "Invalid secret share threshold"),
+            CryptoError::InsufficientShares => write!(f, "Not enough secret shares"),
+            CryptoError::InvalidShard => write!(f, "Invalid erasure shard"),
+            CryptoError::InvalidShardCount => write!(f, "Invalid erasure shard count"),
+            CryptoError::InsufficientShards => write!(f, "Not enough erasure shards"),
+            CryptoError::InvalidBeacon => write!(f, "Invalid randomness beacon"),
+            CryptoError::InvalidBeaconContribution => write!(f, "Invalid beacon contribution"),
+            CryptoError::InvalidBeaconThreshold => write!(f, "Invalid beacon threshold"),
+            CryptoError::InsufficientContributions => {
+                write!(f, "Not enough beacon contributions")
+            }
         }
     }
 }
@@ -48,3 +112,15 @@ impl From<pqcrypto_traits::Error> for CryptoError {
         CryptoError::PQCryptoError(e)
     }
 }
+
+impl From<bip39::Error> for CryptoError {
+    fn from(_: bip39::Error) -> Self {
+        CryptoError::InvalidMnemonic
+    }
+}
+
+impl From<std::io::Error> for CryptoError {
+    fn from(e: std::io::Error) -> Self {
+        CryptoError::IoError(e)
+    }
+}
@@ -1,7 +1,12 @@
+use std::str::FromStr;
+
 use rand::RngCore;
 use sha3::Digest;
 
-use crate::errors::CryptoError;
+use crate::{address, errors::CryptoError};
+
+#[cfg(feature = "serde")]
+use crate::serializer;
 
 use super::{PublicKeyBytes, PUB_BYTE_LEN};
 
@@ -73,6 +78,28 @@ impl DisplayAddress {
     }
 
     pub fn as_str(&self) -> String {
-        bs58::encode(&self.da).into_string()
+        address::encode(&self.da)
+    }
+}
+
+impl FromStr for DisplayAddress {
+    type Err = CryptoError;
+
+    fn from_str(da: &str) -> Result<Self, CryptoError> {
+        Self::from_bytes(&address::decode(da, DA_BYTE_LEN)?)
+    }
+}
+
+#[cfg(feature = "serde")]
+impl serde::Serialize for DisplayAddress {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer::serialize_encoded(serializer, || self.as_str(), &self.da)
+    }
+}
+
+#[cfg(feature = "serde")]
+impl<'de> serde::Deserialize<'de> for DisplayAddress {
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        serializer::deserialize_encoded(deserializer, Self::from_str, Self::from_bytes)
     }
 }
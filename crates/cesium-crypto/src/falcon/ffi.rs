@@ -0,0 +1,113 @@
+use zeroize::Zeroize;
+
+use crate::{derivation::KeyBytes, secret::SecretBytes};
+
+use super::{PUB_BYTE_LEN, SEC_BYTE_LEN};
+
+// pqcrypto only exposes randomized keygen, so deterministic keys call the
+// PQClean routines linked by pqcrypto-falcon directly. This mirrors
+// crypto_sign_keypair in falcon-padded-1024/clean/pqclean.c with the
+// random seed replaced by ours.
+
+const LOGN: u32 = 10;
+const N: usize = 1 << LOGN;
+const KEYGEN_TEMP_BYTES: usize = 28672; // FALCON_KEYGEN_TEMP_10
+const FG_BITS: u32 = 5; // max_fg_bits[10]
+const BIG_FG_BITS: u32 = 8; // max_FG_bits[10]
+
+// shake256incctx from PQClean's fips202.h
+#[repr(C)]
+struct Shake256IncCtx {
+    ctx: [u64; 26],
+}
+
+extern "C" {
+    fn shake256_inc_init(state: *mut Shake256IncCtx);
+    fn shake256_inc_absorb(state: *mut Shake256IncCtx, input: *const u8, inlen: usize);
+    fn shake256_inc_finalize(state: *mut Shake256IncCtx);
+    fn shake256_inc_ctx_release(state: *mut Shake256IncCtx);
+
+    fn PQCLEAN_FALCONPADDED1024_CLEAN_keygen(
+        rng: *mut Shake256IncCtx,
+        f: *mut i8,
+        g: *mut i8,
+        big_f: *mut i8,
+        big_g: *mut i8,
+        h: *mut u16,
+        logn: u32,
+        tmp: *mut u8,
+    );
+    fn PQCLEAN_FALCONPADDED1024_CLEAN_trim_i8_encode(
+        out: *mut u8,
+        max_out_len: usize,
+        x: *const i8,
+        logn: u32,
+        bits: u32,
+    ) -> usize;
+    fn PQCLEAN_FALCONPADDED1024_CLEAN_modq_encode(
+        out: *mut u8,
+        max_out_len: usize,
+        x: *const u16,
+        logn: u32,
+    ) -> usize;
+}
+
+pub fn keypair_from_seed(seed: &KeyBytes) -> (Vec<u8>, SecretBytes) {
+    let mut f = [0i8; N];
+    let mut g = [0i8; N];
+    let mut big_f = [0i8; N];
+    let mut h = [0u16; N];
+    // The C code expects the temporary buffer to be 8 byte aligned
+    let mut tmp = vec![0u64; KEYGEN_TEMP_BYTES / 8];
+
+    let mut pk = vec![0u8; PUB_BYTE_LEN];
+    let mut sk = vec![0u8; SEC_BYTE_LEN];
+
+    unsafe {
+        let mut rng = Shake256IncCtx { ctx: [0; 26] };
+        shake256_inc_init(&mut rng);
+        shake256_inc_absorb(&mut rng, seed.as_ptr(), seed.len());
+        shake256_inc_finalize(&mut rng);
+        PQCLEAN_FALCONPADDED1024_CLEAN_keygen(
+            &mut rng,
+            f.as_mut_ptr(),
+            g.as_mut_ptr(),
+            big_f.as_mut_ptr(),
+            std::ptr::null_mut(),
+            h.as_mut_ptr(),
+            LOGN,
+            tmp.as_mut_ptr() as *mut u8,
+        );
+        shake256_inc_ctx_release(&mut rng);
+
+        sk[0] = 0x50 + LOGN as u8;
+        let mut u = 1;
+        for (x, bits) in [(&f, FG_BITS), (&g, FG_BITS), (&big_f, BIG_FG_BITS)] {
+            u += PQCLEAN_FALCONPADDED1024_CLEAN_trim_i8_encode(
+                sk[u..].as_mut_ptr(),
+                SEC_BYTE_LEN - u,
+                x.as_ptr(),
+                LOGN,
+                bits,
+            );
+        }
+        assert_eq!(u, SEC_BYTE_LEN);
+
+        pk[0] = LOGN as u8;
+        let v = PQCLEAN_FALCONPADDED1024_CLEAN_modq_encode(
+            pk[1..].as_mut_ptr(),
+            PUB_BYTE_LEN - 1,
+            h.as_ptr(),
+            LOGN,
+        );
+        assert_eq!(v, PUB_BYTE_LEN - 1);
+    }
+
+    // The private polynomials are left behind in these buffers
+    f.zeroize();
+    g.zeroize();
+    big_f.zeroize();
+    tmp.zeroize();
+
+    (pk, SecretBytes::new(sk))
+}
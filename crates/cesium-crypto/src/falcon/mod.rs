@@ -1,3 +1,14 @@
+use keypair::{SignerPair, VerifierPair, ViewOperations};
+use pqcrypto_falcon::falconpadded1024;
+use pqcrypto_traits::sign::{DetachedSignature, SecretKey};
+
+use crate::{
+    derivation::KeyBytes,
+    errors::CryptoError,
+    scheme::{Scheme, SignatureScheme},
+    secret::{zeroize_pod, SecretBytes},
+};
+
 // Falcon-1024-padded
 pub const PUB_BYTE_LEN: usize = 1793; // 897 (for falcon-512-padded)
 pub const SEC_BYTE_LEN: usize = 2305; // 1281 (for falcon-512-padded)
@@ -7,9 +18,44 @@ pub type PublicKeyBytes = [u8; PUB_BYTE_LEN];
 pub type SecretKeyBytes = [u8; SEC_BYTE_LEN];
 
 pub mod da;
+mod ffi;
 pub mod keypair;
 pub mod proofs;
 
+/// Marker type implementing `SignatureScheme` for Falcon-1024-padded
+pub struct Falcon;
+
+impl SignatureScheme for Falcon {
+    const SCHEME: Scheme = Scheme::Falcon;
+    const PUB_BYTE_LEN: usize = PUB_BYTE_LEN;
+    const SEC_BYTE_LEN: usize = SEC_BYTE_LEN;
+    const SIG_BYTE_LEN: usize = SIG_BYTE_LEN;
+    const DA_BYTE_LEN: usize = da::DA_BYTE_LEN;
+
+    fn keypair() -> (Vec<u8>, SecretBytes) {
+        SignerPair::create().to_bytes()
+    }
+
+    fn keypair_from_seed(seed: &KeyBytes) -> (Vec<u8>, SecretBytes) {
+        SignerPair::from_seed(seed).to_bytes()
+    }
+
+    fn sign(sec_key: &[u8], msg: &[u8]) -> Result<Vec<u8>, CryptoError> {
+        let mut sec_key = falconpadded1024::SecretKey::from_bytes(sec_key)?;
+        let sig = falconpadded1024::detached_sign(msg, &sec_key);
+        zeroize_pod(&mut sec_key);
+        Ok(sig.as_bytes().to_vec())
+    }
+
+    fn verify(pub_key: &[u8], msg: &[u8], sig: &[u8]) -> Result<bool, CryptoError> {
+        VerifierPair::from_bytes(pub_key)?.verify_detached(msg, sig)
+    }
+
+    fn display_address(pub_key: &[u8]) -> Result<String, CryptoError> {
+        Ok(da::DisplayAddress::try_from_pk(pub_key)?.as_str())
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use pqcrypto_falcon::falconpadded1024;
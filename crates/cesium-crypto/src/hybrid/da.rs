@@ -0,0 +1,84 @@
+use std::str::FromStr;
+
+use sha3::Digest;
+
+use crate::{address, errors::CryptoError};
+
+#[cfg(feature = "serde")]
+use crate::serializer;
+
+use super::{PublicKeyBytes, PUB_BYTE_LEN};
+
+// The display address is a hash over both halves of the hybrid public key,
+// it is thus not reversible and is merely used to identify the public key
+
+pub const DA_BYTE_LEN: usize = 32;
+pub type DABytes = [u8; DA_BYTE_LEN];
+
+pub struct DisplayAddress {
+    da: [u8; DA_BYTE_LEN],
+}
+
+impl DisplayAddress {
+    pub fn try_from_pk(id: &[u8]) -> Result<Self, CryptoError> {
+        if id.len() != PUB_BYTE_LEN {
+            return Err(CryptoError::InvalidDisplayAddress);
+        }
+
+        let mut hasher = sha3::Sha3_256::new();
+        hasher.update(id);
+        let mut da = [0u8; DA_BYTE_LEN];
+        da.copy_from_slice(&hasher.finalize());
+
+        Ok(Self { da })
+    }
+
+    pub fn from_pk(id: &PublicKeyBytes) -> Self {
+        let mut hasher = sha3::Sha3_256::new();
+        hasher.update(id);
+        let mut da = [0u8; DA_BYTE_LEN];
+        da.copy_from_slice(&hasher.finalize());
+
+        Self { da }
+    }
+
+    pub fn as_bytes(&self) -> &DABytes {
+        &self.da
+    }
+
+    pub fn from_bytes(da: &[u8]) -> Result<Self, CryptoError> {
+        if da.len() != DA_BYTE_LEN {
+            return Err(CryptoError::InvalidDisplayAddress);
+        }
+
+        Ok(Self {
+            da: unsafe { *(da.as_ptr() as *const [u8; DA_BYTE_LEN]) },
+        })
+    }
+
+    pub fn as_str(&self) -> String {
+        address::encode(&self.da)
+    }
+}
+
+impl FromStr for DisplayAddress {
+    type Err = CryptoError;
+
+    fn from_str(da: &str) -> Result<Self, CryptoError> {
+        Self::from_bytes(&address::decode(da, DA_BYTE_LEN)?)
+    }
+}
+
+#[cfg(feature = "serde")]
+impl serde::Serialize for DisplayAddress {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer::serialize_encoded(serializer, || self.as_str(), &self.da)
+    }
+}
+
+#[cfg(feature = "serde")]
+impl<'de> serde::Deserialize<'de> for DisplayAddress {
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        serializer::deserialize_encoded(deserializer, Self::from_str, Self::from_bytes)
+    }
+}
@@ -0,0 +1,400 @@
+use std::{fmt, path::Path};
+
+use ed25519_dalek::{Signature, SigningKey, VerifyingKey};
+use pqcrypto_mldsa::{mldsa44, mldsa44_keypair};
+use pqcrypto_traits::sign::{DetachedSignature, PublicKey as _, SecretKey, VerificationError};
+use rand::RngCore;
+use sha3::Digest;
+
+use crate::{
+    derivation::KeyBytes,
+    errors::CryptoError,
+    keystore::Keystore,
+    mldsa,
+    scheme::{AnyPublicKey, AnySignature, Scheme, Signer},
+    secret::{zeroize_pod, SecretBytes},
+};
+
+#[cfg(feature = "serde")]
+use crate::serializer;
+
+use super::{
+    da::DisplayAddress, split_signature, PublicKeyBytes, ED25519_PUB_BYTE_LEN,
+    ED25519_SEC_BYTE_LEN, HYBRID_DOMAIN, PUB_BYTE_LEN, SEC_BYTE_LEN, SIG_BYTE_LEN,
+};
+
+/// Both halves of a hybrid public key, encoded as ED25519_PK | MLDSA_PK
+#[derive(Clone)]
+pub struct PublicKey {
+    ed25519: VerifyingKey,
+    mldsa: mldsa44::PublicKey,
+    bytes: PublicKeyBytes,
+}
+
+impl PublicKey {
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self, CryptoError> {
+        if bytes.len() != PUB_BYTE_LEN {
+            return Err(CryptoError::InvalidKeyLength);
+        }
+
+        let (ed25519, mldsa) = bytes.split_at(ED25519_PUB_BYTE_LEN);
+        let ed25519 = VerifyingKey::from_bytes(ed25519.try_into().unwrap())
+            .map_err(|_| CryptoError::InvalidKeyLength)?;
+        let mldsa = mldsa44::PublicKey::from_bytes(mldsa)?;
+
+        Ok(Self {
+            ed25519,
+            mldsa,
+            bytes: bytes.try_into().unwrap(),
+        })
+    }
+
+    fn from_parts(ed25519: VerifyingKey, mldsa: mldsa44::PublicKey) -> Self {
+        let mut bytes = [0u8; PUB_BYTE_LEN];
+        bytes[..ED25519_PUB_BYTE_LEN].copy_from_slice(ed25519.as_bytes());
+        bytes[ED25519_PUB_BYTE_LEN..].copy_from_slice(mldsa.as_bytes());
+
+        Self {
+            ed25519,
+            mldsa,
+            bytes,
+        }
+    }
+
+    pub fn ed25519(&self) -> &VerifyingKey {
+        &self.ed25519
+    }
+
+    pub fn mldsa(&self) -> &mldsa44::PublicKey {
+        &self.mldsa
+    }
+
+    pub fn as_bytes(&self) -> &PublicKeyBytes {
+        &self.bytes
+    }
+}
+
+/// Splits a hybrid secret key, encoded as ED25519_SEED | MLDSA_SK
+pub fn split_sec_key(sec_key: &[u8]) -> Result<(SigningKey, mldsa44::SecretKey), CryptoError> {
+    if sec_key.len() != SEC_BYTE_LEN {
+        return Err(CryptoError::InvalidKeyLength);
+    }
+
+    let (ed25519, mldsa) = sec_key.split_at(ED25519_SEC_BYTE_LEN);
+    Ok((
+        SigningKey::from_bytes(ed25519.try_into().unwrap()),
+        mldsa44::SecretKey::from_bytes(mldsa)?,
+    ))
+}
+
+pub fn sign_detached(ed25519: &SigningKey, mldsa: &mldsa44::SecretKey, msg: &[u8]) -> Vec<u8> {
+    use ed25519_dalek::Signer as _;
+
+    let mut sig = Vec::with_capacity(SIG_BYTE_LEN);
+    sig.extend_from_slice(&ed25519.sign(msg).to_bytes());
+    sig.extend_from_slice(mldsa44::detached_sign(&domain_msg(msg), mldsa).as_bytes());
+    sig
+}
+
+// The ML-DSA half signs the message behind the hybrid domain, so it does not
+// verify as a plain ML-DSA signature once the Ed25519 half is stripped
+fn domain_msg(msg: &[u8]) -> Vec<u8> {
+    let mut bytes = Vec::with_capacity(HYBRID_DOMAIN.len() + msg.len());
+    bytes.extend_from_slice(HYBRID_DOMAIN);
+    bytes.extend_from_slice(msg);
+    bytes
+}
+
+// Each half gets its own seed, so the halves do not share key material
+fn half_seed(domain: &[u8], seed: &KeyBytes) -> KeyBytes {
+    let mut hasher = sha3::Sha3_256::new();
+    hasher.update(domain);
+    hasher.update(seed);
+    hasher.finalize().into()
+}
+
+pub trait ViewOperations {
+    fn pub_key(&self) -> &PublicKey;
+    fn pub_key_bytes(&self) -> &[u8; PUB_BYTE_LEN] {
+        self.pub_key().as_bytes()
+    }
+    /// Hybrid signatures are always detached, valid only if both halves verify
+    fn verify_detached(&self, msg: &[u8], sig: &[u8]) -> Result<bool, CryptoError> {
+        let (ed25519_sig, mldsa_sig) = split_signature(sig)?;
+
+        let ed25519_sig = Signature::from_bytes(ed25519_sig.try_into().unwrap());
+        if self
+            .pub_key()
+            .ed25519()
+            .verify_strict(msg, &ed25519_sig)
+            .is_err()
+        {
+            return Ok(false);
+        }
+
+        let ds = mldsa44::DetachedSignature::from_bytes(mldsa_sig)?;
+        match mldsa44::verify_detached_signature(&ds, &domain_msg(msg), self.pub_key().mldsa()) {
+            Ok(()) => Ok(true),
+            Err(VerificationError::InvalidSignature) => Ok(false),
+            Err(_) => Err(CryptoError::UnknownVerificationError),
+        }
+    }
+    fn get_da(&self) -> DisplayAddress {
+        DisplayAddress::from_pk(self.pub_key_bytes())
+    }
+}
+
+pub struct VerifierPair {
+    pub_key: PublicKey,
+}
+
+impl VerifierPair {
+    pub fn new(pub_key: &[u8]) -> Result<Self, CryptoError> {
+        Self::from_bytes(pub_key)
+    }
+
+    pub fn from_bytes(pub_key: &[u8]) -> Result<Self, CryptoError> {
+        Ok(Self {
+            pub_key: PublicKey::from_bytes(pub_key)?,
+        })
+    }
+
+    pub fn to_bytes(&self) -> Vec<u8> {
+        self.pub_key.as_bytes().to_vec()
+    }
+}
+
+// Public keys are hex in JSON
+#[cfg(feature = "serde")]
+impl serde::Serialize for VerifierPair {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer::serialize_hex(serializer, &self.to_bytes())
+    }
+}
+
+#[cfg(feature = "serde")]
+impl<'de> serde::Deserialize<'de> for VerifierPair {
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        serializer::deserialize_hex(deserializer, Self::from_bytes)
+    }
+}
+
+impl ViewOperations for VerifierPair {
+    fn pub_key(&self) -> &PublicKey {
+        &self.pub_key
+    }
+}
+
+pub struct SignerPair {
+    pub_key: PublicKey,
+    ed25519: SigningKey,
+    mldsa: mldsa44::SecretKey,
+}
+
+impl SignerPair {
+    pub fn create() -> Self {
+        let mut ed25519 = [0u8; ED25519_SEC_BYTE_LEN];
+        rand::thread_rng().fill_bytes(&mut ed25519);
+        let (pk, sk) = mldsa44_keypair();
+        Self::from_parts(SigningKey::from_bytes(&ed25519), pk, sk)
+    }
+
+    /// Deterministic keypair, the same seed always gives the same keys
+    pub fn from_seed(seed: &KeyBytes) -> Self {
+        let ed25519 = half_seed(b"cesium-hybrid-ed25519", seed);
+        let (pk, sk) =
+            mldsa::keypair::SignerPair::from_seed(&half_seed(HYBRID_DOMAIN, seed)).to_bytes();
+        Self::from_parts(
+            SigningKey::from_bytes(&ed25519),
+            mldsa44::PublicKey::from_bytes(&pk).unwrap(),
+            mldsa44::SecretKey::from_bytes(sk.expose()).unwrap(),
+        )
+    }
+
+    fn from_parts(ed25519: SigningKey, pk: mldsa44::PublicKey, sk: mldsa44::SecretKey) -> Self {
+        Self {
+            pub_key: PublicKey::from_parts(ed25519.verifying_key(), pk),
+            ed25519,
+            mldsa: sk,
+        }
+    }
+
+    pub fn sign_detached(&self, msg: &[u8]) -> Vec<u8> {
+        sign_detached(&self.ed25519, &self.mldsa, msg)
+    }
+
+    pub fn from_bytes(pub_key: &[u8], sec_key: &[u8]) -> Result<Self, CryptoError> {
+        let pub_key = PublicKey::from_bytes(pub_key)?;
+        let (ed25519, mldsa) = split_sec_key(sec_key)?;
+        Ok(Self {
+            pub_key,
+            ed25519,
+            mldsa,
+        })
+    }
+
+    pub fn to_bytes(&self) -> (Vec<u8>, SecretBytes) {
+        let mut sec_key = Vec::with_capacity(SEC_BYTE_LEN);
+        sec_key.extend_from_slice(self.ed25519.as_bytes());
+        sec_key.extend_from_slice(self.mldsa.as_bytes());
+
+        (self.pub_key.as_bytes().to_vec(), SecretBytes::new(sec_key))
+    }
+
+    pub fn to_keystore(&self, password: &[u8]) -> Result<Keystore, CryptoError> {
+        let (pub_key, sec_key) = self.to_bytes();
+        Keystore::encrypt(Scheme::Hybrid, &pub_key, sec_key.expose(), password)
+    }
+
+    pub fn from_keystore(keystore: &Keystore, password: &[u8]) -> Result<Self, CryptoError> {
+        if keystore.scheme() != Scheme::Hybrid {
+            return Err(CryptoError::SchemeMismatch);
+        }
+
+        Self::from_bytes(keystore.pub_key(), keystore.decrypt(password)?.expose())
+    }
+
+    pub fn save_keystore(&self, path: &Path, password: &[u8]) -> Result<(), CryptoError> {
+        self.to_keystore(password)?.save(path)
+    }
+
+    pub fn load_keystore(path: &Path, password: &[u8]) -> Result<Self, CryptoError> {
+        Self::from_keystore(&Keystore::load(path)?, password)
+    }
+}
+
+impl ViewOperations for SignerPair {
+    fn pub_key(&self) -> &PublicKey {
+        &self.pub_key
+    }
+}
+
+// The Ed25519 half wipes itself
+impl Drop for SignerPair {
+    fn drop(&mut self) {
+        zeroize_pod(&mut self.mldsa);
+    }
+}
+
+// Only the address is printed, never the secret key
+impl fmt::Debug for SignerPair {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("SignerPair")
+            .field("address", &self.get_da().as_str())
+            .field("sec_key", &"[REDACTED]")
+            .finish()
+    }
+}
+
+impl Signer for SignerPair {
+    fn public_key(&self) -> AnyPublicKey {
+        AnyPublicKey::new(Scheme::Hybrid, self.pub_key_bytes()).unwrap()
+    }
+
+    fn sign_any(&self, msg: &[u8]) -> AnySignature {
+        AnySignature::new(Scheme::Hybrid, &self.sign_detached(msg)).unwrap()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::hybrid::ED25519_SIG_BYTE_LEN;
+
+    #[test]
+    fn test_signer_sign_detached() {
+        let signer = SignerPair::create();
+        let msg = b"Hello, World!";
+        let sig = signer.sign_detached(msg);
+
+        assert_eq!(sig.len(), SIG_BYTE_LEN);
+        assert!(signer.verify_detached(msg, &sig).unwrap());
+        assert!(!signer.verify_detached(b"Hello, Wurld!", &sig).unwrap());
+    }
+
+    #[test]
+    fn test_signer_both_halves() {
+        let signer = SignerPair::create();
+        let other = SignerPair::create();
+        let msg = b"Hello, World!";
+        let sig = signer.sign_detached(msg);
+        let other_sig = other.sign_detached(msg);
+
+        // Swapping in either half of another key's signature must fail
+        let mut mixed = other_sig[..ED25519_SIG_BYTE_LEN].to_vec();
+        mixed.extend_from_slice(&sig[ED25519_SIG_BYTE_LEN..]);
+        assert!(!signer.verify_detached(msg, &mixed).unwrap());
+
+        let mut mixed = sig[..ED25519_SIG_BYTE_LEN].to_vec();
+        mixed.extend_from_slice(&other_sig[ED25519_SIG_BYTE_LEN..]);
+        assert!(!signer.verify_detached(msg, &mixed).unwrap());
+
+        // The ML-DSA half alone is not a plain ML-DSA signature of the message
+        let mldsa = mldsa::keypair::VerifierPair::new(signer.pub_key().mldsa().as_bytes()).unwrap();
+        assert!(!mldsa::keypair::ViewOperations::verify_detached(
+            &mldsa,
+            msg,
+            &sig[ED25519_SIG_BYTE_LEN..]
+        )
+        .unwrap());
+
+        assert!(signer.verify_detached(msg, &sig[1..]).is_err());
+    }
+
+    #[test]
+    fn test_signer_from_seed() {
+        let signer = SignerPair::from_seed(&[7u8; 32]);
+        let signer2 = SignerPair::from_seed(&[7u8; 32]);
+        let signer3 = SignerPair::from_seed(&[8u8; 32]);
+        assert_eq!(signer.to_bytes(), signer2.to_bytes());
+        assert_ne!(signer.pub_key_bytes(), signer3.pub_key_bytes());
+
+        let msg = b"Hello, World!";
+        let sig = signer.sign_detached(msg);
+        assert!(signer2.verify_detached(msg, &sig).unwrap());
+        assert!(!signer3.verify_detached(msg, &sig).unwrap());
+    }
+
+    #[test]
+    fn test_verifier_verify() {
+        let signer = SignerPair::create();
+        let msg = b"Hello, World!";
+        let sig = signer.sign_detached(msg);
+
+        let verifier = VerifierPair::new(signer.pub_key_bytes()).unwrap();
+        assert!(verifier.verify_detached(msg, &sig).unwrap());
+    }
+
+    #[test]
+    fn test_display_address() {
+        let signer = SignerPair::create();
+        let da = signer.get_da();
+
+        let verifier = VerifierPair::new(signer.pub_key_bytes()).unwrap();
+        assert_eq!(da.as_str(), verifier.get_da().as_str());
+
+        let da2: DisplayAddress = da.as_str().parse().unwrap();
+        assert_eq!(da2.as_bytes(), da.as_bytes());
+    }
+
+    #[test]
+    fn test_signer_bytes() {
+        let signer = SignerPair::create();
+        let (pub_key, sec_key) = signer.to_bytes();
+        assert_eq!(sec_key.len(), SEC_BYTE_LEN);
+
+        let signer2 = SignerPair::from_bytes(&pub_key, sec_key.expose()).unwrap();
+        assert_eq!(signer.to_bytes(), signer2.to_bytes());
+        assert!(SignerPair::from_bytes(&pub_key, &sec_key.expose()[1..]).is_err());
+    }
+
+    #[test]
+    fn test_verifier_bytes() {
+        let signer = SignerPair::create();
+        let verifier = VerifierPair::new(signer.pub_key_bytes()).unwrap();
+        let pub_key = verifier.to_bytes();
+
+        let verifier2 = VerifierPair::from_bytes(&pub_key).unwrap();
+        assert_eq!(verifier.pub_key_bytes(), verifier2.pub_key_bytes());
+    }
+}
@@ -0,0 +1,73 @@
+use keypair::{VerifierPair, ViewOperations};
+
+use crate::{
+    derivation::KeyBytes,
+    errors::CryptoError,
+    mldsa,
+    scheme::{Scheme, SignatureScheme},
+    secret::{zeroize_pod, SecretBytes},
+};
+
+// A hybrid key is an Ed25519 key next to an ML-DSA-44 key, a signature is
+// only valid if both halves verify. The Ed25519 half signs the plain message
+// so existing Ed25519 tooling can check it on its own, the ML-DSA half signs
+// the message behind HYBRID_DOMAIN so it can not be passed off as a plain
+// ML-DSA signature.
+
+pub const ED25519_PUB_BYTE_LEN: usize = 32;
+pub const ED25519_SEC_BYTE_LEN: usize = 32;
+pub const ED25519_SIG_BYTE_LEN: usize = 64;
+
+pub const PUB_BYTE_LEN: usize = ED25519_PUB_BYTE_LEN + mldsa::PUB_BYTE_LEN;
+pub const SEC_BYTE_LEN: usize = ED25519_SEC_BYTE_LEN + mldsa::SEC_BYTE_LEN;
+pub const SIG_BYTE_LEN: usize = ED25519_SIG_BYTE_LEN + mldsa::SIG_BYTE_LEN;
+
+pub const HYBRID_DOMAIN: &[u8] = b"cesium-hybrid-mldsa";
+
+pub type PublicKeyBytes = [u8; PUB_BYTE_LEN];
+
+pub mod da;
+pub mod keypair;
+
+/// Splits a hybrid signature into its Ed25519 and ML-DSA halves
+pub fn split_signature(sig: &[u8]) -> Result<(&[u8], &[u8]), CryptoError> {
+    if sig.len() != SIG_BYTE_LEN {
+        return Err(CryptoError::InvalidSignature);
+    }
+
+    Ok(sig.split_at(ED25519_SIG_BYTE_LEN))
+}
+
+/// Marker type implementing `SignatureScheme` for Ed25519 + ML-DSA-44
+pub struct Hybrid;
+
+impl SignatureScheme for Hybrid {
+    const SCHEME: Scheme = Scheme::Hybrid;
+    const PUB_BYTE_LEN: usize = PUB_BYTE_LEN;
+    const SEC_BYTE_LEN: usize = SEC_BYTE_LEN;
+    const SIG_BYTE_LEN: usize = SIG_BYTE_LEN;
+    const DA_BYTE_LEN: usize = da::DA_BYTE_LEN;
+
+    fn keypair() -> (Vec<u8>, SecretBytes) {
+        keypair::SignerPair::create().to_bytes()
+    }
+
+    fn keypair_from_seed(seed: &KeyBytes) -> (Vec<u8>, SecretBytes) {
+        keypair::SignerPair::from_seed(seed).to_bytes()
+    }
+
+    fn sign(sec_key: &[u8], msg: &[u8]) -> Result<Vec<u8>, CryptoError> {
+        let (ed25519_key, mut mldsa_key) = keypair::split_sec_key(sec_key)?;
+        let sig = keypair::sign_detached(&ed25519_key, &mldsa_key, msg);
+        zeroize_pod(&mut mldsa_key);
+        Ok(sig)
+    }
+
+    fn verify(pub_key: &[u8], msg: &[u8], sig: &[u8]) -> Result<bool, CryptoError> {
+        VerifierPair::from_bytes(pub_key)?.verify_detached(msg, sig)
+    }
+
+    fn display_address(pub_key: &[u8]) -> Result<String, CryptoError> {
+        Ok(da::DisplayAddress::try_from_pk(pub_key)?.as_str())
+    }
+}
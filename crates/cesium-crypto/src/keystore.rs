@@ -0,0 +1,313 @@
+use std::{io::Write, path::Path};
+
+use argon2::{Algorithm, Argon2, Params, Version};
+use chacha20poly1305::{
+    aead::{Aead, KeyInit, Payload},
+    Key, XChaCha20Poly1305, XNonce,
+};
+use rand::RngCore;
+
+use crate::{
+    errors::CryptoError,
+    scheme::Scheme,
+    secret::{SecretBytes, Zeroizing},
+};
+
+// Keystore layout, integers are little endian:
+// MAGIC (4) | VERSION (1) | SCHEME (1) | M_COST (4) | T_COST (4) | P_COST (4)
+// | SALT (16) | NONCE (24) | PUB_KEY_LEN (4) | PUB_KEY | CIPHERTEXT
+// Everything before the ciphertext is authenticated as associated data, so
+// the scheme and public key (and with it the address) can be read without
+// the password but not tampered with.
+
+pub const MAGIC: &[u8; 4] = b"CSKS";
+pub const VERSION: u8 = 1;
+pub const SALT_BYTE_LEN: usize = 16;
+pub const NONCE_BYTE_LEN: usize = 24;
+pub const KEY_BYTE_LEN: usize = 32;
+pub const TAG_BYTE_LEN: usize = 16;
+pub const HEADER_BYTE_LEN: usize = 4 + 1 + 1 + 12 + SALT_BYTE_LEN + NONCE_BYTE_LEN + 4;
+
+macro_rules! bounds_check {
+    ($bytes:expr, $len:expr) => {
+        if $bytes.len() < $len {
+            return Err(CryptoError::InvalidKeystore);
+        }
+    };
+}
+
+/// Argon2id cost parameters, memory is given in KiB
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct KdfParams {
+    pub m_cost: u32,
+    pub t_cost: u32,
+    pub p_cost: u32,
+}
+
+impl Default for KdfParams {
+    fn default() -> Self {
+        Self {
+            m_cost: 64 * 1024,
+            t_cost: 3,
+            p_cost: 1,
+        }
+    }
+}
+
+impl KdfParams {
+    fn derive_key(
+        &self,
+        password: &[u8],
+        salt: &[u8],
+    ) -> Result<Zeroizing<[u8; KEY_BYTE_LEN]>, CryptoError> {
+        let params = Params::new(self.m_cost, self.t_cost, self.p_cost, Some(KEY_BYTE_LEN))
+            .map_err(|_| CryptoError::InvalidKeystore)?;
+        let argon2 = Argon2::new(Algorithm::Argon2id, Version::V0x13, params);
+
+        let mut key = Zeroizing::new([0u8; KEY_BYTE_LEN]);
+        argon2
+            .hash_password_into(password, salt, key.as_mut())
+            .map_err(|_| CryptoError::InvalidKeystore)?;
+        Ok(key)
+    }
+}
+
+pub struct Keystore {
+    scheme: Scheme,
+    kdf: KdfParams,
+    salt: [u8; SALT_BYTE_LEN],
+    nonce: [u8; NONCE_BYTE_LEN],
+    pub_key: Vec<u8>,
+    ciphertext: Vec<u8>,
+}
+
+impl Keystore {
+    pub fn encrypt(
+        scheme: Scheme,
+        pub_key: &[u8],
+        sec_key: &[u8],
+        password: &[u8],
+    ) -> Result<Self, CryptoError> {
+        Self::encrypt_with_params(scheme, pub_key, sec_key, password, KdfParams::default())
+    }
+
+    pub fn encrypt_with_params(
+        scheme: Scheme,
+        pub_key: &[u8],
+        sec_key: &[u8],
+        password: &[u8],
+        kdf: KdfParams,
+    ) -> Result<Self, CryptoError> {
+        if pub_key.len() != scheme.pub_byte_len()? {
+            return Err(CryptoError::InvalidKeyLength);
+        }
+
+        let mut rng = rand::thread_rng();
+        let mut salt = [0u8; SALT_BYTE_LEN];
+        let mut nonce = [0u8; NONCE_BYTE_LEN];
+        rng.fill_bytes(&mut salt);
+        rng.fill_bytes(&mut nonce);
+
+        let mut keystore = Self {
+            scheme,
+            kdf,
+            salt,
+            nonce,
+            pub_key: pub_key.to_vec(),
+            ciphertext: Vec::new(),
+        };
+
+        let key = kdf.derive_key(password, &salt)?;
+        let cipher = XChaCha20Poly1305::new(Key::from_slice(key.as_slice()));
+        let payload = Payload {
+            msg: sec_key,
+            aad: &keystore.header(),
+        };
+        keystore.ciphertext = cipher
+            .encrypt(XNonce::from_slice(&nonce), payload)
+            .map_err(|_| CryptoError::InvalidKeystore)?;
+
+        Ok(keystore)
+    }
+
+    /// Returns the secret key, fails if the password is wrong or the
+    /// keystore was modified
+    pub fn decrypt(&self, password: &[u8]) -> Result<SecretBytes, CryptoError> {
+        let key = self.kdf.derive_key(password, &self.salt)?;
+        let cipher = XChaCha20Poly1305::new(Key::from_slice(key.as_slice()));
+        let payload = Payload {
+            msg: &self.ciphertext,
+            aad: &self.header(),
+        };
+        cipher
+            .decrypt(XNonce::from_slice(&self.nonce), payload)
+            .map(SecretBytes::new)
+            .map_err(|_| CryptoError::KeystoreDecryptionError)
+    }
+
+    pub fn scheme(&self) -> Scheme {
+        self.scheme
+    }
+
+    pub fn kdf_params(&self) -> KdfParams {
+        self.kdf
+    }
+
+    pub fn pub_key(&self) -> &[u8] {
+        &self.pub_key
+    }
+
+    pub fn address(&self) -> Result<String, CryptoError> {
+        self.scheme.display_address(&self.pub_key)
+    }
+
+    fn header(&self) -> Vec<u8> {
+        let mut bytes = Vec::with_capacity(HEADER_BYTE_LEN + self.pub_key.len());
+        bytes.extend_from_slice(MAGIC);
+        bytes.push(VERSION);
+        bytes.push(self.scheme.to_u8());
+        bytes.extend(self.kdf.m_cost.to_le_bytes());
+        bytes.extend(self.kdf.t_cost.to_le_bytes());
+        bytes.extend(self.kdf.p_cost.to_le_bytes());
+        bytes.extend_from_slice(&self.salt);
+        bytes.extend_from_slice(&self.nonce);
+        bytes.extend((self.pub_key.len() as u32).to_le_bytes());
+        bytes.extend_from_slice(&self.pub_key);
+        bytes
+    }
+
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let mut bytes = self.header();
+        bytes.extend_from_slice(&self.ciphertext);
+        bytes
+    }
+
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self, CryptoError> {
+        bounds_check!(bytes, HEADER_BYTE_LEN);
+        if &bytes[0..4] != MAGIC || bytes[4] != VERSION {
+            return Err(CryptoError::InvalidKeystore);
+        }
+
+        let scheme = Scheme::from_u8(bytes[5]).ok_or(CryptoError::UnsupportedScheme(bytes[5]))?;
+        let mut offset = 6;
+
+        let read_u32 = |offset: &mut usize| {
+            let value = u32::from_le_bytes(bytes[*offset..*offset + 4].try_into().unwrap());
+            *offset += 4;
+            value
+        };
+        let kdf = KdfParams {
+            m_cost: read_u32(&mut offset),
+            t_cost: read_u32(&mut offset),
+            p_cost: read_u32(&mut offset),
+        };
+
+        let mut salt = [0u8; SALT_BYTE_LEN];
+        salt.copy_from_slice(&bytes[offset..offset + SALT_BYTE_LEN]);
+        offset += SALT_BYTE_LEN;
+
+        let mut nonce = [0u8; NONCE_BYTE_LEN];
+        nonce.copy_from_slice(&bytes[offset..offset + NONCE_BYTE_LEN]);
+        offset += NONCE_BYTE_LEN;
+
+        let pub_key_len = read_u32(&mut offset) as usize;
+        if pub_key_len != scheme.pub_byte_len()? {
+            return Err(CryptoError::InvalidKeystore);
+        }
+        bounds_check!(bytes[offset..], pub_key_len + TAG_BYTE_LEN);
+        let pub_key = bytes[offset..offset + pub_key_len].to_vec();
+        offset += pub_key_len;
+
+        Ok(Self {
+            scheme,
+            kdf,
+            salt,
+            nonce,
+            pub_key,
+            ciphertext: bytes[offset..].to_vec(),
+        })
+    }
+
+    pub fn save(&self, path: &Path) -> Result<(), CryptoError> {
+        let mut options = std::fs::OpenOptions::new();
+        options.write(true).create(true).truncate(true);
+        // Only the owner may read the keystore
+        #[cfg(unix)]
+        std::os::unix::fs::OpenOptionsExt::mode(&mut options, 0o600);
+
+        let mut file = options.open(path)?;
+        file.write_all(&self.to_bytes())?;
+        Ok(())
+    }
+
+    pub fn load(path: &Path) -> Result<Self, CryptoError> {
+        Self::from_bytes(&std::fs::read(path)?)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // Keep the tests fast, the default parameters take about a second
+    const TEST_KDF: KdfParams = KdfParams {
+        m_cost: 256,
+        t_cost: 1,
+        p_cost: 1,
+    };
+
+    #[cfg(feature = "mldsa")]
+    fn test_keystore(password: &[u8]) -> (Keystore, SecretBytes) {
+        let signer = crate::mldsa::keypair::SignerPair::create();
+        let (pk, sk) = signer.to_bytes();
+        let keystore =
+            Keystore::encrypt_with_params(Scheme::MlDsa, &pk, sk.expose(), password, TEST_KDF)
+                .unwrap();
+        (keystore, sk)
+    }
+
+    #[cfg(feature = "mldsa")]
+    #[test]
+    fn test_encrypt_decrypt() {
+        let (keystore, sk) = test_keystore(b"hunter2");
+        assert_eq!(keystore.decrypt(b"hunter2").unwrap(), sk);
+        assert!(matches!(
+            keystore.decrypt(b"hunter3"),
+            Err(CryptoError::KeystoreDecryptionError)
+        ));
+    }
+
+    #[cfg(feature = "mldsa")]
+    #[test]
+    fn test_bytes() {
+        let (keystore, sk) = test_keystore(b"hunter2");
+        let bytes = keystore.to_bytes();
+        assert_eq!(
+            bytes.len(),
+            HEADER_BYTE_LEN + keystore.pub_key().len() + sk.len() + TAG_BYTE_LEN
+        );
+
+        let keystore2 = Keystore::from_bytes(&bytes).unwrap();
+        assert_eq!(keystore2.scheme(), Scheme::MlDsa);
+        assert_eq!(keystore2.kdf_params(), TEST_KDF);
+        assert_eq!(keystore2.address().unwrap(), keystore.address().unwrap());
+        assert_eq!(keystore2.decrypt(b"hunter2").unwrap(), sk);
+
+        assert!(Keystore::from_bytes(&bytes[..HEADER_BYTE_LEN]).is_err());
+        let mut bad_magic = bytes.clone();
+        bad_magic[0] = b'X';
+        assert!(Keystore::from_bytes(&bad_magic).is_err());
+    }
+
+    #[cfg(feature = "mldsa")]
+    #[test]
+    fn test_tampered_metadata() {
+        let (keystore, _) = test_keystore(b"hunter2");
+        let mut bytes = keystore.to_bytes();
+
+        // Flip a bit of the public key, the header is authenticated
+        bytes[HEADER_BYTE_LEN] ^= 1;
+        let keystore = Keystore::from_bytes(&bytes).unwrap();
+        assert!(keystore.decrypt(b"hunter2").is_err());
+    }
+}
@@ -1,15 +1,40 @@
+pub mod address;
+pub mod batch;
+pub mod beacon;
+pub mod derivation;
 pub mod errors;
-// pub mod polynomial;
-// pub mod serializer;
+pub mod keystore;
+pub mod merkle;
+pub mod mnemonic;
+pub mod scheme;
+pub mod secret;
+
+#[cfg(feature = "serde")]
+pub mod serializer;
 
 #[cfg(feature = "bulletproofs")]
 pub mod bulletproofs;
 
+#[cfg(feature = "bulletproofs")]
+pub mod polynomial;
+
+#[cfg(feature = "shamir")]
+pub mod shamir;
+
+#[cfg(feature = "erasure")]
+pub mod erasure;
+
 #[cfg(feature = "mldsa")]
 pub mod mldsa;
 
+#[cfg(feature = "mlkem")]
+pub mod mlkem;
+
 #[cfg(feature = "sphincsplus")]
 pub mod sphincsplus;
 
 #[cfg(feature = "falcon")]
 pub mod falcon;
+
+#[cfg(feature = "hybrid")]
+pub mod hybrid;
@@ -0,0 +1,439 @@
+use sha3::{Digest, Sha3_256};
+
+use crate::errors::CryptoError;
+
+// Leaves and inner nodes are hashed with different prefixes, so an inner
+// node can never be passed off as a leaf. A node without a sibling is carried
+// up to the next level as is instead of being paired with itself.
+
+pub const HASH_BYTE_LEN: usize = 32;
+const LEAF_PREFIX: u8 = 0x00;
+const NODE_PREFIX: u8 = 0x01;
+
+pub type Hash = [u8; HASH_BYTE_LEN];
+
+/// Root of a tree without leaves
+pub const EMPTY_ROOT: Hash = [0u8; HASH_BYTE_LEN];
+
+macro_rules! bounds_check {
+    ($bytes:expr, $len:expr) => {
+        if $bytes.len() < $len {
+            return Err(CryptoError::InvalidMerkleProof);
+        }
+    };
+}
+
+pub fn leaf_hash(data: &[u8]) -> Hash {
+    let mut hasher = Sha3_256::new();
+    hasher.update([LEAF_PREFIX]);
+    hasher.update(data);
+    hasher.finalize().into()
+}
+
+pub fn node_hash(left: &Hash, right: &Hash) -> Hash {
+    let mut hasher = Sha3_256::new();
+    hasher.update([NODE_PREFIX]);
+    hasher.update(left);
+    hasher.update(right);
+    hasher.finalize().into()
+}
+
+// Width of every level from the leaves up to the root
+fn level_widths(leaf_count: usize) -> Vec<usize> {
+    let mut widths = vec![leaf_count];
+    let mut width = leaf_count;
+    while width > 1 {
+        width = width.div_ceil(2);
+        widths.push(width);
+    }
+    widths
+}
+
+pub struct MerkleTree {
+    // levels[0] holds the leaf hashes, the last level holds the root
+    levels: Vec<Vec<Hash>>,
+}
+
+impl MerkleTree {
+    pub fn new<T: AsRef<[u8]>>(leaves: &[T]) -> Self {
+        let mut levels = vec![leaves
+            .iter()
+            .map(|leaf| leaf_hash(leaf.as_ref()))
+            .collect::<Vec<Hash>>()];
+
+        while levels.last().unwrap().len() > 1 {
+            let next = levels
+                .last()
+                .unwrap()
+                .chunks(2)
+                .map(|pair| match pair {
+                    [left, right] => node_hash(left, right),
+                    [single] => *single,
+                    _ => unreachable!(),
+                })
+                .collect();
+            levels.push(next);
+        }
+
+        Self { levels }
+    }
+
+    pub fn leaf_count(&self) -> usize {
+        self.levels[0].len()
+    }
+
+    pub fn root(&self) -> Hash {
+        match self.levels.last().unwrap().first() {
+            Some(root) => *root,
+            None => EMPTY_ROOT,
+        }
+    }
+
+    pub fn proof(&self, index: usize) -> Option<MerkleProof> {
+        if index >= self.leaf_count() {
+            return None;
+        }
+
+        let mut siblings = Vec::new();
+        let mut position = index;
+        for level in &self.levels[..self.levels.len() - 1] {
+            if let Some(sibling) = level.get(position ^ 1) {
+                siblings.push(*sibling);
+            }
+            position /= 2;
+        }
+
+        Some(MerkleProof {
+            index: index as u64,
+            leaf_count: self.leaf_count() as u64,
+            siblings,
+        })
+    }
+
+    /// A single proof for several leaves, hashes shared between their paths
+    /// are only included once
+    pub fn multi_proof(&self, indices: &[usize]) -> Option<MerkleMultiProof> {
+        let mut known: Vec<usize> = indices.to_vec();
+        known.sort_unstable();
+        known.dedup();
+        if known.is_empty() || *known.last().unwrap() >= self.leaf_count() {
+            return None;
+        }
+
+        let leaf_indices = known.iter().map(|&i| i as u64).collect();
+        let mut hashes = Vec::new();
+        for level in &self.levels[..self.levels.len() - 1] {
+            for &position in &known {
+                let sibling = position ^ 1;
+                if sibling < level.len() && known.binary_search(&sibling).is_err() {
+                    hashes.push(level[sibling]);
+                }
+            }
+            known = known.iter().map(|position| position / 2).collect();
+            known.dedup();
+        }
+
+        Some(MerkleMultiProof {
+            leaf_count: self.leaf_count() as u64,
+            indices: leaf_indices,
+            hashes,
+        })
+    }
+}
+
+/// Proof that a leaf is part of a tree, holds one sibling per level
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct MerkleProof {
+    pub index: u64,
+    pub leaf_count: u64,
+    pub siblings: Vec<Hash>,
+}
+
+impl MerkleProof {
+    pub fn verify(&self, root: &Hash, leaf: &[u8]) -> bool {
+        self.verify_hash(root, &leaf_hash(leaf))
+    }
+
+    pub fn verify_hash(&self, root: &Hash, leaf_hash: &Hash) -> bool {
+        if self.index >= self.leaf_count {
+            return false;
+        }
+
+        let mut siblings = self.siblings.iter();
+        let mut position = self.index as usize;
+        let mut hash = *leaf_hash;
+        for width in level_widths(self.leaf_count as usize) {
+            if width == 1 {
+                break;
+            }
+            if position % 2 == 1 {
+                match siblings.next() {
+                    Some(sibling) => hash = node_hash(sibling, &hash),
+                    None => return false,
+                }
+            } else if position + 1 < width {
+                match siblings.next() {
+                    Some(sibling) => hash = node_hash(&hash, sibling),
+                    None => return false,
+                }
+            }
+            position /= 2;
+        }
+
+        siblings.next().is_none() && hash == *root
+    }
+
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let mut bytes = Vec::with_capacity(16 + self.siblings.len() * HASH_BYTE_LEN);
+        bytes.extend(self.index.to_le_bytes());
+        bytes.extend(self.leaf_count.to_le_bytes());
+        for sibling in &self.siblings {
+            bytes.extend_from_slice(sibling);
+        }
+        bytes
+    }
+
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self, CryptoError> {
+        bounds_check!(bytes, 16);
+        let index = u64::from_le_bytes(bytes[0..8].try_into().unwrap());
+        let leaf_count = u64::from_le_bytes(bytes[8..16].try_into().unwrap());
+
+        let rest = &bytes[16..];
+        if !rest.len().is_multiple_of(HASH_BYTE_LEN) {
+            return Err(CryptoError::InvalidMerkleProof);
+        }
+        let siblings = rest
+            .chunks_exact(HASH_BYTE_LEN)
+            .map(|chunk| chunk.try_into().unwrap())
+            .collect();
+
+        Ok(Self {
+            index,
+            leaf_count,
+            siblings,
+        })
+    }
+}
+
+/// Proof that several leaves are part of a tree, the leaves are given in
+/// ascending index order when verifying
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct MerkleMultiProof {
+    pub leaf_count: u64,
+    pub indices: Vec<u64>,
+    pub hashes: Vec<Hash>,
+}
+
+impl MerkleMultiProof {
+    pub fn verify<T: AsRef<[u8]>>(&self, root: &Hash, leaves: &[T]) -> bool {
+        let hashes: Vec<Hash> = leaves.iter().map(|l| leaf_hash(l.as_ref())).collect();
+        self.verify_hashes(root, &hashes)
+    }
+
+    pub fn verify_hashes(&self, root: &Hash, leaf_hashes: &[Hash]) -> bool {
+        if self.indices.is_empty()
+            || self.indices.len() != leaf_hashes.len()
+            || self.indices.windows(2).any(|pair| pair[0] >= pair[1])
+            || *self.indices.last().unwrap() >= self.leaf_count
+        {
+            return false;
+        }
+
+        let mut known: Vec<(usize, Hash)> = self
+            .indices
+            .iter()
+            .map(|&i| i as usize)
+            .zip(leaf_hashes.iter().copied())
+            .collect();
+        let mut proof_hashes = self.hashes.iter();
+
+        for width in level_widths(self.leaf_count as usize) {
+            if width == 1 {
+                break;
+            }
+
+            let mut next = Vec::with_capacity(known.len());
+            let mut i = 0;
+            while i < known.len() {
+                let (position, hash) = known[i];
+                let parent = if position % 2 == 1 {
+                    match proof_hashes.next() {
+                        Some(sibling) => node_hash(sibling, &hash),
+                        None => return false,
+                    }
+                } else if known.get(i + 1).map(|(p, _)| *p) == Some(position + 1) {
+                    i += 1;
+                    node_hash(&hash, &known[i].1)
+                } else if position + 1 < width {
+                    match proof_hashes.next() {
+                        Some(sibling) => node_hash(&hash, sibling),
+                        None => return false,
+                    }
+                } else {
+                    hash
+                };
+                next.push((position / 2, parent));
+                i += 1;
+            }
+            known = next;
+        }
+
+        proof_hashes.next().is_none() && known.len() == 1 && known[0].1 == *root
+    }
+
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let mut bytes = Vec::new();
+        bytes.extend(self.leaf_count.to_le_bytes());
+        bytes.extend((self.indices.len() as u32).to_le_bytes());
+        for index in &self.indices {
+            bytes.extend(index.to_le_bytes());
+        }
+        for hash in &self.hashes {
+            bytes.extend_from_slice(hash);
+        }
+        bytes
+    }
+
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self, CryptoError> {
+        bounds_check!(bytes, 12);
+        let leaf_count = u64::from_le_bytes(bytes[0..8].try_into().unwrap());
+        let index_count = u32::from_le_bytes(bytes[8..12].try_into().unwrap()) as usize;
+        let mut offset = 12;
+
+        bounds_check!(bytes[offset..], index_count * 8);
+        let indices = bytes[offset..offset + index_count * 8]
+            .chunks_exact(8)
+            .map(|chunk| u64::from_le_bytes(chunk.try_into().unwrap()))
+            .collect();
+        offset += index_count * 8;
+
+        let rest = &bytes[offset..];
+        if !rest.len().is_multiple_of(HASH_BYTE_LEN) {
+            return Err(CryptoError::InvalidMerkleProof);
+        }
+        let hashes = rest
+            .chunks_exact(HASH_BYTE_LEN)
+            .map(|chunk| chunk.try_into().unwrap())
+            .collect();
+
+        Ok(Self {
+            leaf_count,
+            indices,
+            hashes,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn leaves(count: usize) -> Vec<Vec<u8>> {
+        (0..count)
+            .map(|i| format!("tx{}", i).into_bytes())
+            .collect()
+    }
+
+    #[test]
+    fn test_root() {
+        assert_eq!(MerkleTree::new::<Vec<u8>>(&[]).root(), EMPTY_ROOT);
+        assert_eq!(MerkleTree::new(&[b"a"]).root(), leaf_hash(b"a"));
+
+        let expected = node_hash(
+            &node_hash(&leaf_hash(b"a"), &leaf_hash(b"b")),
+            &leaf_hash(b"c"),
+        );
+        assert_eq!(MerkleTree::new(&[b"a", b"b", b"c"]).root(), expected);
+
+        // Duplicating the last leaf changes the root
+        assert_ne!(MerkleTree::new(&[b"a", b"b", b"c", b"c"]).root(), expected);
+    }
+
+    #[test]
+    fn test_domain_separation() {
+        // An inner node presented as a leaf does not verify
+        let tree = MerkleTree::new(&leaves(4));
+        let inner = [tree.levels[1][0], tree.levels[1][1]].concat();
+        let proof = MerkleProof {
+            index: 0,
+            leaf_count: 1,
+            siblings: vec![],
+        };
+        assert!(!proof.verify(&tree.root(), &inner));
+    }
+
+    #[test]
+    fn test_proofs() {
+        for count in [1, 2, 3, 5, 8, 13] {
+            let leaves = leaves(count);
+            let tree = MerkleTree::new(&leaves);
+            let root = tree.root();
+
+            for (i, leaf) in leaves.iter().enumerate() {
+                let proof = tree.proof(i).unwrap();
+                assert!(proof.verify(&root, leaf));
+                assert!(!proof.verify(&root, b"other"));
+
+                let proof2 = MerkleProof::from_bytes(&proof.to_bytes()).unwrap();
+                assert_eq!(proof2, proof);
+            }
+            assert!(tree.proof(count).is_none());
+        }
+    }
+
+    #[test]
+    fn test_tampered_proof() {
+        let leaves = leaves(6);
+        let tree = MerkleTree::new(&leaves);
+        let root = tree.root();
+
+        let mut proof = tree.proof(2).unwrap();
+        proof.index = 3;
+        assert!(!proof.verify(&root, &leaves[2]));
+
+        let mut proof = tree.proof(2).unwrap();
+        proof.siblings[0][0] ^= 1;
+        assert!(!proof.verify(&root, &leaves[2]));
+
+        let mut proof = tree.proof(2).unwrap();
+        proof.siblings.push([0u8; HASH_BYTE_LEN]);
+        assert!(!proof.verify(&root, &leaves[2]));
+    }
+
+    #[test]
+    fn test_multi_proofs() {
+        let leaves = leaves(11);
+        let tree = MerkleTree::new(&leaves);
+        let root = tree.root();
+
+        for indices in [
+            vec![0],
+            vec![3, 4],
+            vec![0, 1, 2, 3],
+            vec![1, 6, 10],
+            vec![10],
+        ] {
+            let proof = tree.multi_proof(&indices).unwrap();
+            let selected: Vec<&Vec<u8>> = indices.iter().map(|&i| &leaves[i]).collect();
+            assert!(proof.verify(&root, &selected));
+
+            let proof2 = MerkleMultiProof::from_bytes(&proof.to_bytes()).unwrap();
+            assert_eq!(proof2, proof);
+
+            let mut wrong = selected.clone();
+            wrong[0] = &leaves[(indices[0] + 1) % 11];
+            assert!(!proof.verify(&root, &wrong));
+        }
+
+        // Neighbouring leaves share their path, so the proof is smaller than
+        // two single proofs
+        let multi = tree.multi_proof(&[4, 5]).unwrap();
+        assert_eq!(
+            multi.hashes.len(),
+            tree.proof(4).unwrap().siblings.len() - 1
+        );
+
+        assert!(tree.multi_proof(&[]).is_none());
+        assert!(tree.multi_proof(&[11]).is_none());
+    }
+}
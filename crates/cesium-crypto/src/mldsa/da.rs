@@ -1,9 +1,14 @@
+use std::str::FromStr;
+
 use rand::RngCore;
 use sha3::Digest;
 
-use crate::errors::CryptoError;
+use crate::{address, errors::CryptoError};
+
+#[cfg(feature = "serde")]
+use crate::serializer;
 
-use super::{PublicKeyBytes, PUB_BYTE_LEN};
+use super::Level;
 
 // For Dilithium the public key and display address are not interchangeable
 // the display address is a hash of the public key, it is thus not reversible
@@ -45,7 +50,7 @@ impl DisplayAddress {
     }
 
     pub fn try_from_pk(id: &[u8]) -> Result<Self, CryptoError> {
-        if id.len() != PUB_BYTE_LEN {
+        if Level::from_pub_len(id.len()).is_none() {
             return Err(CryptoError::InvalidDisplayAddress);
         }
 
@@ -57,7 +62,8 @@ impl DisplayAddress {
         Ok(Self { da })
     }
 
-    pub fn from_pk(id: &PublicKeyBytes) -> Self {
+    /// Works for the public key of every level
+    pub fn from_pk(id: &[u8]) -> Self {
         let mut hasher = sha3::Sha3_256::new();
         hasher.update(id);
         let mut da = [0u8; DA_BYTE_LEN];
@@ -81,6 +87,28 @@ impl DisplayAddress {
     }
 
     pub fn as_str(&self) -> String {
-        bs58::encode(&self.da).into_string()
+        address::encode(&self.da)
+    }
+}
+
+impl FromStr for DisplayAddress {
+    type Err = CryptoError;
+
+    fn from_str(da: &str) -> Result<Self, CryptoError> {
+        Self::from_bytes(&address::decode(da, DA_BYTE_LEN)?)
+    }
+}
+
+#[cfg(feature = "serde")]
+impl serde::Serialize for DisplayAddress {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer::serialize_encoded(serializer, || self.as_str(), &self.da)
+    }
+}
+
+#[cfg(feature = "serde")]
+impl<'de> serde::Deserialize<'de> for DisplayAddress {
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        serializer::deserialize_encoded(deserializer, Self::from_str, Self::from_bytes)
     }
 }
@@ -1,62 +1,202 @@
-use pqcrypto_mldsa::{mldsa44, mldsa44_keypair};
-use pqcrypto_traits::sign::{PublicKey, SecretKey, SignedMessage};
+use std::{fmt, path::Path};
 
-use crate::errors::CryptoError;
+use fips204::{
+    ml_dsa_44, ml_dsa_65, ml_dsa_87,
+    traits::{KeyGen, SerDes},
+};
+use pqcrypto_mldsa::{mldsa44, mldsa65, mldsa87};
+use pqcrypto_traits::sign::{
+    DetachedSignature as _, PublicKey as _, SecretKey as _, SignedMessage as _, VerificationError,
+};
 
-use super::{da::DisplayAddress, PUB_BYTE_LEN, SIG_BYTE_LEN};
+use crate::{
+    derivation::KeyBytes,
+    errors::CryptoError,
+    keystore::Keystore,
+    scheme::{AnyPublicKey, AnySignature, Signer},
+    secret::{zeroize_pod, SecretBytes},
+};
+
+#[cfg(feature = "shamir")]
+use crate::shamir::{self, Share};
+
+#[cfg(feature = "serde")]
+use crate::serializer;
+
+use super::{da::DisplayAddress, Level};
+
+/// An ML-DSA public key of any level
+#[derive(Clone)]
+pub enum PublicKey {
+    MlDsa44(Box<mldsa44::PublicKey>),
+    MlDsa65(Box<mldsa65::PublicKey>),
+    MlDsa87(Box<mldsa87::PublicKey>),
+}
+
+impl PublicKey {
+    /// The level follows from the length of the key
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self, CryptoError> {
+        match Level::from_pub_len(bytes.len()) {
+            Some(Level::MlDsa44) => Ok(Self::MlDsa44(Box::new(mldsa44::PublicKey::from_bytes(
+                bytes,
+            )?))),
+            Some(Level::MlDsa65) => Ok(Self::MlDsa65(Box::new(mldsa65::PublicKey::from_bytes(
+                bytes,
+            )?))),
+            Some(Level::MlDsa87) => Ok(Self::MlDsa87(Box::new(mldsa87::PublicKey::from_bytes(
+                bytes,
+            )?))),
+            None => Err(CryptoError::InvalidKeyLength),
+        }
+    }
+
+    pub fn level(&self) -> Level {
+        match self {
+            Self::MlDsa44(_) => Level::MlDsa44,
+            Self::MlDsa65(_) => Level::MlDsa65,
+            Self::MlDsa87(_) => Level::MlDsa87,
+        }
+    }
+
+    pub fn as_bytes(&self) -> &[u8] {
+        match self {
+            Self::MlDsa44(pk) => pk.as_bytes(),
+            Self::MlDsa65(pk) => pk.as_bytes(),
+            Self::MlDsa87(pk) => pk.as_bytes(),
+        }
+    }
+}
+
+/// An ML-DSA secret key of any level, wiped when dropped
+pub enum SecretKey {
+    MlDsa44(Box<mldsa44::SecretKey>),
+    MlDsa65(Box<mldsa65::SecretKey>),
+    MlDsa87(Box<mldsa87::SecretKey>),
+}
+
+impl SecretKey {
+    pub fn from_bytes(level: Level, bytes: &[u8]) -> Result<Self, CryptoError> {
+        if bytes.len() != level.sec_byte_len() {
+            return Err(CryptoError::InvalidKeyLength);
+        }
+
+        Ok(match level {
+            Level::MlDsa44 => Self::MlDsa44(Box::new(mldsa44::SecretKey::from_bytes(bytes)?)),
+            Level::MlDsa65 => Self::MlDsa65(Box::new(mldsa65::SecretKey::from_bytes(bytes)?)),
+            Level::MlDsa87 => Self::MlDsa87(Box::new(mldsa87::SecretKey::from_bytes(bytes)?)),
+        })
+    }
+
+    pub fn as_bytes(&self) -> &[u8] {
+        match self {
+            Self::MlDsa44(sk) => sk.as_bytes(),
+            Self::MlDsa65(sk) => sk.as_bytes(),
+            Self::MlDsa87(sk) => sk.as_bytes(),
+        }
+    }
+
+    pub fn sign(&self, msg: &[u8]) -> Vec<u8> {
+        match self {
+            Self::MlDsa44(sk) => mldsa44::sign(msg, sk).as_bytes().to_vec(),
+            Self::MlDsa65(sk) => mldsa65::sign(msg, sk).as_bytes().to_vec(),
+            Self::MlDsa87(sk) => mldsa87::sign(msg, sk).as_bytes().to_vec(),
+        }
+    }
+
+    pub fn sign_detached(&self, msg: &[u8]) -> Vec<u8> {
+        match self {
+            Self::MlDsa44(sk) => mldsa44::detached_sign(msg, sk).as_bytes().to_vec(),
+            Self::MlDsa65(sk) => mldsa65::detached_sign(msg, sk).as_bytes().to_vec(),
+            Self::MlDsa87(sk) => mldsa87::detached_sign(msg, sk).as_bytes().to_vec(),
+        }
+    }
+}
+
+impl Drop for SecretKey {
+    fn drop(&mut self) {
+        match self {
+            Self::MlDsa44(sk) => zeroize_pod(&mut **sk),
+            Self::MlDsa65(sk) => zeroize_pod(&mut **sk),
+            Self::MlDsa87(sk) => zeroize_pod(&mut **sk),
+        }
+    }
+}
+
+impl fmt::Debug for SecretKey {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "SecretKey([REDACTED])")
+    }
+}
 
 pub trait ViewOperations {
-    fn pub_key(&self) -> &mldsa44::PublicKey;
-    fn pub_key_bytes(&self) -> &[u8; PUB_BYTE_LEN] {
-        let id = unsafe { &*(self.pub_key().as_bytes().as_ptr() as *const [u8; PUB_BYTE_LEN]) };
-        id
+    fn pub_key(&self) -> &PublicKey;
+    fn level(&self) -> Level {
+        self.pub_key().level()
+    }
+    fn pub_key_bytes(&self) -> &[u8] {
+        self.pub_key().as_bytes()
     }
     fn verify(&self, msg: &[u8], sig: &[u8]) -> Result<bool, CryptoError> {
-        if sig.len() != SIG_BYTE_LEN + msg.len() {
+        if sig.len() != self.level().sig_byte_len() + msg.len() {
             return Err(CryptoError::InvalidSignature);
         }
 
-        let sm = mldsa44::SignedMessage::from_bytes(sig)?;
-        let v_result = mldsa44::open(&sm, self.pub_key());
-        if let Err(e) = v_result {
-            if e.to_string().contains("verification failed") {
-                return Ok(false);
-            }
-
-            return Err(CryptoError::UnknownVerificationError);
+        let v_result = match self.pub_key() {
+            PublicKey::MlDsa44(pk) => mldsa44::open(&mldsa44::SignedMessage::from_bytes(sig)?, pk),
+            PublicKey::MlDsa65(pk) => mldsa65::open(&mldsa65::SignedMessage::from_bytes(sig)?, pk),
+            PublicKey::MlDsa87(pk) => mldsa87::open(&mldsa87::SignedMessage::from_bytes(sig)?, pk),
+        };
+        match v_result {
+            Ok(opened) => Ok(opened == msg),
+            Err(VerificationError::InvalidSignature) => Ok(false),
+            Err(_) => Err(CryptoError::UnknownVerificationError),
+        }
+    }
+    fn verify_detached(&self, msg: &[u8], sig: &[u8]) -> Result<bool, CryptoError> {
+        if sig.len() != self.level().sig_byte_len() {
+            return Err(CryptoError::InvalidSignature);
         }
 
-        Ok(v_result.unwrap() == msg)
+        let v_result = match self.pub_key() {
+            PublicKey::MlDsa44(pk) => mldsa44::verify_detached_signature(
+                &mldsa44::DetachedSignature::from_bytes(sig)?,
+                msg,
+                pk,
+            ),
+            PublicKey::MlDsa65(pk) => mldsa65::verify_detached_signature(
+                &mldsa65::DetachedSignature::from_bytes(sig)?,
+                msg,
+                pk,
+            ),
+            PublicKey::MlDsa87(pk) => mldsa87::verify_detached_signature(
+                &mldsa87::DetachedSignature::from_bytes(sig)?,
+                msg,
+                pk,
+            ),
+        };
+        match v_result {
+            Ok(()) => Ok(true),
+            Err(VerificationError::InvalidSignature) => Ok(false),
+            Err(_) => Err(CryptoError::UnknownVerificationError),
+        }
     }
     fn get_da(&self) -> DisplayAddress {
-        // Get the public key
-        let pub_key = self.pub_key().as_bytes();
-
-        // Convert this slice to PublicKeyBytes
-        // A test in super::mod.rs will fail if the length of PublicKey does not equal PUB_BYTE_LEN
-        // but essentially, we are sure that the length of pub_key is PUB_BYTE_LEN
-        // so we can safely use unsafe to cast the slice to PublicKeyBytes
-        let id = unsafe { &*(pub_key.as_ptr() as *const [u8; PUB_BYTE_LEN]) };
-
-        // Compute the display address
-        let da = DisplayAddress::from_pk(&id);
-        da
+        DisplayAddress::from_pk(self.pub_key_bytes())
     }
 }
 
 pub struct VerifierPair {
-    pub_key: mldsa44::PublicKey,
+    pub_key: PublicKey,
 }
 
 impl VerifierPair {
     #[must_use]
     pub fn new(pub_key: &[u8]) -> Result<Self, CryptoError> {
-        let pub_key = mldsa44::PublicKey::from_bytes(pub_key)?;
-        Ok(Self { pub_key })
+        Self::from_bytes(pub_key)
     }
 
     pub fn from_bytes(pub_key: &[u8]) -> Result<Self, CryptoError> {
-        let pub_key = mldsa44::PublicKey::from_bytes(pub_key)?;
+        let pub_key = PublicKey::from_bytes(pub_key)?;
         Ok(Self { pub_key })
     }
 
@@ -65,54 +205,197 @@ impl VerifierPair {
     }
 }
 
+// Public keys are hex in JSON
+#[cfg(feature = "serde")]
+impl serde::Serialize for VerifierPair {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer::serialize_hex(serializer, &self.to_bytes())
+    }
+}
+
+#[cfg(feature = "serde")]
+impl<'de> serde::Deserialize<'de> for VerifierPair {
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        serializer::deserialize_hex(deserializer, Self::from_bytes)
+    }
+}
+
 impl ViewOperations for VerifierPair {
-    fn pub_key(&self) -> &mldsa44::PublicKey {
+    fn pub_key(&self) -> &PublicKey {
         &self.pub_key
     }
 }
 
 pub struct SignerPair {
-    pub_key: mldsa44::PublicKey,
-    sec_key: mldsa44::SecretKey,
+    pub_key: PublicKey,
+    sec_key: SecretKey,
 }
 
 impl SignerPair {
+    /// ML-DSA-44 keypair, see `create_with_level` for the other levels
     pub fn create() -> Self {
-        let (pk, sk) = mldsa44_keypair();
-        Self {
-            pub_key: pk,
-            sec_key: sk,
-        }
+        Self::create_with_level(Level::MlDsa44)
+    }
+
+    pub fn create_with_level(level: Level) -> Self {
+        let (pub_key, sec_key) = match level {
+            Level::MlDsa44 => {
+                let (pk, sk) = mldsa44::keypair();
+                (
+                    PublicKey::MlDsa44(Box::new(pk)),
+                    SecretKey::MlDsa44(Box::new(sk)),
+                )
+            }
+            Level::MlDsa65 => {
+                let (pk, sk) = mldsa65::keypair();
+                (
+                    PublicKey::MlDsa65(Box::new(pk)),
+                    SecretKey::MlDsa65(Box::new(sk)),
+                )
+            }
+            Level::MlDsa87 => {
+                let (pk, sk) = mldsa87::keypair();
+                (
+                    PublicKey::MlDsa87(Box::new(pk)),
+                    SecretKey::MlDsa87(Box::new(sk)),
+                )
+            }
+        };
+
+        Self { pub_key, sec_key }
+    }
+
+    /// Deterministic ML-DSA-44 keypair, the same seed always gives the same keys
+    pub fn from_seed(seed: &KeyBytes) -> Self {
+        Self::from_seed_with_level(Level::MlDsa44, seed)
+    }
+
+    pub fn from_seed_with_level(level: Level, seed: &KeyBytes) -> Self {
+        // fips204 and pqcrypto share the FIPS 204 key encoding
+        let (pk, sk) = match level {
+            Level::MlDsa44 => {
+                let (pk, sk) = ml_dsa_44::KG::keygen_from_seed(seed);
+                (
+                    pk.into_bytes().to_vec(),
+                    SecretBytes::from_slice(&sk.into_bytes()),
+                )
+            }
+            Level::MlDsa65 => {
+                let (pk, sk) = ml_dsa_65::KG::keygen_from_seed(seed);
+                (
+                    pk.into_bytes().to_vec(),
+                    SecretBytes::from_slice(&sk.into_bytes()),
+                )
+            }
+            Level::MlDsa87 => {
+                let (pk, sk) = ml_dsa_87::KG::keygen_from_seed(seed);
+                (
+                    pk.into_bytes().to_vec(),
+                    SecretBytes::from_slice(&sk.into_bytes()),
+                )
+            }
+        };
+        Self::from_bytes(&pk, sk.expose()).unwrap()
     }
 
     pub fn sign(&self, msg: &[u8]) -> Vec<u8> {
-        let sm = mldsa44::sign(msg, &self.sec_key);
-        sm.as_bytes().to_vec()
+        self.sec_key.sign(msg)
     }
 
+    pub fn sign_detached(&self, msg: &[u8]) -> Vec<u8> {
+        self.sec_key.sign_detached(msg)
+    }
+
+    /// The level follows from the length of the public key
     pub fn from_bytes(pub_key: &[u8], sec_key: &[u8]) -> Result<Self, CryptoError> {
-        let pub_key = mldsa44::PublicKey::from_bytes(pub_key)?;
-        let sec_key = mldsa44::SecretKey::from_bytes(sec_key)?;
+        let pub_key = PublicKey::from_bytes(pub_key)?;
+        let sec_key = SecretKey::from_bytes(pub_key.level(), sec_key)?;
         Ok(Self { pub_key, sec_key })
     }
 
-    pub fn to_bytes(&self) -> (Vec<u8>, Vec<u8>) {
+    pub fn to_bytes(&self) -> (Vec<u8>, SecretBytes) {
         (
             self.pub_key.as_bytes().to_vec(),
-            self.sec_key.as_bytes().to_vec(),
+            SecretBytes::from_slice(self.sec_key.as_bytes()),
+        )
+    }
+
+    pub fn to_keystore(&self, password: &[u8]) -> Result<Keystore, CryptoError> {
+        let (pub_key, sec_key) = self.to_bytes();
+        Keystore::encrypt(self.level().scheme(), &pub_key, sec_key.expose(), password)
+    }
+
+    pub fn from_keystore(keystore: &Keystore, password: &[u8]) -> Result<Self, CryptoError> {
+        match Level::from_scheme(keystore.scheme()) {
+            Some(level) if level.pub_byte_len() == keystore.pub_key().len() => {}
+            _ => return Err(CryptoError::SchemeMismatch),
+        }
+
+        Self::from_bytes(keystore.pub_key(), keystore.decrypt(password)?.expose())
+    }
+
+    pub fn save_keystore(&self, path: &Path, password: &[u8]) -> Result<(), CryptoError> {
+        self.to_keystore(password)?.save(path)
+    }
+
+    pub fn load_keystore(path: &Path, password: &[u8]) -> Result<Self, CryptoError> {
+        Self::from_keystore(&Keystore::load(path)?, password)
+    }
+
+    #[cfg(feature = "shamir")]
+    pub fn to_shares(&self, threshold: u8, count: u8) -> Result<Vec<Share>, CryptoError> {
+        let (pub_key, sec_key) = self.to_bytes();
+        shamir::split(
+            self.level().scheme(),
+            &pub_key,
+            sec_key.expose(),
+            threshold,
+            count,
         )
     }
+
+    #[cfg(feature = "shamir")]
+    pub fn from_shares(shares: &[Share]) -> Result<Self, CryptoError> {
+        let share = shares.first().ok_or(CryptoError::InsufficientShares)?;
+        match Level::from_scheme(share.scheme()) {
+            Some(level) if level.pub_byte_len() == share.pub_key().len() => {}
+            _ => return Err(CryptoError::SchemeMismatch),
+        }
+
+        Self::from_bytes(share.pub_key(), shamir::combine(shares)?.expose())
+    }
 }
 
 impl ViewOperations for SignerPair {
-    fn pub_key(&self) -> &mldsa44::PublicKey {
+    fn pub_key(&self) -> &PublicKey {
         &self.pub_key
     }
 }
 
+// Only the address is printed, never the secret key
+impl fmt::Debug for SignerPair {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("SignerPair")
+            .field("address", &self.get_da().as_str())
+            .field("sec_key", &self.sec_key)
+            .finish()
+    }
+}
+
+impl Signer for SignerPair {
+    fn public_key(&self) -> AnyPublicKey {
+        AnyPublicKey::new(self.level().scheme(), self.pub_key_bytes()).unwrap()
+    }
+
+    fn sign_any(&self, msg: &[u8]) -> AnySignature {
+        AnySignature::new(self.level().scheme(), &self.sign_detached(msg)).unwrap()
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::{mldsa::SIG_BYTE_LEN, scheme::Scheme};
 
     #[test]
     fn test_signer_sign() {
@@ -123,6 +406,62 @@ mod tests {
         assert!(signer.verify(msg, &sig).unwrap());
     }
 
+    #[test]
+    fn test_signer_sign_detached() {
+        let signer = SignerPair::create();
+        let msg = b"Hello, World!";
+        let sig = signer.sign_detached(msg);
+
+        assert_eq!(sig.len(), SIG_BYTE_LEN);
+        assert!(signer.verify_detached(msg, &sig).unwrap());
+        assert!(!signer.verify_detached(b"Hello, Wurld!", &sig).unwrap());
+    }
+
+    #[test]
+    fn test_signer_from_seed() {
+        let signer = SignerPair::from_seed(&[7u8; 32]);
+        let signer2 = SignerPair::from_seed(&[7u8; 32]);
+        let signer3 = SignerPair::from_seed(&[8u8; 32]);
+        assert_eq!(signer.to_bytes(), signer2.to_bytes());
+        assert_ne!(signer.pub_key().as_bytes(), signer3.pub_key().as_bytes());
+
+        let msg = b"Hello, World!";
+        let sig = signer.sign_detached(msg);
+        assert!(signer2.verify_detached(msg, &sig).unwrap());
+        assert!(!signer3.verify_detached(msg, &sig).unwrap());
+    }
+
+    #[test]
+    fn test_signer_keystore() {
+        use crate::keystore::KdfParams;
+
+        let signer = SignerPair::create();
+        let (pk, sk) = signer.to_bytes();
+        let kdf = KdfParams {
+            m_cost: 256,
+            t_cost: 1,
+            p_cost: 1,
+        };
+        let keystore =
+            Keystore::encrypt_with_params(Scheme::MlDsa, &pk, sk.expose(), b"hunter2", kdf)
+                .unwrap();
+
+        let signer2 = SignerPair::from_keystore(&keystore, b"hunter2").unwrap();
+        assert_eq!(signer.to_bytes(), signer2.to_bytes());
+        assert!(SignerPair::from_keystore(&keystore, b"hunter3").is_err());
+    }
+
+    #[test]
+    fn test_signer_redacted() {
+        let signer = SignerPair::create();
+        let (_, sec_key) = signer.to_bytes();
+
+        let debug = format!("{:?}", signer);
+        assert!(debug.contains(&signer.get_da().as_str()));
+        assert!(debug.contains("REDACTED"));
+        assert_eq!(format!("{}", sec_key), "[REDACTED]");
+    }
+
     #[test]
     fn test_verifier_verify() {
         let signer = SignerPair::create();
@@ -143,6 +482,10 @@ mod tests {
         let da2 = vierifer.get_da();
 
         assert_eq!(da.as_str(), da2.as_str());
+
+        // The string form parses back to the same address
+        let da3: DisplayAddress = da.as_str().parse().unwrap();
+        assert_eq!(da3.as_bytes(), da.as_bytes());
     }
 
     #[test]
@@ -150,7 +493,7 @@ mod tests {
         let signer = SignerPair::create();
         let (pub_key, sec_key) = signer.to_bytes();
 
-        let signer2 = SignerPair::from_bytes(&pub_key, &sec_key).unwrap();
+        let signer2 = SignerPair::from_bytes(&pub_key, sec_key.expose()).unwrap();
         assert_eq!(signer.pub_key().as_bytes(), signer2.pub_key().as_bytes());
     }
 
@@ -166,4 +509,70 @@ mod tests {
             verifier2.pub_key().as_bytes()
         );
     }
+
+    #[test]
+    fn test_signer_levels() {
+        let msg = b"Hello, World!";
+        for level in Level::ALL {
+            let signer = SignerPair::create_with_level(level);
+            assert_eq!(signer.level(), level);
+            assert_eq!(signer.pub_key_bytes().len(), level.pub_byte_len());
+
+            let sig = signer.sign_detached(msg);
+            assert_eq!(sig.len(), level.sig_byte_len());
+            assert!(signer.verify_detached(msg, &sig).unwrap());
+            assert!(signer.verify(msg, &signer.sign(msg)).unwrap());
+
+            // The level is carried by the scheme tag
+            let pk = signer.public_key();
+            assert_eq!(pk.scheme(), level.scheme());
+            assert!(pk.verify(msg, &signer.sign_any(msg)).unwrap());
+
+            let (pub_key, sec_key) = signer.to_bytes();
+            let signer2 = SignerPair::from_bytes(&pub_key, sec_key.expose()).unwrap();
+            assert_eq!(signer2.level(), level);
+            assert!(VerifierPair::new(&pub_key)
+                .unwrap()
+                .verify_detached(msg, &signer2.sign_detached(msg))
+                .unwrap());
+
+            let da: DisplayAddress = signer.get_da().as_str().parse().unwrap();
+            assert_eq!(da.as_bytes(), signer.get_da().as_bytes());
+        }
+    }
+
+    #[test]
+    fn test_signer_mixed_levels() {
+        let msg = b"Hello, World!";
+        let signer = SignerPair::create_with_level(Level::MlDsa44);
+        let verifier =
+            VerifierPair::new(SignerPair::create_with_level(Level::MlDsa65).pub_key_bytes())
+                .unwrap();
+        assert!(verifier
+            .verify_detached(msg, &signer.sign_detached(msg))
+            .is_err());
+
+        // A secret key of another level does not fit the public key
+        let (pub_key, _) = SignerPair::create_with_level(Level::MlDsa87).to_bytes();
+        let (_, sec_key) = signer.to_bytes();
+        assert!(SignerPair::from_bytes(&pub_key, sec_key.expose()).is_err());
+
+        let seed = [7u8; 32];
+        let signer = SignerPair::from_seed_with_level(Level::MlDsa87, &seed);
+        let signer2 = SignerPair::from_seed_with_level(Level::MlDsa87, &seed);
+        assert_eq!(signer.to_bytes(), signer2.to_bytes());
+        assert_eq!(signer.level(), Level::MlDsa87);
+    }
+
+    #[cfg(feature = "shamir")]
+    #[test]
+    fn test_signer_shares() {
+        let signer = SignerPair::create_with_level(Level::MlDsa65);
+        let shares = signer.to_shares(2, 3).unwrap();
+
+        let restored = SignerPair::from_shares(&shares[1..]).unwrap();
+        assert_eq!(restored.to_bytes(), signer.to_bytes());
+        assert_eq!(restored.level(), Level::MlDsa65);
+        assert!(SignerPair::from_shares(&shares[..1]).is_err());
+    }
 }
@@ -1,7 +1,30 @@
-// Dilihtium2
-pub const PUB_BYTE_LEN: usize = 1312; // 1952 (for d3, mldsa65)
-pub const SEC_BYTE_LEN: usize = 2560; // 4032 (for d3, mldsa65)
-pub const SIG_BYTE_LEN: usize = 2420; // 3309 (for d3, mldsa65)
+use keypair::{SecretKey, SignerPair, VerifierPair, ViewOperations};
+
+use crate::{
+    derivation::KeyBytes,
+    errors::CryptoError,
+    scheme::{Scheme, SignatureScheme},
+    secret::SecretBytes,
+};
+
+// The level is chosen per key, on the wire it is carried by the scheme tag
+// of the key and signature. The unsuffixed lengths are ML-DSA-44, the level
+// used before keys could pick one.
+
+// ML-DSA-44, NIST level 2
+pub const PUB_BYTE_LEN: usize = 1312;
+pub const SEC_BYTE_LEN: usize = 2560;
+pub const SIG_BYTE_LEN: usize = 2420;
+
+// ML-DSA-65, NIST level 3
+pub const PUB_BYTE_LEN_65: usize = 1952;
+pub const SEC_BYTE_LEN_65: usize = 4032;
+pub const SIG_BYTE_LEN_65: usize = 3309;
+
+// ML-DSA-87, NIST level 5
+pub const PUB_BYTE_LEN_87: usize = 2592;
+pub const SEC_BYTE_LEN_87: usize = 4896;
+pub const SIG_BYTE_LEN_87: usize = 4627;
 
 pub type PublicKeyBytes = [u8; PUB_BYTE_LEN];
 pub type SecretKeyBytes = [u8; SEC_BYTE_LEN];
@@ -10,9 +33,144 @@ pub mod da;
 pub mod keypair;
 pub mod proofs;
 
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Level {
+    MlDsa44,
+    MlDsa65,
+    MlDsa87,
+}
+
+impl Level {
+    pub const ALL: [Level; 3] = [Level::MlDsa44, Level::MlDsa65, Level::MlDsa87];
+
+    pub fn from_scheme(scheme: Scheme) -> Option<Level> {
+        match scheme {
+            Scheme::MlDsa => Some(Level::MlDsa44),
+            Scheme::MlDsa65 => Some(Level::MlDsa65),
+            Scheme::MlDsa87 => Some(Level::MlDsa87),
+            _ => None,
+        }
+    }
+
+    pub fn scheme(&self) -> Scheme {
+        match self {
+            Level::MlDsa44 => Scheme::MlDsa,
+            Level::MlDsa65 => Scheme::MlDsa65,
+            Level::MlDsa87 => Scheme::MlDsa87,
+        }
+    }
+
+    /// Public key lengths differ between levels, so a raw key identifies its level
+    pub fn from_pub_len(len: usize) -> Option<Level> {
+        Self::ALL
+            .into_iter()
+            .find(|level| level.pub_byte_len() == len)
+    }
+
+    pub fn pub_byte_len(&self) -> usize {
+        match self {
+            Level::MlDsa44 => PUB_BYTE_LEN,
+            Level::MlDsa65 => PUB_BYTE_LEN_65,
+            Level::MlDsa87 => PUB_BYTE_LEN_87,
+        }
+    }
+
+    pub fn sec_byte_len(&self) -> usize {
+        match self {
+            Level::MlDsa44 => SEC_BYTE_LEN,
+            Level::MlDsa65 => SEC_BYTE_LEN_65,
+            Level::MlDsa87 => SEC_BYTE_LEN_87,
+        }
+    }
+
+    pub fn sig_byte_len(&self) -> usize {
+        match self {
+            Level::MlDsa44 => SIG_BYTE_LEN,
+            Level::MlDsa65 => SIG_BYTE_LEN_65,
+            Level::MlDsa87 => SIG_BYTE_LEN_87,
+        }
+    }
+}
+
+// The marker types only differ in their level
+macro_rules! mldsa_scheme {
+    ($name:ident, $level:expr, $scheme:expr, $pub_len:expr, $sec_len:expr, $sig_len:expr) => {
+        impl SignatureScheme for $name {
+            const SCHEME: Scheme = $scheme;
+            const PUB_BYTE_LEN: usize = $pub_len;
+            const SEC_BYTE_LEN: usize = $sec_len;
+            const SIG_BYTE_LEN: usize = $sig_len;
+            const DA_BYTE_LEN: usize = da::DA_BYTE_LEN;
+
+            fn keypair() -> (Vec<u8>, SecretBytes) {
+                SignerPair::create_with_level($level).to_bytes()
+            }
+
+            fn keypair_from_seed(seed: &KeyBytes) -> (Vec<u8>, SecretBytes) {
+                SignerPair::from_seed_with_level($level, seed).to_bytes()
+            }
+
+            fn sign(sec_key: &[u8], msg: &[u8]) -> Result<Vec<u8>, CryptoError> {
+                Ok(SecretKey::from_bytes($level, sec_key)?.sign_detached(msg))
+            }
+
+            fn verify(pub_key: &[u8], msg: &[u8], sig: &[u8]) -> Result<bool, CryptoError> {
+                let verifier = VerifierPair::from_bytes(pub_key)?;
+                if verifier.level() != $level {
+                    return Err(CryptoError::InvalidKeyLength);
+                }
+
+                verifier.verify_detached(msg, sig)
+            }
+
+            fn display_address(pub_key: &[u8]) -> Result<String, CryptoError> {
+                if pub_key.len() != $pub_len {
+                    return Err(CryptoError::InvalidDisplayAddress);
+                }
+
+                Ok(da::DisplayAddress::try_from_pk(pub_key)?.as_str())
+            }
+        }
+    };
+}
+
+/// Marker type implementing `SignatureScheme` for ML-DSA-44
+pub struct MlDsa;
+
+/// Marker type implementing `SignatureScheme` for ML-DSA-65
+pub struct MlDsa65;
+
+/// Marker type implementing `SignatureScheme` for ML-DSA-87
+pub struct MlDsa87;
+
+mldsa_scheme!(
+    MlDsa,
+    Level::MlDsa44,
+    Scheme::MlDsa,
+    PUB_BYTE_LEN,
+    SEC_BYTE_LEN,
+    SIG_BYTE_LEN
+);
+mldsa_scheme!(
+    MlDsa65,
+    Level::MlDsa65,
+    Scheme::MlDsa65,
+    PUB_BYTE_LEN_65,
+    SEC_BYTE_LEN_65,
+    SIG_BYTE_LEN_65
+);
+mldsa_scheme!(
+    MlDsa87,
+    Level::MlDsa87,
+    Scheme::MlDsa87,
+    PUB_BYTE_LEN_87,
+    SEC_BYTE_LEN_87,
+    SIG_BYTE_LEN_87
+);
+
 #[cfg(test)]
 mod tests {
-    use pqcrypto_mldsa::mldsa44;
+    use pqcrypto_mldsa::{mldsa44, mldsa65, mldsa87};
 
     use super::*;
 
@@ -21,5 +179,24 @@ mod tests {
         assert_eq!(PUB_BYTE_LEN, mldsa44::public_key_bytes());
         assert_eq!(SEC_BYTE_LEN, mldsa44::secret_key_bytes());
         assert_eq!(SIG_BYTE_LEN, mldsa44::signature_bytes());
+
+        assert_eq!(PUB_BYTE_LEN_65, mldsa65::public_key_bytes());
+        assert_eq!(SEC_BYTE_LEN_65, mldsa65::secret_key_bytes());
+        assert_eq!(SIG_BYTE_LEN_65, mldsa65::signature_bytes());
+
+        assert_eq!(PUB_BYTE_LEN_87, mldsa87::public_key_bytes());
+        assert_eq!(SEC_BYTE_LEN_87, mldsa87::secret_key_bytes());
+        assert_eq!(SIG_BYTE_LEN_87, mldsa87::signature_bytes());
+    }
+
+    #[test]
+    fn test_levels() {
+        for level in Level::ALL {
+            assert_eq!(Level::from_scheme(level.scheme()), Some(level));
+            assert_eq!(Level::from_pub_len(level.pub_byte_len()), Some(level));
+            assert_eq!(level.scheme().pub_byte_len().unwrap(), level.pub_byte_len());
+            assert_eq!(level.scheme().sig_byte_len().unwrap(), level.sig_byte_len());
+        }
+        assert_eq!(Level::from_pub_len(PUB_BYTE_LEN + 1), None);
     }
 }
@@ -1,5 +1,23 @@
+use std::io::Read;
+
+use sha3::{
+    digest::{ExtendableOutput, Update},
+    Shake256,
+};
+
+use crate::{
+    errors::CryptoError,
+    secret::{ct_eq, SecretBytes},
+};
+
 const SALT_LENGTH: usize = 32;
 const CHALLENGE_LENGTH: usize = 32;
+// Separates the challenges of these proofs from other uses of the hash
+const PROOF_DOMAIN: &[u8] = b"cesium-proof";
+
+// Proof encoding: COMMITMENT (32) | RESPONSE, the response is a detached
+// signature over the commitment and the challenge
+// Opening encoding: SALT (32) | SECRET
 
 #[derive(Clone, Debug)]
 pub struct Commitment(pub Vec<u8>);
@@ -10,6 +28,160 @@ pub struct Challenge(pub Vec<u8>);
 #[derive(Clone, Debug)]
 pub struct Response(pub Vec<u8>);
 
+/// Reveals the secret and salt behind a commitment
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Opening {
+    pub secret: SecretBytes,
+    pub salt: Vec<u8>,
+}
+
+/// A non-interactive proof of ownership of an address, the challenge is
+/// derived from the commitment, the public key and the verifier's context
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct OwnershipProof {
+    pub commitment: Commitment,
+    pub response: Response,
+}
+
+// Compared in constant time, a commitment binds the prover's secret
+impl PartialEq for Commitment {
+    fn eq(&self, other: &Self) -> bool {
+        ct_eq(&self.0, &other.0)
+    }
+}
+
+impl Eq for Commitment {}
+
+impl PartialEq for Challenge {
+    fn eq(&self, other: &Self) -> bool {
+        ct_eq(&self.0, &other.0)
+    }
+}
+
+impl Eq for Challenge {}
+
+impl PartialEq for Response {
+    fn eq(&self, other: &Self) -> bool {
+        ct_eq(&self.0, &other.0)
+    }
+}
+
+impl Eq for Response {}
+
+impl Commitment {
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self, CryptoError> {
+        if bytes.len() != CHALLENGE_LENGTH {
+            return Err(CryptoError::ZkInvalidCommitment);
+        }
+        Ok(Self(bytes.to_vec()))
+    }
+
+    pub fn as_bytes(&self) -> &[u8] {
+        &self.0
+    }
+}
+
+impl Challenge {
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self, CryptoError> {
+        if bytes.len() != CHALLENGE_LENGTH {
+            return Err(CryptoError::ZkInvalidChallenge);
+        }
+        Ok(Self(bytes.to_vec()))
+    }
+
+    pub fn as_bytes(&self) -> &[u8] {
+        &self.0
+    }
+}
+
+impl Response {
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self, CryptoError> {
+        if bytes.is_empty() {
+            return Err(CryptoError::ZkInvalidResponse);
+        }
+        Ok(Self(bytes.to_vec()))
+    }
+
+    pub fn as_bytes(&self) -> &[u8] {
+        &self.0
+    }
+}
+
+impl Opening {
+    pub fn to_bytes(&self) -> SecretBytes {
+        let mut bytes = Vec::with_capacity(SALT_LENGTH + self.secret.len());
+        bytes.extend_from_slice(&self.salt);
+        bytes.extend_from_slice(self.secret.expose());
+        SecretBytes::new(bytes)
+    }
+
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self, CryptoError> {
+        if bytes.len() < SALT_LENGTH {
+            return Err(CryptoError::ZkInvalidSecret);
+        }
+        Ok(Self {
+            secret: SecretBytes::from_slice(&bytes[SALT_LENGTH..]),
+            salt: bytes[..SALT_LENGTH].to_vec(),
+        })
+    }
+}
+
+impl OwnershipProof {
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let mut bytes = Vec::with_capacity(CHALLENGE_LENGTH + self.response.0.len());
+        bytes.extend_from_slice(&self.commitment.0);
+        bytes.extend_from_slice(&self.response.0);
+        bytes
+    }
+
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self, CryptoError> {
+        if bytes.len() < CHALLENGE_LENGTH {
+            return Err(CryptoError::ZkInvalidCommitment);
+        }
+        Ok(Self {
+            commitment: Commitment::from_bytes(&bytes[..CHALLENGE_LENGTH])?,
+            response: Response::from_bytes(&bytes[CHALLENGE_LENGTH..])?,
+        })
+    }
+}
+
+fn shake(parts: &[&[u8]]) -> Vec<u8> {
+    let mut hasher = Shake256::default();
+    for part in parts {
+        hasher.update(part);
+    }
+
+    let mut output = vec![0u8; CHALLENGE_LENGTH];
+    let mut xof = hasher.finalize_xof();
+    let _ = xof.read(&mut output);
+    output
+}
+
+fn commit(secret: &[u8], salt: &[u8]) -> Commitment {
+    Commitment(shake(&[secret, salt]))
+}
+
+// Binding the challenge to the public key keeps a proof from being replayed
+// for another address, the context given by the verifier (a nonce or the
+// purpose of the proof) keeps it from being replayed to another verifier
+fn fiat_shamir_challenge(commitment: &Commitment, pub_key: &[u8], context: &[u8]) -> Challenge {
+    Challenge(shake(&[
+        PROOF_DOMAIN,
+        &commitment.0,
+        &(pub_key.len() as u32).to_le_bytes(),
+        pub_key,
+        &(context.len() as u32).to_le_bytes(),
+        context,
+    ]))
+}
+
+fn signed_message(commitment: &Commitment, challenge: &Challenge) -> Vec<u8> {
+    let mut message = Vec::with_capacity(commitment.0.len() + challenge.0.len());
+    message.extend_from_slice(&commitment.0);
+    message.extend_from_slice(&challenge.0);
+    message
+}
+
 pub mod prover;
 pub mod verifier;
 
@@ -18,10 +190,16 @@ mod tests {
     use prover::ProverProtocol;
     use verifier::VerifierProtocol;
 
-    use crate::mldsa::keypair::SignerPair;
+    use crate::mldsa::keypair::{SignerPair, VerifierPair, ViewOperations};
 
     use super::*;
 
+    const CONTEXT: &[u8] = b"login nonce";
+
+    fn verifier(account: &SignerPair) -> VerifierPair {
+        VerifierPair::new(account.pub_key_bytes()).unwrap()
+    }
+
     #[test]
     fn test_valid_proof() {
         let account = SignerPair::create();
@@ -29,7 +207,7 @@ mod tests {
         // Create a secret value
         let secret = b"my secret value";
         // Prover generates commitment
-        let (commitment, _salt) = ProverProtocol::generate_commitment(secret).unwrap();
+        let (commitment, _opening) = ProverProtocol::generate_commitment(secret).unwrap();
 
         // Verifier generates challenge
         let challenge = VerifierProtocol::generate_challenge(&commitment);
@@ -38,9 +216,10 @@ mod tests {
         let response =
             ProverProtocol::generate_response(&account, &commitment, &challenge).unwrap();
 
-        // Verifier verifies the proof
+        // Verifier verifies the proof with the public key only
         let is_valid =
-            VerifierProtocol::verify(&account, &commitment, &challenge, &response).unwrap();
+            VerifierProtocol::verify(&verifier(&account), &commitment, &challenge, &response)
+                .unwrap();
 
         assert!(is_valid);
     }
@@ -53,7 +232,7 @@ mod tests {
         // Create a secret value
         let secret = b"my secret value";
         // Prover generates commitment
-        let (commitment, _salt) = ProverProtocol::generate_commitment(secret).unwrap();
+        let (commitment, _opening) = ProverProtocol::generate_commitment(secret).unwrap();
 
         // Verifier generates challenge
         let challenge = VerifierProtocol::generate_challenge(&commitment);
@@ -63,8 +242,13 @@ mod tests {
             ProverProtocol::generate_response(&account, &commitment, &challenge).unwrap();
 
         // Verifier verifies the proof
-        let is_valid =
-            VerifierProtocol::verify(&wrong_account, &commitment, &challenge, &response).unwrap();
+        let is_valid = VerifierProtocol::verify(
+            &verifier(&wrong_account),
+            &commitment,
+            &challenge,
+            &response,
+        )
+        .unwrap();
 
         assert!(!is_valid);
     }
@@ -76,7 +260,7 @@ mod tests {
         // Create a secret value
         let secret = b"my secret value";
         // Prover generates commitment
-        let (commitment, _salt) = ProverProtocol::generate_commitment(secret).unwrap();
+        let (commitment, _opening) = ProverProtocol::generate_commitment(secret).unwrap();
 
         // Verifier generates challenge
         let challenge = VerifierProtocol::generate_challenge(&commitment);
@@ -87,8 +271,13 @@ mod tests {
 
         // Verifier verifies the proof with wrong commitment
         let wrong_commitment = Commitment(vec![0; CHALLENGE_LENGTH]);
-        let is_valid =
-            VerifierProtocol::verify(&account, &wrong_commitment, &challenge, &response).unwrap();
+        let is_valid = VerifierProtocol::verify(
+            &verifier(&account),
+            &wrong_commitment,
+            &challenge,
+            &response,
+        )
+        .unwrap();
 
         assert!(!is_valid);
     }
@@ -101,6 +290,37 @@ mod tests {
 
         // Commitments should be different due to random salt
         assert_ne!(commitment1.0, commitment2.0);
+        assert_ne!(commitment1, commitment2);
+        assert_eq!(commitment1, commitment1.clone());
+
+        // Challenges are fresh, a response can not be replayed
+        let challenge1 = VerifierProtocol::generate_challenge(&commitment1);
+        let challenge2 = VerifierProtocol::generate_challenge(&commitment1);
+        assert_ne!(challenge1, challenge2);
+    }
+
+    #[test]
+    fn test_opening() {
+        let secret = b"my secret value";
+        let (commitment, opening) = ProverProtocol::generate_commitment(secret).unwrap();
+        assert_eq!(opening.secret.expose(), secret);
+        assert!(VerifierProtocol::verify_opening(&commitment, &opening));
+
+        let opening = Opening::from_bytes(opening.to_bytes().expose()).unwrap();
+        assert!(VerifierProtocol::verify_opening(&commitment, &opening));
+
+        let wrong_secret = Opening {
+            secret: SecretBytes::from_slice(b"my secret valve"),
+            salt: opening.salt.clone(),
+        };
+        assert!(!VerifierProtocol::verify_opening(
+            &commitment,
+            &wrong_secret
+        ));
+
+        let (other, _) = ProverProtocol::generate_commitment(secret).unwrap();
+        assert!(!VerifierProtocol::verify_opening(&other, &opening));
+        assert!(Opening::from_bytes(&[0; SALT_LENGTH - 1]).is_err());
     }
 
     #[test]
@@ -108,12 +328,44 @@ mod tests {
         let account = SignerPair::create();
         let secret = b"my secret value";
 
-        let (commitment, response) =
-            ProverProtocol::generate_non_interactive(&account, secret).unwrap();
+        let (proof, opening) =
+            ProverProtocol::generate_non_interactive(&account, secret, CONTEXT).unwrap();
 
         let valid =
-            VerifierProtocol::verify_non_interactive(&account, &commitment, &response).unwrap();
+            VerifierProtocol::verify_non_interactive(&verifier(&account), &proof, CONTEXT).unwrap();
         assert!(valid);
+        assert!(VerifierProtocol::verify_opening(
+            &proof.commitment,
+            &opening
+        ));
+    }
+
+    #[test]
+    fn test_non_interactive_bytes() {
+        let account = SignerPair::create();
+        let (proof, _) =
+            ProverProtocol::generate_non_interactive(&account, b"secret", CONTEXT).unwrap();
+
+        let bytes = proof.to_bytes();
+        assert_eq!(
+            bytes.len(),
+            CHALLENGE_LENGTH + account.level().sig_byte_len()
+        );
+
+        let decoded = OwnershipProof::from_bytes(&bytes).unwrap();
+        assert_eq!(decoded, proof);
+        assert!(
+            VerifierProtocol::verify_non_interactive(&verifier(&account), &decoded, CONTEXT)
+                .unwrap()
+        );
+
+        assert!(OwnershipProof::from_bytes(&bytes[..CHALLENGE_LENGTH]).is_err());
+        assert!(VerifierProtocol::verify_non_interactive(
+            &verifier(&account),
+            &OwnershipProof::from_bytes(&bytes[..bytes.len() - 1]).unwrap(),
+            CONTEXT
+        )
+        .is_err());
     }
 
     #[test]
@@ -122,27 +374,47 @@ mod tests {
         let wrong_account = SignerPair::create();
         let secret = b"my secret value";
 
-        let (commitment, response) =
-            ProverProtocol::generate_non_interactive(&account, secret).unwrap();
+        let (proof, _) =
+            ProverProtocol::generate_non_interactive(&account, secret, CONTEXT).unwrap();
 
         let valid =
-            VerifierProtocol::verify_non_interactive(&wrong_account, &commitment, &response)
+            VerifierProtocol::verify_non_interactive(&verifier(&wrong_account), &proof, CONTEXT)
                 .unwrap();
 
         assert!(!valid);
     }
 
+    #[test]
+    fn test_non_interactive_wrong_context() {
+        let account = SignerPair::create();
+        let (proof, _) =
+            ProverProtocol::generate_non_interactive(&account, b"secret", CONTEXT).unwrap();
+
+        // A proof shown to one verifier does not pass for another
+        let valid =
+            VerifierProtocol::verify_non_interactive(&verifier(&account), &proof, b"other nonce")
+                .unwrap();
+        assert!(!valid);
+        let valid =
+            VerifierProtocol::verify_non_interactive(&verifier(&account), &proof, b"").unwrap();
+        assert!(!valid);
+    }
+
     #[test]
     fn test_non_interactive_wrong_commitment() {
         let account = SignerPair::create();
         let secret = b"my secret value";
 
-        let (_, response) = ProverProtocol::generate_non_interactive(&account, secret).unwrap();
+        let (proof, _) =
+            ProverProtocol::generate_non_interactive(&account, secret, CONTEXT).unwrap();
 
-        let wrong_commitment = Commitment(vec![0; CHALLENGE_LENGTH]);
+        let wrong_proof = OwnershipProof {
+            commitment: Commitment(vec![0; CHALLENGE_LENGTH]),
+            response: proof.response,
+        };
 
         let valid =
-            VerifierProtocol::verify_non_interactive(&account, &wrong_commitment, &response)
+            VerifierProtocol::verify_non_interactive(&verifier(&account), &wrong_proof, CONTEXT)
                 .unwrap();
 
         assert!(!valid);
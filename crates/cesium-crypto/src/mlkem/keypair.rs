@@ -0,0 +1,135 @@
+use pqcrypto_mlkem::{mlkem768, mlkem768_keypair};
+use pqcrypto_traits::kem::{Ciphertext, PublicKey, SecretKey, SharedSecret};
+
+use crate::{
+    errors::CryptoError,
+    secret::{zeroize_pod, SecretBytes},
+};
+
+use super::{SharedSecretBytes, CT_BYTE_LEN};
+
+fn shared_secret_bytes(ss: &mlkem768::SharedSecret) -> SharedSecretBytes {
+    ss.as_bytes().try_into().unwrap()
+}
+
+/// The public half, held by the peer that opens a channel
+pub struct EncapsulatorPair {
+    pub_key: mlkem768::PublicKey,
+}
+
+impl EncapsulatorPair {
+    pub fn new(pub_key: &[u8]) -> Result<Self, CryptoError> {
+        Self::from_bytes(pub_key)
+    }
+
+    pub fn from_bytes(pub_key: &[u8]) -> Result<Self, CryptoError> {
+        let pub_key = mlkem768::PublicKey::from_bytes(pub_key)?;
+        Ok(Self { pub_key })
+    }
+
+    pub fn to_bytes(&self) -> Vec<u8> {
+        self.pub_key.as_bytes().to_vec()
+    }
+
+    /// Returns the ciphertext for the key owner and the shared secret
+    pub fn encapsulate(&self) -> (Vec<u8>, SharedSecretBytes) {
+        let (mut ss, ct) = mlkem768::encapsulate(&self.pub_key);
+        let bytes = shared_secret_bytes(&ss);
+        zeroize_pod(&mut ss);
+        (ct.as_bytes().to_vec(), bytes)
+    }
+}
+
+/// The full keypair, held by the peer accepting channels
+pub struct DecapsulatorPair {
+    pub_key: mlkem768::PublicKey,
+    sec_key: mlkem768::SecretKey,
+}
+
+impl DecapsulatorPair {
+    pub fn create() -> Self {
+        let (pk, sk) = mlkem768_keypair();
+        Self {
+            pub_key: pk,
+            sec_key: sk,
+        }
+    }
+
+    pub fn pub_key_bytes(&self) -> &[u8] {
+        self.pub_key.as_bytes()
+    }
+
+    pub fn encapsulator(&self) -> EncapsulatorPair {
+        EncapsulatorPair {
+            pub_key: self.pub_key,
+        }
+    }
+
+    /// A tampered ciphertext does not fail, it yields an unrelated secret
+    /// (implicit rejection), so the channel breaks on the first decryption
+    pub fn decapsulate(&self, ct: &[u8]) -> Result<SharedSecretBytes, CryptoError> {
+        if ct.len() != CT_BYTE_LEN {
+            return Err(CryptoError::InvalidCiphertext);
+        }
+
+        let ct = mlkem768::Ciphertext::from_bytes(ct)?;
+        let mut ss = mlkem768::decapsulate(&ct, &self.sec_key);
+        let bytes = shared_secret_bytes(&ss);
+        zeroize_pod(&mut ss);
+        Ok(bytes)
+    }
+
+    pub fn from_bytes(pub_key: &[u8], sec_key: &[u8]) -> Result<Self, CryptoError> {
+        let pub_key = mlkem768::PublicKey::from_bytes(pub_key)?;
+        let sec_key = mlkem768::SecretKey::from_bytes(sec_key)?;
+        Ok(Self { pub_key, sec_key })
+    }
+
+    pub fn to_bytes(&self) -> (Vec<u8>, SecretBytes) {
+        (
+            self.pub_key.as_bytes().to_vec(),
+            SecretBytes::from_slice(self.sec_key.as_bytes()),
+        )
+    }
+}
+
+impl Drop for DecapsulatorPair {
+    fn drop(&mut self) {
+        zeroize_pod(&mut self.sec_key);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_encapsulate() {
+        let receiver = DecapsulatorPair::create();
+        let sender = EncapsulatorPair::new(receiver.pub_key_bytes()).unwrap();
+
+        let (ct, ss) = sender.encapsulate();
+        assert_eq!(ct.len(), CT_BYTE_LEN);
+        assert_eq!(receiver.decapsulate(&ct).unwrap(), ss);
+
+        // Every encapsulation gives a fresh secret
+        let (_, ss2) = receiver.encapsulator().encapsulate();
+        assert_ne!(ss, ss2);
+
+        let mut tampered = ct.clone();
+        tampered[0] ^= 1;
+        assert_ne!(receiver.decapsulate(&tampered).unwrap(), ss);
+        assert!(receiver.decapsulate(&ct[1..]).is_err());
+    }
+
+    #[test]
+    fn test_decapsulator_bytes() {
+        let receiver = DecapsulatorPair::create();
+        let (pub_key, sec_key) = receiver.to_bytes();
+        let receiver2 = DecapsulatorPair::from_bytes(&pub_key, sec_key.expose()).unwrap();
+
+        let (ct, ss) = receiver.encapsulator().encapsulate();
+        assert_eq!(receiver2.decapsulate(&ct).unwrap(), ss);
+        assert_eq!(receiver2.encapsulator().to_bytes(), pub_key);
+    }
+}
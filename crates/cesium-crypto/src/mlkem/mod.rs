@@ -0,0 +1,28 @@
+// ML-KEM-768, key encapsulation for confidential channels between
+// validators. Unlike the signature schemes it has no display address and
+// no wire tag, keys are only exchanged during the channel handshake.
+pub const PUB_BYTE_LEN: usize = 1184;
+pub const SEC_BYTE_LEN: usize = 2400;
+pub const CT_BYTE_LEN: usize = 1088;
+pub const SS_BYTE_LEN: usize = 32;
+
+pub type PublicKeyBytes = [u8; PUB_BYTE_LEN];
+pub type SharedSecretBytes = [u8; SS_BYTE_LEN];
+
+pub mod keypair;
+pub mod session;
+
+#[cfg(test)]
+mod tests {
+    use pqcrypto_mlkem::mlkem768;
+
+    use super::*;
+
+    #[test]
+    fn test_lengths() {
+        assert_eq!(PUB_BYTE_LEN, mlkem768::public_key_bytes());
+        assert_eq!(SEC_BYTE_LEN, mlkem768::secret_key_bytes());
+        assert_eq!(CT_BYTE_LEN, mlkem768::ciphertext_bytes());
+        assert_eq!(SS_BYTE_LEN, mlkem768::shared_secret_bytes());
+    }
+}
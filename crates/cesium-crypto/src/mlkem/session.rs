@@ -0,0 +1,85 @@
+use hkdf::Hkdf;
+use sha3::Sha3_256;
+use zeroize::Zeroize;
+
+use super::SharedSecretBytes;
+
+// Both directions of a channel get their own key, derived with HKDF-SHA3-256
+// from the shared secret and bound to the handshake ciphertext
+
+pub const SESSION_DOMAIN: &[u8] = b"cesium-kem-session";
+pub const SESSION_KEY_BYTE_LEN: usize = 32;
+pub type SessionKey = [u8; SESSION_KEY_BYTE_LEN];
+
+const INITIATOR_LABEL: &[u8] = b"initiator";
+const RESPONDER_LABEL: &[u8] = b"responder";
+
+/// The initiator encapsulates to the responder's public key
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Role {
+    Initiator,
+    Responder,
+}
+
+#[derive(Clone, PartialEq, Eq)]
+pub struct SessionKeys {
+    pub send: SessionKey,
+    pub recv: SessionKey,
+}
+
+impl SessionKeys {
+    pub fn derive(shared_secret: &SharedSecretBytes, ct: &[u8], role: Role) -> Self {
+        let initiator = derive_key(shared_secret, ct, INITIATOR_LABEL);
+        let responder = derive_key(shared_secret, ct, RESPONDER_LABEL);
+
+        match role {
+            Role::Initiator => Self {
+                send: initiator,
+                recv: responder,
+            },
+            Role::Responder => Self {
+                send: responder,
+                recv: initiator,
+            },
+        }
+    }
+}
+
+impl Drop for SessionKeys {
+    fn drop(&mut self) {
+        self.send.zeroize();
+        self.recv.zeroize();
+    }
+}
+
+fn derive_key(shared_secret: &SharedSecretBytes, ct: &[u8], label: &[u8]) -> SessionKey {
+    let hkdf = Hkdf::<Sha3_256>::new(Some(SESSION_DOMAIN), shared_secret);
+    let mut key = [0u8; SESSION_KEY_BYTE_LEN];
+    // A 32 byte output is always within the HKDF limit
+    hkdf.expand_multi_info(&[ct, label], &mut key).unwrap();
+    key
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::mlkem::keypair::DecapsulatorPair;
+
+    #[test]
+    fn test_session_keys() {
+        let responder = DecapsulatorPair::create();
+        let (ct, ss) = responder.encapsulator().encapsulate();
+
+        let initiator_keys = SessionKeys::derive(&ss, &ct, Role::Initiator);
+        let responder_keys =
+            SessionKeys::derive(&responder.decapsulate(&ct).unwrap(), &ct, Role::Responder);
+
+        assert_eq!(initiator_keys.send, responder_keys.recv);
+        assert_eq!(initiator_keys.recv, responder_keys.send);
+        assert_ne!(initiator_keys.send, initiator_keys.recv);
+
+        // The keys are bound to the handshake ciphertext
+        let other = SessionKeys::derive(&ss, &ct[1..], Role::Initiator);
+        assert_ne!(other.send, initiator_keys.send);
+    }
+}
@@ -0,0 +1,85 @@
+use rand::RngCore;
+use zeroize::Zeroize;
+
+use crate::errors::CryptoError;
+
+// 32 bytes of entropy give a 24 word phrase
+pub const ENTROPY_BYTE_LEN: usize = 32;
+pub const SEED_BYTE_LEN: usize = 64;
+
+pub type SeedBytes = [u8; SEED_BYTE_LEN];
+
+/// BIP-39 english backup phrase, used as the root of every derived account
+pub struct Mnemonic {
+    inner: bip39::Mnemonic,
+}
+
+impl Mnemonic {
+    pub fn generate() -> Self {
+        let mut rng = rand::thread_rng();
+        let mut entropy = [0u8; ENTROPY_BYTE_LEN];
+        rng.fill_bytes(&mut entropy);
+
+        // The entropy length is always valid, so this can not fail
+        let inner = bip39::Mnemonic::from_entropy(&entropy).unwrap();
+        entropy.zeroize();
+        Self { inner }
+    }
+
+    pub fn from_phrase(phrase: &str) -> Result<Self, CryptoError> {
+        let inner = bip39::Mnemonic::parse(phrase)?;
+        Ok(Self { inner })
+    }
+
+    pub fn phrase(&self) -> String {
+        self.inner.to_string()
+    }
+
+    pub fn word_count(&self) -> usize {
+        self.inner.word_count()
+    }
+
+    /// Stretch the phrase into a seed, an empty passphrase is allowed
+    pub fn to_seed(&self, passphrase: &str) -> SeedBytes {
+        self.inner.to_seed(passphrase)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_generate() {
+        let mnemonic = Mnemonic::generate();
+        assert_eq!(mnemonic.word_count(), 24);
+
+        let restored = Mnemonic::from_phrase(&mnemonic.phrase()).unwrap();
+        assert_eq!(mnemonic.to_seed(""), restored.to_seed(""));
+        assert_ne!(mnemonic.to_seed(""), mnemonic.to_seed("cesium"));
+    }
+
+    #[test]
+    fn test_known_seed() {
+        // BIP-39 test vector
+        let mnemonic = Mnemonic::from_phrase(
+            "abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon about",
+        )
+        .unwrap();
+        let seed = mnemonic.to_seed("TREZOR");
+        assert_eq!(hex(&seed[..8]), "c55257c360c07c72");
+    }
+
+    #[test]
+    fn test_invalid_phrase() {
+        assert!(Mnemonic::from_phrase("abandon abandon abandon").is_err());
+        assert!(Mnemonic::from_phrase(
+            "abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon",
+        )
+        .is_err());
+    }
+
+    fn hex(bytes: &[u8]) -> String {
+        bytes.iter().map(|b| format!("{:02x}", b)).collect()
+    }
+}
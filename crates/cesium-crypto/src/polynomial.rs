@@ -20,6 +20,14 @@ impl Polynomial {
         }
     }
 
+    /// A random polynomial of the given degree through (0, constant)
+    pub fn random(constant: FieldElement, degree: usize, rng: &mut impl rand::RngCore) -> Self {
+        let mut coefficients = Vec::with_capacity(degree + 1);
+        coefficients.push(constant);
+        coefficients.extend((0..degree).map(|_| FieldElement::random(rng)));
+        Self { coefficients }
+    }
+
     pub fn evaluate(&self, point: &FieldElement) -> FieldElement {
         let mut result = FieldElement::new(0);
         let mut power = FieldElement::new(1);
@@ -30,6 +38,42 @@ impl Polynomial {
         }
         result
     }
+
+    /// The Lagrange basis over distinct points, the i-th polynomial is one
+    /// at `points[i]` and zero at every other point
+    pub fn lagrange_basis(points: &[FieldElement]) -> Vec<Self> {
+        points
+            .iter()
+            .enumerate()
+            .map(|(i, xi)| {
+                let mut basis = Self::new(vec![FieldElement::new(1)]);
+                let mut denominator = FieldElement::new(1);
+                for (j, xj) in points.iter().enumerate() {
+                    if i != j {
+                        basis = basis.mul_linear(xj);
+                        denominator = denominator.mul(&xi.sub(xj));
+                    }
+                }
+                basis.scale(&denominator.inverse())
+            })
+            .collect()
+    }
+
+    // Multiplies by (x - root)
+    fn mul_linear(&self, root: &FieldElement) -> Self {
+        let mut coefficients = vec![FieldElement::new(0); self.coefficients.len() + 1];
+        for (i, coeff) in self.coefficients.iter().enumerate() {
+            coefficients[i + 1] = coefficients[i + 1].add(coeff);
+            coefficients[i] = coefficients[i].sub(&coeff.mul(root));
+        }
+        Self { coefficients }
+    }
+
+    fn scale(&self, factor: &FieldElement) -> Self {
+        Self {
+            coefficients: self.coefficients.iter().map(|c| c.mul(factor)).collect(),
+        }
+    }
 }
 
 #[cfg(test)]
@@ -64,4 +108,18 @@ mod tests {
 
         assert_eq!(result, FieldElement::new(0));
     }
+
+    #[test]
+    fn test_lagrange_basis() {
+        let points: Vec<FieldElement> = [1, 2, 5, 9].into_iter().map(FieldElement::new).collect();
+        let basis = Polynomial::lagrange_basis(&points);
+
+        for (i, poly) in basis.iter().enumerate() {
+            assert_eq!(poly.coefficients.len(), points.len());
+            for (j, point) in points.iter().enumerate() {
+                let expected = FieldElement::new((i == j) as u64);
+                assert_eq!(poly.evaluate(point), expected);
+            }
+        }
+    }
 }
@@ -0,0 +1,389 @@
+use crate::{derivation::KeyBytes, errors::CryptoError, secret::SecretBytes};
+
+#[cfg(feature = "falcon")]
+use crate::falcon::Falcon;
+#[cfg(feature = "hybrid")]
+use crate::hybrid::Hybrid;
+#[cfg(feature = "mldsa")]
+use crate::mldsa::{MlDsa, MlDsa65, MlDsa87};
+#[cfg(feature = "sphincsplus")]
+use crate::sphincsplus::SphincsPlus;
+
+// The numeric value of a scheme is written in front of keys and
+// signatures on the wire, so existing values must never be changed
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Scheme {
+    MlDsa,
+    Falcon,
+    SphincsPlus,
+    Hybrid,
+    MlDsa65,
+    MlDsa87,
+}
+
+impl Scheme {
+    pub fn from_u8(value: u8) -> Option<Scheme> {
+        match value {
+            0 => Some(Scheme::MlDsa),
+            1 => Some(Scheme::Falcon),
+            2 => Some(Scheme::SphincsPlus),
+            3 => Some(Scheme::Hybrid),
+            4 => Some(Scheme::MlDsa65),
+            5 => Some(Scheme::MlDsa87),
+            _ => None,
+        }
+    }
+
+    pub fn to_u8(&self) -> u8 {
+        match self {
+            Scheme::MlDsa => 0,
+            Scheme::Falcon => 1,
+            Scheme::SphincsPlus => 2,
+            Scheme::Hybrid => 3,
+            Scheme::MlDsa65 => 4,
+            Scheme::MlDsa87 => 5,
+        }
+    }
+
+    pub fn pub_byte_len(&self) -> Result<usize, CryptoError> {
+        match self {
+            #[cfg(feature = "mldsa")]
+            Scheme::MlDsa => Ok(MlDsa::PUB_BYTE_LEN),
+            #[cfg(feature = "mldsa")]
+            Scheme::MlDsa65 => Ok(MlDsa65::PUB_BYTE_LEN),
+            #[cfg(feature = "mldsa")]
+            Scheme::MlDsa87 => Ok(MlDsa87::PUB_BYTE_LEN),
+            #[cfg(feature = "falcon")]
+            Scheme::Falcon => Ok(Falcon::PUB_BYTE_LEN),
+            #[cfg(feature = "sphincsplus")]
+            Scheme::SphincsPlus => Ok(SphincsPlus::PUB_BYTE_LEN),
+            #[cfg(feature = "hybrid")]
+            Scheme::Hybrid => Ok(Hybrid::PUB_BYTE_LEN),
+            #[allow(unreachable_patterns)]
+            _ => Err(CryptoError::UnsupportedScheme(self.to_u8())),
+        }
+    }
+
+    pub fn sig_byte_len(&self) -> Result<usize, CryptoError> {
+        match self {
+            #[cfg(feature = "mldsa")]
+            Scheme::MlDsa => Ok(MlDsa::SIG_BYTE_LEN),
+            #[cfg(feature = "mldsa")]
+            Scheme::MlDsa65 => Ok(MlDsa65::SIG_BYTE_LEN),
+            #[cfg(feature = "mldsa")]
+            Scheme::MlDsa87 => Ok(MlDsa87::SIG_BYTE_LEN),
+            #[cfg(feature = "falcon")]
+            Scheme::Falcon => Ok(Falcon::SIG_BYTE_LEN),
+            #[cfg(feature = "sphincsplus")]
+            Scheme::SphincsPlus => Ok(SphincsPlus::SIG_BYTE_LEN),
+            #[cfg(feature = "hybrid")]
+            Scheme::Hybrid => Ok(Hybrid::SIG_BYTE_LEN),
+            #[allow(unreachable_patterns)]
+            _ => Err(CryptoError::UnsupportedScheme(self.to_u8())),
+        }
+    }
+
+    pub fn da_byte_len(&self) -> Result<usize, CryptoError> {
+        match self {
+            #[cfg(feature = "mldsa")]
+            Scheme::MlDsa => Ok(MlDsa::DA_BYTE_LEN),
+            #[cfg(feature = "mldsa")]
+            Scheme::MlDsa65 => Ok(MlDsa65::DA_BYTE_LEN),
+            #[cfg(feature = "mldsa")]
+            Scheme::MlDsa87 => Ok(MlDsa87::DA_BYTE_LEN),
+            #[cfg(feature = "falcon")]
+            Scheme::Falcon => Ok(Falcon::DA_BYTE_LEN),
+            #[cfg(feature = "sphincsplus")]
+            Scheme::SphincsPlus => Ok(SphincsPlus::DA_BYTE_LEN),
+            #[cfg(feature = "hybrid")]
+            Scheme::Hybrid => Ok(Hybrid::DA_BYTE_LEN),
+            #[allow(unreachable_patterns)]
+            _ => Err(CryptoError::UnsupportedScheme(self.to_u8())),
+        }
+    }
+
+    pub fn verify(&self, pub_key: &[u8], msg: &[u8], sig: &[u8]) -> Result<bool, CryptoError> {
+        match self {
+            #[cfg(feature = "mldsa")]
+            Scheme::MlDsa => MlDsa::verify(pub_key, msg, sig),
+            #[cfg(feature = "mldsa")]
+            Scheme::MlDsa65 => MlDsa65::verify(pub_key, msg, sig),
+            #[cfg(feature = "mldsa")]
+            Scheme::MlDsa87 => MlDsa87::verify(pub_key, msg, sig),
+            #[cfg(feature = "falcon")]
+            Scheme::Falcon => Falcon::verify(pub_key, msg, sig),
+            #[cfg(feature = "sphincsplus")]
+            Scheme::SphincsPlus => SphincsPlus::verify(pub_key, msg, sig),
+            #[cfg(feature = "hybrid")]
+            Scheme::Hybrid => Hybrid::verify(pub_key, msg, sig),
+            #[allow(unreachable_patterns)]
+            _ => Err(CryptoError::UnsupportedScheme(self.to_u8())),
+        }
+    }
+
+    pub fn display_address(&self, pub_key: &[u8]) -> Result<String, CryptoError> {
+        match self {
+            #[cfg(feature = "mldsa")]
+            Scheme::MlDsa => MlDsa::display_address(pub_key),
+            #[cfg(feature = "mldsa")]
+            Scheme::MlDsa65 => MlDsa65::display_address(pub_key),
+            #[cfg(feature = "mldsa")]
+            Scheme::MlDsa87 => MlDsa87::display_address(pub_key),
+            #[cfg(feature = "falcon")]
+            Scheme::Falcon => Falcon::display_address(pub_key),
+            #[cfg(feature = "sphincsplus")]
+            Scheme::SphincsPlus => SphincsPlus::display_address(pub_key),
+            #[cfg(feature = "hybrid")]
+            Scheme::Hybrid => Hybrid::display_address(pub_key),
+            #[allow(unreachable_patterns)]
+            _ => Err(CryptoError::UnsupportedScheme(self.to_u8())),
+        }
+    }
+}
+
+/// Common operations of a signature scheme, implemented by a marker type
+/// in each scheme module (`mldsa::MlDsa`, `falcon::Falcon`, ...).
+/// Signatures are detached and always exactly `SIG_BYTE_LEN` bytes long.
+pub trait SignatureScheme {
+    const SCHEME: Scheme;
+    const PUB_BYTE_LEN: usize;
+    const SEC_BYTE_LEN: usize;
+    const SIG_BYTE_LEN: usize;
+    const DA_BYTE_LEN: usize;
+
+    /// Generate a new random keypair, returned as (public key, secret key)
+    fn keypair() -> (Vec<u8>, SecretBytes);
+    /// Deterministic keypair, usually from a `derivation::derive_key` output
+    fn keypair_from_seed(seed: &KeyBytes) -> (Vec<u8>, SecretBytes);
+    fn sign(sec_key: &[u8], msg: &[u8]) -> Result<Vec<u8>, CryptoError>;
+    fn verify(pub_key: &[u8], msg: &[u8], sig: &[u8]) -> Result<bool, CryptoError>;
+    fn display_address(pub_key: &[u8]) -> Result<String, CryptoError>;
+}
+
+/// Implemented by the `SignerPair` of every scheme, so callers that only
+/// need to produce signatures do not depend on a specific scheme
+pub trait Signer {
+    fn public_key(&self) -> AnyPublicKey;
+    fn sign_any(&self, msg: &[u8]) -> AnySignature;
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct AnyPublicKey {
+    scheme: Scheme,
+    bytes: Vec<u8>,
+}
+
+impl AnyPublicKey {
+    pub fn new(scheme: Scheme, bytes: &[u8]) -> Result<Self, CryptoError> {
+        if bytes.len() != scheme.pub_byte_len()? {
+            return Err(CryptoError::InvalidKeyLength);
+        }
+
+        Ok(Self {
+            scheme,
+            bytes: bytes.to_vec(),
+        })
+    }
+
+    pub fn scheme(&self) -> Scheme {
+        self.scheme
+    }
+
+    pub fn as_bytes(&self) -> &[u8] {
+        &self.bytes
+    }
+
+    /// Length of the tagged encoding returned by `to_bytes`
+    pub fn byte_len(&self) -> usize {
+        1 + self.bytes.len()
+    }
+
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let mut bytes = Vec::with_capacity(self.byte_len());
+        bytes.push(self.scheme.to_u8());
+        bytes.extend_from_slice(&self.bytes);
+        bytes
+    }
+
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self, CryptoError> {
+        if bytes.is_empty() {
+            return Err(CryptoError::InvalidKeyLength);
+        }
+
+        let scheme = Scheme::from_u8(bytes[0]).ok_or(CryptoError::UnsupportedScheme(bytes[0]))?;
+        Self::new(scheme, &bytes[1..])
+    }
+
+    pub fn verify(&self, msg: &[u8], sig: &AnySignature) -> Result<bool, CryptoError> {
+        if sig.scheme != self.scheme {
+            return Err(CryptoError::SchemeMismatch);
+        }
+
+        self.scheme.verify(&self.bytes, msg, &sig.bytes)
+    }
+
+    pub fn display_address(&self) -> Result<String, CryptoError> {
+        self.scheme.display_address(&self.bytes)
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct AnySignature {
+    scheme: Scheme,
+    bytes: Vec<u8>,
+}
+
+impl AnySignature {
+    pub fn new(scheme: Scheme, bytes: &[u8]) -> Result<Self, CryptoError> {
+        if bytes.len() != scheme.sig_byte_len()? {
+            return Err(CryptoError::InvalidSignature);
+        }
+
+        Ok(Self {
+            scheme,
+            bytes: bytes.to_vec(),
+        })
+    }
+
+    pub fn scheme(&self) -> Scheme {
+        self.scheme
+    }
+
+    pub fn as_bytes(&self) -> &[u8] {
+        &self.bytes
+    }
+
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let mut bytes = Vec::with_capacity(1 + self.bytes.len());
+        bytes.push(self.scheme.to_u8());
+        bytes.extend_from_slice(&self.bytes);
+        bytes
+    }
+
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self, CryptoError> {
+        if bytes.is_empty() {
+            return Err(CryptoError::InvalidSignature);
+        }
+
+        let scheme = Scheme::from_u8(bytes[0]).ok_or(CryptoError::UnsupportedScheme(bytes[0]))?;
+        Self::new(scheme, &bytes[1..])
+    }
+}
+
+// Keys and signatures keep their scheme tag, in JSON they are hex
+#[cfg(feature = "serde")]
+impl serde::Serialize for AnyPublicKey {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        crate::serializer::serialize_hex(serializer, &self.to_bytes())
+    }
+}
+
+#[cfg(feature = "serde")]
+impl<'de> serde::Deserialize<'de> for AnyPublicKey {
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        crate::serializer::deserialize_hex(deserializer, Self::from_bytes)
+    }
+}
+
+#[cfg(feature = "serde")]
+impl serde::Serialize for AnySignature {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        crate::serializer::serialize_hex(serializer, &self.to_bytes())
+    }
+}
+
+#[cfg(feature = "serde")]
+impl<'de> serde::Deserialize<'de> for AnySignature {
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        crate::serializer::deserialize_hex(deserializer, Self::from_bytes)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_scheme_tags() {
+        for scheme in [
+            Scheme::MlDsa,
+            Scheme::Falcon,
+            Scheme::SphincsPlus,
+            Scheme::Hybrid,
+            Scheme::MlDsa65,
+            Scheme::MlDsa87,
+        ] {
+            assert_eq!(Scheme::from_u8(scheme.to_u8()), Some(scheme));
+        }
+        assert_eq!(Scheme::from_u8(255), None);
+    }
+
+    #[cfg(feature = "mldsa")]
+    #[test]
+    fn test_any_sign_verify() {
+        let signer = crate::mldsa::keypair::SignerPair::create();
+        let msg = b"Hello, World!";
+
+        let pk = signer.public_key();
+        let sig = signer.sign_any(msg);
+        assert_eq!(pk.scheme(), Scheme::MlDsa);
+        assert!(pk.verify(msg, &sig).unwrap());
+        assert!(!pk.verify(b"Hello, Wurld!", &sig).unwrap());
+    }
+
+    #[cfg(feature = "mldsa")]
+    #[test]
+    fn test_any_bytes() {
+        let signer = crate::mldsa::keypair::SignerPair::create();
+        let msg = b"Hello, World!";
+
+        let pk = AnyPublicKey::from_bytes(&signer.public_key().to_bytes()).unwrap();
+        let sig = AnySignature::from_bytes(&signer.sign_any(msg).to_bytes()).unwrap();
+        assert!(pk.verify(msg, &sig).unwrap());
+
+        assert!(AnyPublicKey::from_bytes(&[255, 1, 2, 3]).is_err());
+        assert!(AnyPublicKey::from_bytes(&[Scheme::MlDsa.to_u8(), 1, 2, 3]).is_err());
+    }
+
+    #[cfg(all(feature = "mldsa", feature = "serde"))]
+    #[test]
+    fn test_any_serde() {
+        use crate::mldsa::da::DisplayAddress;
+
+        let signer = crate::mldsa::keypair::SignerPair::create();
+        let pk = signer.public_key();
+        let sig = signer.sign_any(b"Hello, World!");
+
+        // Keys and signatures are hex in JSON, addresses are bech32m
+        let json = serde_json::to_string(&pk).unwrap();
+        assert_eq!(json, format!("\"{}\"", hex::encode(pk.to_bytes())));
+        assert_eq!(serde_json::from_str::<AnyPublicKey>(&json).unwrap(), pk);
+
+        let json = serde_json::to_string(&sig).unwrap();
+        assert_eq!(serde_json::from_str::<AnySignature>(&json).unwrap(), sig);
+
+        let da = DisplayAddress::new();
+        let json = serde_json::to_string(&da).unwrap();
+        assert_eq!(json, format!("\"{}\"", da.as_str()));
+        let da2: DisplayAddress = serde_json::from_str(&json).unwrap();
+        assert_eq!(da2.as_bytes(), da.as_bytes());
+
+        assert!(serde_json::from_str::<AnyPublicKey>("\"zz\"").is_err());
+        assert!(serde_json::from_str::<DisplayAddress>("\"not an address\"").is_err());
+    }
+
+    #[cfg(all(feature = "mldsa", feature = "falcon"))]
+    #[test]
+    fn test_scheme_mismatch() {
+        let mldsa = crate::mldsa::keypair::SignerPair::create();
+        let falcon = crate::falcon::keypair::SignerPair::create();
+        let msg = b"Hello, World!";
+
+        let sig = falcon.sign_any(msg);
+        assert!(matches!(
+            mldsa.public_key().verify(msg, &sig),
+            Err(CryptoError::SchemeMismatch)
+        ));
+        assert!(falcon.public_key().verify(msg, &sig).unwrap());
+    }
+}
@@ -0,0 +1,112 @@
+use std::fmt;
+
+use subtle::ConstantTimeEq;
+use zeroize::Zeroize;
+
+pub use zeroize::Zeroizing;
+
+/// Exported secret key material, wiped when dropped. The bytes are only
+/// reachable through `expose`, `Debug` and `Display` never print them and
+/// comparisons run in constant time.
+#[derive(Clone, Default)]
+pub struct SecretBytes(Vec<u8>);
+
+impl SecretBytes {
+    pub fn new(bytes: Vec<u8>) -> Self {
+        Self(bytes)
+    }
+
+    pub fn from_slice(bytes: &[u8]) -> Self {
+        Self(bytes.to_vec())
+    }
+
+    /// Every read of the secret goes through here, so uses are easy to audit
+    pub fn expose(&self) -> &[u8] {
+        &self.0
+    }
+
+    pub fn len(&self) -> usize {
+        self.0.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+}
+
+impl From<Vec<u8>> for SecretBytes {
+    fn from(bytes: Vec<u8>) -> Self {
+        Self(bytes)
+    }
+}
+
+impl Drop for SecretBytes {
+    fn drop(&mut self) {
+        self.0.zeroize();
+    }
+}
+
+impl PartialEq for SecretBytes {
+    fn eq(&self, other: &Self) -> bool {
+        ct_eq(&self.0, &other.0)
+    }
+}
+
+impl Eq for SecretBytes {}
+
+impl fmt::Debug for SecretBytes {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "SecretBytes([REDACTED; {}])", self.0.len())
+    }
+}
+
+impl fmt::Display for SecretBytes {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "[REDACTED]")
+    }
+}
+
+/// Constant time equality, only the lengths are compared in variable time
+pub fn ct_eq(a: &[u8], b: &[u8]) -> bool {
+    a.len() == b.len() && bool::from(a.ct_eq(b))
+}
+
+/// Wipes a key type of the PQClean bindings in place, those are plain byte
+/// arrays that do not implement `Zeroize` themselves
+pub(crate) fn zeroize_pod<T: Copy>(value: &mut T) {
+    // T is Copy, so it holds no pointers or drop glue that zeroes could break
+    let bytes = unsafe {
+        std::slice::from_raw_parts_mut(value as *mut T as *mut u8, std::mem::size_of::<T>())
+    };
+    bytes.zeroize();
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_secret_bytes_redacted() {
+        let secret = SecretBytes::from_slice(&[0xAB; 4]);
+        assert_eq!(format!("{:?}", secret), "SecretBytes([REDACTED; 4])");
+        assert_eq!(format!("{}", secret), "[REDACTED]");
+        assert_eq!(secret.expose(), &[0xAB; 4]);
+    }
+
+    #[test]
+    fn test_ct_eq() {
+        assert!(ct_eq(b"cesium", b"cesium"));
+        assert!(!ct_eq(b"cesium", b"cesiun"));
+        assert!(!ct_eq(b"cesium", b"cesium!"));
+
+        assert_eq!(SecretBytes::from_slice(b"a"), SecretBytes::from_slice(b"a"));
+        assert_ne!(SecretBytes::from_slice(b"a"), SecretBytes::from_slice(b"b"));
+    }
+
+    #[test]
+    fn test_zeroize_pod() {
+        let mut key = [0x5Au8; 48];
+        zeroize_pod(&mut key);
+        assert_eq!(key, [0u8; 48]);
+    }
+}
@@ -1,69 +1,124 @@
-use serde::de::{Deserialize, Deserializer, Error, SeqAccess, Visitor};
-use serde::ser::{Serialize, SerializeTuple, Serializer};
 use std::fmt;
-use std::marker::PhantomData;
-
-pub trait Array48<'de>: Sized {
-    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
-    where
-        S: Serializer;
-    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
-    where
-        D: Deserializer<'de>;
+
+use serde::de::{Deserializer, Error, SeqAccess, Visitor};
+use serde::ser::Serializer;
+
+// Human readable formats (JSON) get the same text as the rest of the
+// project shows to users, bech32m addresses and hex bytes. Binary formats
+// get the raw bytes, the same bytes `to_bytes` writes.
+
+/// Writes `text()` for human readable formats and `bytes` otherwise
+pub fn serialize_encoded<S, F>(serializer: S, text: F, bytes: &[u8]) -> Result<S::Ok, S::Error>
+where
+    S: Serializer,
+    F: FnOnce() -> String,
+{
+    if serializer.is_human_readable() {
+        serializer.serialize_str(&text())
+    } else {
+        serializer.serialize_bytes(bytes)
+    }
 }
 
-impl<'de, T> Array48<'de> for [T; 48]
+/// Reads what `serialize_encoded` wrote, parsing the text or the bytes
+pub fn deserialize_encoded<'de, D, T, E1, E2>(
+    deserializer: D,
+    from_str: impl FnOnce(&str) -> Result<T, E1>,
+    from_bytes: impl FnOnce(&[u8]) -> Result<T, E2>,
+) -> Result<T, D::Error>
 where
-    T: Default + Clone + Serialize + Deserialize<'de>,
+    D: Deserializer<'de>,
+    E1: fmt::Display,
+    E2: fmt::Display,
 {
-    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
-    where
-        S: Serializer,
-    {
-        let mut seq = serializer.serialize_tuple(48)?;
-        for e in self.iter() {
-            seq.serialize_element(e)?;
-        }
-        seq.end()
+    if deserializer.is_human_readable() {
+        let text = deserializer.deserialize_str(StringVisitor)?;
+        from_str(&text).map_err(D::Error::custom)
+    } else {
+        let bytes = deserializer.deserialize_byte_buf(BytesVisitor)?;
+        from_bytes(&bytes).map_err(D::Error::custom)
     }
+}
 
-    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
-    where
-        D: Deserializer<'de>,
-    {
-        struct ArrayVisitor<T> {
-            marker: PhantomData<T>,
-        }
+/// Hex for human readable formats, raw bytes otherwise
+pub fn serialize_hex<S: Serializer>(serializer: S, bytes: &[u8]) -> Result<S::Ok, S::Error> {
+    serialize_encoded(serializer, || hex::encode(bytes), bytes)
+}
 
-        impl<'de, T> Visitor<'de> for ArrayVisitor<T>
-        where
-            T: Default + Clone + Deserialize<'de>,
-        {
-            type Value = [T; 48];
-
-            fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
-                formatter.write_str("an array of 48 elements")
-            }
-
-            fn visit_seq<A>(self, mut seq: A) -> Result<Self::Value, A::Error>
-            where
-                A: SeqAccess<'de>,
-            {
-                let mut arr = core::array::from_fn(|_| T::default());
-                for i in 0..48 {
-                    arr[i] = seq
-                        .next_element()?
-                        .ok_or_else(|| Error::invalid_length(i, &self))?;
-                }
-                Ok(arr)
-            }
-        }
+pub fn deserialize_hex<'de, D, T, E>(
+    deserializer: D,
+    from_bytes: impl Fn(&[u8]) -> Result<T, E>,
+) -> Result<T, D::Error>
+where
+    D: Deserializer<'de>,
+    E: fmt::Display,
+{
+    deserialize_encoded(
+        deserializer,
+        |text| match hex::decode(text) {
+            Ok(bytes) => from_bytes(&bytes).map_err(|e| e.to_string()),
+            Err(e) => Err(e.to_string()),
+        },
+        &from_bytes,
+    )
+}
+
+/// For `#[serde(with = "...")]` on byte vectors
+pub mod hex_bytes {
+    use serde::{Deserializer, Serializer};
+
+    pub fn serialize<S: Serializer>(bytes: &[u8], serializer: S) -> Result<S::Ok, S::Error> {
+        super::serialize_hex(serializer, bytes)
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Vec<u8>, D::Error> {
+        super::deserialize_hex(deserializer, |bytes| {
+            Ok::<_, std::convert::Infallible>(bytes.to_vec())
+        })
+    }
+}
+
+struct StringVisitor;
+
+impl Visitor<'_> for StringVisitor {
+    type Value = String;
+
+    fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+        formatter.write_str("a string")
+    }
+
+    fn visit_str<E: Error>(self, value: &str) -> Result<Self::Value, E> {
+        Ok(value.to_string())
+    }
+
+    fn visit_string<E: Error>(self, value: String) -> Result<Self::Value, E> {
+        Ok(value)
+    }
+}
 
-        deserializer.deserialize_tuple(
-            48,
-            ArrayVisitor {
-                marker: PhantomData,
-            },
-        )
+struct BytesVisitor;
+
+impl<'de> Visitor<'de> for BytesVisitor {
+    type Value = Vec<u8>;
+
+    fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+        formatter.write_str("a byte array")
+    }
+
+    fn visit_bytes<E: Error>(self, value: &[u8]) -> Result<Self::Value, E> {
+        Ok(value.to_vec())
+    }
+
+    fn visit_byte_buf<E: Error>(self, value: Vec<u8>) -> Result<Self::Value, E> {
+        Ok(value)
+    }
+
+    // Some formats write bytes as a plain sequence
+    fn visit_seq<A: SeqAccess<'de>>(self, mut seq: A) -> Result<Self::Value, A::Error> {
+        let mut bytes = Vec::with_capacity(seq.size_hint().unwrap_or(0));
+        while let Some(byte) = seq.next_element()? {
+            bytes.push(byte);
+        }
+        Ok(bytes)
     }
 }
@@ -0,0 +1,389 @@
+use std::{io::Write, path::Path};
+
+use sha3::{Digest, Sha3_256};
+use zeroize::Zeroize;
+
+use crate::{
+    bulletproofs::fields::FieldElement,
+    errors::CryptoError,
+    polynomial::Polynomial,
+    scheme::Scheme,
+    secret::{ct_eq, SecretBytes, Zeroizing},
+};
+
+// Shamir secret sharing of a secret key over the Mersenne-61 field. The key
+// is cut into 7 byte chunks, each one small enough to be a field element,
+// and every chunk is shared with its own random polynomial. A share holds
+// the evaluations of all of them at its index.
+//
+// Share layout, integers are little endian:
+// MAGIC (4) | VERSION (1) | SCHEME (1) | THRESHOLD (1) | INDEX (1)
+// | SECRET_LEN (4) | PUB_KEY_LEN (4) | PUB_KEY | CHECKSUM (32) | VALUES (8 each)
+// The header is the same for every share of a split, the checksum commits to
+// the public and secret key so a wrong or tampered share is caught when the
+// key is recombined.
+
+pub const MAGIC: &[u8; 4] = b"CSSS";
+pub const VERSION: u8 = 1;
+pub const CHUNK_BYTE_LEN: usize = 7;
+pub const CHECKSUM_BYTE_LEN: usize = 32;
+pub const HEADER_BYTE_LEN: usize = 4 + 1 + 1 + 1 + 1 + 4 + 4;
+
+const CHECKSUM_DOMAIN: &[u8] = b"cesium-shamir";
+
+macro_rules! bounds_check {
+    ($bytes:expr, $len:expr) => {
+        if $bytes.len() < $len {
+            return Err(CryptoError::InvalidShare);
+        }
+    };
+}
+
+pub struct Share {
+    scheme: Scheme,
+    threshold: u8,
+    index: u8,
+    secret_len: u32,
+    pub_key: Vec<u8>,
+    checksum: [u8; CHECKSUM_BYTE_LEN],
+    values: Vec<FieldElement>,
+}
+
+fn checksum(scheme: Scheme, pub_key: &[u8], sec_key: &[u8]) -> [u8; CHECKSUM_BYTE_LEN] {
+    let mut hasher = Sha3_256::new();
+    hasher.update(CHECKSUM_DOMAIN);
+    hasher.update([scheme.to_u8()]);
+    hasher.update(pub_key);
+    hasher.update(sec_key);
+    hasher.finalize().into()
+}
+
+fn chunk_count(secret_len: usize) -> usize {
+    secret_len.div_ceil(CHUNK_BYTE_LEN)
+}
+
+/// Splits the secret key into `count` shares, any `threshold` of them
+/// recombine it while fewer reveal nothing about it
+pub fn split(
+    scheme: Scheme,
+    pub_key: &[u8],
+    sec_key: &[u8],
+    threshold: u8,
+    count: u8,
+) -> Result<Vec<Share>, CryptoError> {
+    if threshold == 0 || threshold > count {
+        return Err(CryptoError::InvalidShareThreshold);
+    }
+    if pub_key.len() != scheme.pub_byte_len()? || sec_key.is_empty() {
+        return Err(CryptoError::InvalidKeyLength);
+    }
+
+    let checksum = checksum(scheme, pub_key, sec_key);
+    let mut shares: Vec<Share> = (1..=count)
+        .map(|index| Share {
+            scheme,
+            threshold,
+            index,
+            secret_len: sec_key.len() as u32,
+            pub_key: pub_key.to_vec(),
+            checksum,
+            values: Vec::with_capacity(chunk_count(sec_key.len())),
+        })
+        .collect();
+
+    let mut rng = rand::thread_rng();
+    for chunk in sec_key.chunks(CHUNK_BYTE_LEN) {
+        let mut word = [0u8; 8];
+        word[..chunk.len()].copy_from_slice(chunk);
+
+        let mut poly = Polynomial::random(
+            FieldElement::new(u64::from_le_bytes(word)),
+            threshold as usize - 1,
+            &mut rng,
+        );
+        word.zeroize();
+
+        for share in shares.iter_mut() {
+            let value = poly.evaluate(&FieldElement::new(share.index as u64));
+            share.values.push(value);
+        }
+
+        for coeff in poly.coefficients.iter_mut() {
+            coeff.value.zeroize();
+        }
+    }
+
+    Ok(shares)
+}
+
+/// Recombines the secret key from at least `threshold` shares of one split
+pub fn combine(shares: &[Share]) -> Result<SecretBytes, CryptoError> {
+    let first = shares.first().ok_or(CryptoError::InsufficientShares)?;
+    if shares.len() < first.threshold as usize {
+        return Err(CryptoError::InsufficientShares);
+    }
+
+    for (i, share) in shares.iter().enumerate() {
+        if !first.same_split(share) {
+            return Err(CryptoError::InvalidShare);
+        }
+        if shares[..i].iter().any(|other| other.index == share.index) {
+            return Err(CryptoError::InvalidShare);
+        }
+    }
+
+    // Any threshold shares determine the polynomials, extra ones are unused
+    let shares = &shares[..first.threshold as usize];
+    let xs: Vec<FieldElement> = shares
+        .iter()
+        .map(|share| FieldElement::new(share.index as u64))
+        .collect();
+
+    // Lagrange basis polynomials evaluated at zero
+    let weights: Vec<FieldElement> = xs
+        .iter()
+        .enumerate()
+        .map(|(i, xi)| {
+            let mut num = FieldElement::new(1);
+            let mut den = FieldElement::new(1);
+            for (j, xj) in xs.iter().enumerate() {
+                if i != j {
+                    num = num.mul(xj);
+                    den = den.mul(&xj.sub(xi));
+                }
+            }
+            num.mul(&den.inverse())
+        })
+        .collect();
+
+    let secret_len = first.secret_len as usize;
+    let mut bytes = Zeroizing::new(Vec::with_capacity(chunk_count(secret_len) * CHUNK_BYTE_LEN));
+    for chunk in 0..chunk_count(secret_len) {
+        let mut value = FieldElement::new(0);
+        for (share, weight) in shares.iter().zip(&weights) {
+            value = value.add(&share.values[chunk].mul(weight));
+        }
+
+        let word = Zeroizing::new(value.value.to_le_bytes());
+        value.value.zeroize();
+        // The chunks are 7 bytes, a set high byte means mismatched shares
+        if word[CHUNK_BYTE_LEN] != 0 {
+            return Err(CryptoError::InvalidShare);
+        }
+        bytes.extend_from_slice(&word[..CHUNK_BYTE_LEN]);
+    }
+    let sec_key = SecretBytes::from_slice(&bytes[..secret_len]);
+
+    if !ct_eq(
+        &checksum(first.scheme, &first.pub_key, sec_key.expose()),
+        &first.checksum,
+    ) {
+        return Err(CryptoError::InvalidShare);
+    }
+
+    Ok(sec_key)
+}
+
+impl Share {
+    fn same_split(&self, other: &Share) -> bool {
+        self.scheme == other.scheme
+            && self.threshold == other.threshold
+            && self.secret_len == other.secret_len
+            && self.pub_key == other.pub_key
+            && self.checksum == other.checksum
+    }
+
+    pub fn scheme(&self) -> Scheme {
+        self.scheme
+    }
+
+    pub fn threshold(&self) -> u8 {
+        self.threshold
+    }
+
+    pub fn index(&self) -> u8 {
+        self.index
+    }
+
+    pub fn pub_key(&self) -> &[u8] {
+        &self.pub_key
+    }
+
+    pub fn address(&self) -> Result<String, CryptoError> {
+        self.scheme.display_address(&self.pub_key)
+    }
+
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let mut bytes = Vec::with_capacity(
+            HEADER_BYTE_LEN + self.pub_key.len() + CHECKSUM_BYTE_LEN + self.values.len() * 8,
+        );
+        bytes.extend_from_slice(MAGIC);
+        bytes.push(VERSION);
+        bytes.push(self.scheme.to_u8());
+        bytes.push(self.threshold);
+        bytes.push(self.index);
+        bytes.extend(self.secret_len.to_le_bytes());
+        bytes.extend((self.pub_key.len() as u32).to_le_bytes());
+        bytes.extend_from_slice(&self.pub_key);
+        bytes.extend_from_slice(&self.checksum);
+        for value in &self.values {
+            bytes.extend(value.value.to_le_bytes());
+        }
+        bytes
+    }
+
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self, CryptoError> {
+        bounds_check!(bytes, HEADER_BYTE_LEN);
+        if &bytes[0..4] != MAGIC || bytes[4] != VERSION {
+            return Err(CryptoError::InvalidShare);
+        }
+
+        let scheme = Scheme::from_u8(bytes[5]).ok_or(CryptoError::UnsupportedScheme(bytes[5]))?;
+        let threshold = bytes[6];
+        let index = bytes[7];
+        if threshold == 0 || index == 0 {
+            return Err(CryptoError::InvalidShare);
+        }
+
+        let secret_len = u32::from_le_bytes(bytes[8..12].try_into().unwrap());
+        let pub_key_len = u32::from_le_bytes(bytes[12..16].try_into().unwrap()) as usize;
+        if pub_key_len != scheme.pub_byte_len()? || secret_len == 0 {
+            return Err(CryptoError::InvalidShare);
+        }
+
+        let mut offset = HEADER_BYTE_LEN;
+        let chunks = chunk_count(secret_len as usize);
+        if bytes.len() != offset + pub_key_len + CHECKSUM_BYTE_LEN + chunks * 8 {
+            return Err(CryptoError::InvalidShare);
+        }
+
+        let pub_key = bytes[offset..offset + pub_key_len].to_vec();
+        offset += pub_key_len;
+
+        let mut checksum = [0u8; CHECKSUM_BYTE_LEN];
+        checksum.copy_from_slice(&bytes[offset..offset + CHECKSUM_BYTE_LEN]);
+        offset += CHECKSUM_BYTE_LEN;
+
+        let values = bytes[offset..]
+            .chunks_exact(8)
+            .map(|value| FieldElement::new(u64::from_le_bytes(value.try_into().unwrap())))
+            .collect();
+
+        Ok(Self {
+            scheme,
+            threshold,
+            index,
+            secret_len,
+            pub_key,
+            checksum,
+            values,
+        })
+    }
+
+    pub fn save(&self, path: &Path) -> Result<(), CryptoError> {
+        let mut options = std::fs::OpenOptions::new();
+        options.write(true).create_new(true);
+        // Only the owner may read the share
+        #[cfg(unix)]
+        std::os::unix::fs::OpenOptionsExt::mode(&mut options, 0o600);
+
+        let mut file = options.open(path)?;
+        file.write_all(&self.to_bytes())?;
+        Ok(())
+    }
+
+    pub fn load(path: &Path) -> Result<Self, CryptoError> {
+        Self::from_bytes(&std::fs::read(path)?)
+    }
+}
+
+impl Drop for Share {
+    fn drop(&mut self) {
+        for value in self.values.iter_mut() {
+            value.value.zeroize();
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SCHEME: Scheme = Scheme::MlDsa;
+
+    fn test_key() -> (Vec<u8>, Vec<u8>) {
+        let pub_key = vec![7u8; SCHEME.pub_byte_len().unwrap()];
+        // Not a multiple of the chunk length, so the last chunk is padded
+        let sec_key = (0..100u8).map(|i| i.wrapping_mul(37) | 0x80).collect();
+        (pub_key, sec_key)
+    }
+
+    #[test]
+    fn test_split_combine() {
+        let (pub_key, sec_key) = test_key();
+        let shares = split(SCHEME, &pub_key, &sec_key, 3, 5).unwrap();
+        assert_eq!(shares.len(), 5);
+
+        for subset in [[0, 1, 2], [4, 2, 0], [1, 3, 4]] {
+            let picked: Vec<Share> = subset
+                .iter()
+                .map(|&i| Share::from_bytes(&shares[i].to_bytes()).unwrap())
+                .collect();
+            assert_eq!(combine(&picked).unwrap().expose(), &sec_key[..]);
+        }
+
+        assert!(matches!(
+            combine(&shares[..2]),
+            Err(CryptoError::InsufficientShares)
+        ));
+        assert_eq!(combine(&shares).unwrap().expose(), &sec_key[..]);
+    }
+
+    #[test]
+    fn test_threshold() {
+        let (pub_key, sec_key) = test_key();
+        for (threshold, count) in [(0, 3), (4, 3)] {
+            assert!(matches!(
+                split(SCHEME, &pub_key, &sec_key, threshold, count),
+                Err(CryptoError::InvalidShareThreshold)
+            ));
+        }
+
+        let shares = split(SCHEME, &pub_key, &sec_key, 1, 1).unwrap();
+        assert_eq!(combine(&shares).unwrap().expose(), &sec_key[..]);
+    }
+
+    #[test]
+    fn test_invalid_shares() {
+        let (pub_key, sec_key) = test_key();
+        let shares = split(SCHEME, &pub_key, &sec_key, 2, 3).unwrap();
+        let other = split(SCHEME, &pub_key, &sec_key, 2, 3).unwrap();
+
+        // Same index twice
+        let dup = Share::from_bytes(&shares[0].to_bytes()).unwrap();
+        assert!(combine(&[dup, Share::from_bytes(&shares[0].to_bytes()).unwrap()]).is_err());
+
+        // Shares of two splits of the same key do not mix
+        let mixed = [
+            Share::from_bytes(&shares[0].to_bytes()).unwrap(),
+            Share::from_bytes(&other[1].to_bytes()).unwrap(),
+        ];
+        assert!(matches!(combine(&mixed), Err(CryptoError::InvalidShare)));
+
+        // A tampered value fails the checksum
+        let mut bytes = shares[1].to_bytes();
+        let last = bytes.len() - 1;
+        bytes[last - 8] ^= 1;
+        let tampered = [
+            Share::from_bytes(&shares[0].to_bytes()).unwrap(),
+            Share::from_bytes(&bytes).unwrap(),
+        ];
+        assert!(matches!(combine(&tampered), Err(CryptoError::InvalidShare)));
+
+        let bytes = shares[0].to_bytes();
+        assert!(Share::from_bytes(&bytes[..bytes.len() - 1]).is_err());
+        let mut bad_magic = bytes.clone();
+        bad_magic[0] = b'X';
+        assert!(Share::from_bytes(&bad_magic).is_err());
+    }
+}
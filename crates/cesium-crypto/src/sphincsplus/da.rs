@@ -1,7 +1,12 @@
+use std::str::FromStr;
+
 use rand::RngCore;
 use sha3::Digest;
 
-use crate::errors::CryptoError;
+use crate::{address, errors::CryptoError};
+
+#[cfg(feature = "serde")]
+use crate::serializer;
 
 use super::{PublicKeyBytes, PUB_BYTE_LEN};
 
@@ -38,10 +43,8 @@ impl DisplayAddress {
             return Err(CryptoError::InvalidDisplayAddress);
         }
 
-        let mut hasher = sha3::Sha3_256::new();
-        hasher.update(id);
         let mut da = [0u8; DA_BYTE_LEN];
-        da.copy_from_slice(&hasher.finalize());
+        da.copy_from_slice(id);
 
         Ok(Self { da })
     }
@@ -65,13 +68,28 @@ impl DisplayAddress {
     }
 
     pub fn as_str(&self) -> String {
-        bs58::encode(&self.da).into_string()
+        address::encode(&self.da)
     }
+}
+
+impl FromStr for DisplayAddress {
+    type Err = CryptoError;
+
+    fn from_str(da: &str) -> Result<Self, CryptoError> {
+        Self::from_bytes(&address::decode(da, DA_BYTE_LEN)?)
+    }
+}
+
+#[cfg(feature = "serde")]
+impl serde::Serialize for DisplayAddress {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer::serialize_encoded(serializer, || self.as_str(), &self.da)
+    }
+}
 
-    pub fn from_str(da: &str) -> Result<Self, bs58::decode::Error> {
-        let da = bs58::decode(da).into_vec()?;
-        let mut da_bytes = [0u8; DA_BYTE_LEN];
-        da_bytes.copy_from_slice(&da);
-        Ok(Self { da: da_bytes })
+#[cfg(feature = "serde")]
+impl<'de> serde::Deserialize<'de> for DisplayAddress {
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        serializer::deserialize_encoded(deserializer, Self::from_str, Self::from_bytes)
     }
 }
@@ -0,0 +1,47 @@
+use sha3::{
+    digest::{ExtendableOutput, Update, XofReader},
+    Shake256,
+};
+
+use zeroize::Zeroize;
+
+use crate::{derivation::KeyBytes, secret::SecretBytes};
+
+use super::{PUB_BYTE_LEN, SEC_BYTE_LEN};
+
+// pqcrypto only exposes randomized keygen, PQClean's seeded keygen is part
+// of the library linked by pqcrypto-sphincsplus and takes SK.seed, SK.prf
+// and PK.seed (3 * n bytes) as input
+
+const SEED_BYTE_LEN: usize = 72;
+const SEED_DOMAIN: &[u8] = b"cesium-sphincsplus-keygen";
+
+extern "C" {
+    fn PQCLEAN_SPHINCSSHA2192SSIMPLE_CLEAN_crypto_sign_seed_keypair(
+        pk: *mut u8,
+        sk: *mut u8,
+        seed: *const u8,
+    ) -> i32;
+}
+
+pub fn keypair_from_seed(seed: &KeyBytes) -> (Vec<u8>, SecretBytes) {
+    let mut hasher = Shake256::default();
+    hasher.update(SEED_DOMAIN);
+    hasher.update(seed);
+    let mut expanded = [0u8; SEED_BYTE_LEN];
+    hasher.finalize_xof().read(&mut expanded);
+
+    let mut pk = vec![0u8; PUB_BYTE_LEN];
+    let mut sk = vec![0u8; SEC_BYTE_LEN];
+    let rc = unsafe {
+        PQCLEAN_SPHINCSSHA2192SSIMPLE_CLEAN_crypto_sign_seed_keypair(
+            pk.as_mut_ptr(),
+            sk.as_mut_ptr(),
+            expanded.as_ptr(),
+        )
+    };
+    assert_eq!(rc, 0);
+    expanded.zeroize();
+
+    (pk, SecretBytes::new(sk))
+}
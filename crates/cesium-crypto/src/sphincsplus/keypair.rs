@@ -1,12 +1,26 @@
+use std::{fmt, path::Path};
+
 use pqcrypto_sphincsplus::{
-    sphincssha2192ssimple, sphincssha2192ssimple_keypair, sphincssha2192ssimple_open,
-    sphincssha2192ssimple_sign,
+    sphincssha2192ssimple, sphincssha2192ssimple_detached_sign, sphincssha2192ssimple_keypair,
+    sphincssha2192ssimple_open, sphincssha2192ssimple_sign,
+    sphincssha2192ssimple_verify_detached_signature,
+};
+use pqcrypto_traits::sign::{
+    DetachedSignature, PublicKey, SecretKey, SignedMessage, VerificationError,
+};
+
+use crate::{
+    derivation::KeyBytes,
+    errors::CryptoError,
+    keystore::Keystore,
+    scheme::{AnyPublicKey, AnySignature, Scheme, Signer},
+    secret::{zeroize_pod, SecretBytes},
 };
-use pqcrypto_traits::sign::{PublicKey, SecretKey, SignedMessage};
 
-use crate::errors::CryptoError;
+#[cfg(feature = "serde")]
+use crate::serializer;
 
-use super::{da::DisplayAddress, PUB_BYTE_LEN, SIG_BYTE_LEN};
+use super::{da::DisplayAddress, ffi, PUB_BYTE_LEN, SIG_BYTE_LEN};
 
 pub trait ViewOperations {
     fn pub_key(&self) -> &sphincssha2192ssimple::PublicKey;
@@ -27,6 +41,18 @@ pub trait ViewOperations {
 
         Ok(v_result.unwrap() == msg)
     }
+    fn verify_detached(&self, msg: &[u8], sig: &[u8]) -> Result<bool, CryptoError> {
+        if sig.len() != SIG_BYTE_LEN {
+            return Err(CryptoError::InvalidSignature);
+        }
+
+        let ds = sphincssha2192ssimple::DetachedSignature::from_bytes(sig)?;
+        match sphincssha2192ssimple_verify_detached_signature(&ds, msg, self.pub_key()) {
+            Ok(()) => Ok(true),
+            Err(VerificationError::InvalidSignature) => Ok(false),
+            Err(_) => Err(CryptoError::UnknownVerificationError),
+        }
+    }
     fn get_da(&self) -> DisplayAddress {
         // Get the public key
         let pub_key = self.pub_key().as_bytes();
@@ -64,6 +90,21 @@ impl VerifierPair {
     }
 }
 
+// Public keys are hex in JSON
+#[cfg(feature = "serde")]
+impl serde::Serialize for VerifierPair {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer::serialize_hex(serializer, &self.to_bytes())
+    }
+}
+
+#[cfg(feature = "serde")]
+impl<'de> serde::Deserialize<'de> for VerifierPair {
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        serializer::deserialize_hex(deserializer, Self::from_bytes)
+    }
+}
+
 impl ViewOperations for VerifierPair {
     fn pub_key(&self) -> &sphincssha2192ssimple::PublicKey {
         &self.pub_key
@@ -84,23 +125,55 @@ impl SignerPair {
         }
     }
 
+    /// Deterministic keypair, the same seed always gives the same keys
+    pub fn from_seed(seed: &KeyBytes) -> Self {
+        let (pk, sk) = ffi::keypair_from_seed(seed);
+        Self::from_bytes(&pk, sk.expose()).unwrap()
+    }
+
     pub fn sign(&self, msg: &[u8]) -> Vec<u8> {
         let sm = sphincssha2192ssimple_sign(msg, &self.sec_key);
         sm.as_bytes().to_vec()
     }
 
+    pub fn sign_detached(&self, msg: &[u8]) -> Vec<u8> {
+        let ds = sphincssha2192ssimple_detached_sign(msg, &self.sec_key);
+        ds.as_bytes().to_vec()
+    }
+
     pub fn from_bytes(pub_key: &[u8], sec_key: &[u8]) -> Result<Self, CryptoError> {
         let pub_key = sphincssha2192ssimple::PublicKey::from_bytes(pub_key)?;
         let sec_key = sphincssha2192ssimple::SecretKey::from_bytes(sec_key)?;
         Ok(Self { pub_key, sec_key })
     }
 
-    pub fn to_bytes(&self) -> (Vec<u8>, Vec<u8>) {
+    pub fn to_bytes(&self) -> (Vec<u8>, SecretBytes) {
         (
             self.pub_key.as_bytes().to_vec(),
-            self.sec_key.as_bytes().to_vec(),
+            SecretBytes::from_slice(self.sec_key.as_bytes()),
         )
     }
+
+    pub fn to_keystore(&self, password: &[u8]) -> Result<Keystore, CryptoError> {
+        let (pub_key, sec_key) = self.to_bytes();
+        Keystore::encrypt(Scheme::SphincsPlus, &pub_key, sec_key.expose(), password)
+    }
+
+    pub fn from_keystore(keystore: &Keystore, password: &[u8]) -> Result<Self, CryptoError> {
+        if keystore.scheme() != Scheme::SphincsPlus {
+            return Err(CryptoError::SchemeMismatch);
+        }
+
+        Self::from_bytes(keystore.pub_key(), keystore.decrypt(password)?.expose())
+    }
+
+    pub fn save_keystore(&self, path: &Path, password: &[u8]) -> Result<(), CryptoError> {
+        self.to_keystore(password)?.save(path)
+    }
+
+    pub fn load_keystore(path: &Path, password: &[u8]) -> Result<Self, CryptoError> {
+        Self::from_keystore(&Keystore::load(path)?, password)
+    }
 }
 
 impl ViewOperations for SignerPair {
@@ -109,6 +182,32 @@ impl ViewOperations for SignerPair {
     }
 }
 
+impl Drop for SignerPair {
+    fn drop(&mut self) {
+        zeroize_pod(&mut self.sec_key);
+    }
+}
+
+// Only the address is printed, never the secret key
+impl fmt::Debug for SignerPair {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("SignerPair")
+            .field("address", &self.get_da().as_str())
+            .field("sec_key", &"[REDACTED]")
+            .finish()
+    }
+}
+
+impl Signer for SignerPair {
+    fn public_key(&self) -> AnyPublicKey {
+        AnyPublicKey::new(Scheme::SphincsPlus, self.pub_key().as_bytes()).unwrap()
+    }
+
+    fn sign_any(&self, msg: &[u8]) -> AnySignature {
+        AnySignature::new(Scheme::SphincsPlus, &self.sign_detached(msg)).unwrap()
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -122,6 +221,31 @@ mod tests {
         assert!(signer.verify(msg, &sig).unwrap());
     }
 
+    #[test]
+    fn test_signer_sign_detached() {
+        let signer = SignerPair::create();
+        let msg = b"Hello, World!";
+        let sig = signer.sign_detached(msg);
+
+        assert_eq!(sig.len(), SIG_BYTE_LEN);
+        assert!(signer.verify_detached(msg, &sig).unwrap());
+        assert!(!signer.verify_detached(b"Hello, Wurld!", &sig).unwrap());
+    }
+
+    #[test]
+    fn test_signer_from_seed() {
+        let signer = SignerPair::from_seed(&[7u8; 32]);
+        let signer2 = SignerPair::from_seed(&[7u8; 32]);
+        let signer3 = SignerPair::from_seed(&[8u8; 32]);
+        assert_eq!(signer.to_bytes(), signer2.to_bytes());
+        assert_ne!(signer.pub_key().as_bytes(), signer3.pub_key().as_bytes());
+
+        let msg = b"Hello, World!";
+        let sig = signer.sign_detached(msg);
+        assert!(signer2.verify_detached(msg, &sig).unwrap());
+        assert!(!signer3.verify_detached(msg, &sig).unwrap());
+    }
+
     #[test]
     fn test_verifier_verify() {
         let signer = SignerPair::create();
@@ -142,6 +266,10 @@ mod tests {
         let da2 = vierifer.get_da();
 
         assert_eq!(da.as_str(), da2.as_str());
+
+        // The string form parses back to the same address
+        let da3: DisplayAddress = da.as_str().parse().unwrap();
+        assert_eq!(da3.as_bytes(), da.as_bytes());
     }
 
     #[test]
@@ -149,7 +277,7 @@ mod tests {
         let signer = SignerPair::create();
         let (pub_key, sec_key) = signer.to_bytes();
 
-        let signer2 = SignerPair::from_bytes(&pub_key, &sec_key).unwrap();
+        let signer2 = SignerPair::from_bytes(&pub_key, sec_key.expose()).unwrap();
         assert_eq!(signer.pub_key().as_bytes(), signer2.pub_key().as_bytes());
     }
 
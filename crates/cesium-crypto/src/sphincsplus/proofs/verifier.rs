@@ -1,68 +1,56 @@
-use std::io::Read;
-
-use sha3::{
-    digest::{ExtendableOutput, Update},
-    Shake256,
-};
+use pqcrypto_traits::sign::PublicKey as _;
+use rand::{rngs::OsRng, Rng};
 
 use crate::{
     errors::CryptoError,
-    sphincsplus::keypair::{SignerPair, ViewOperations},
+    secret::ct_eq,
+    sphincsplus::keypair::{VerifierPair, ViewOperations},
 };
 
-use super::{Challenge, Commitment, Response, CHALLENGE_LENGTH};
+use super::{
+    commit, fiat_shamir_challenge, shake, signed_message, Challenge, Commitment, Opening,
+    OwnershipProof, Response, CHALLENGE_LENGTH,
+};
 
-/// Functions for the Verifier role
+/// Functions for the Verifier role, only the prover's public key is needed
 pub struct VerifierProtocol;
 
 impl VerifierProtocol {
-    /// Generate a challenge using SHAKE256
+    /// Generate a fresh challenge for the commitment using SHAKE256
     pub fn generate_challenge(commitment: &Commitment) -> Challenge {
-        let mut hasher = Shake256::default();
-        hasher.update(&commitment.0);
+        let mut rng = OsRng;
+        let mut nonce = [0u8; CHALLENGE_LENGTH];
+        rng.fill(&mut nonce[..]);
 
-        let mut challenge = vec![0u8; CHALLENGE_LENGTH];
-        let mut xof = hasher.finalize_xof();
-        let _ = xof.read(&mut challenge);
-
-        Challenge(challenge)
+        Challenge(shake(&[&commitment.0, &nonce]))
     }
 
     /// Verify a proof using SPHINCS+ signature verification
     pub fn verify(
-        account: &SignerPair,
+        account: &VerifierPair,
         commitment: &Commitment,
         challenge: &Challenge,
         response: &Response,
     ) -> Result<bool, CryptoError> {
         // Reconstruct message that was signed
-        let mut message = Vec::new();
-        message.extend_from_slice(&commitment.0);
-        message.extend_from_slice(&challenge.0);
-
-        // Convert response back to DetachedSignature
-        account.verify(&message, &response.0)
+        let message = signed_message(commitment, challenge);
+        account.verify_detached(&message, &response.0)
     }
 
     pub fn verify_non_interactive(
-        account: &SignerPair,
-        commitment: &Commitment,
-        response: &Response,
+        account: &VerifierPair,
+        proof: &OwnershipProof,
+        context: &[u8],
     ) -> Result<bool, CryptoError> {
         // Deterministically recreate the challenge from the commitment
-        let mut hasher = Shake256::default();
-        hasher.update(&commitment.0);
-
-        let mut challenge = vec![0u8; CHALLENGE_LENGTH];
-        let mut xof = hasher.finalize_xof();
-        let _ = xof.read(&mut challenge);
-
-        // Reconstruct the message that was signed
-        let mut message = Vec::new();
-        message.extend_from_slice(&commitment.0);
-        message.extend_from_slice(&challenge);
+        let challenge =
+            fiat_shamir_challenge(&proof.commitment, account.pub_key().as_bytes(), context);
+        Self::verify(account, &proof.commitment, &challenge, &proof.response)
+    }
 
-        // Verify the response signature
-        account.verify(&message, &response.0)
+    /// Check that the opening reveals the secret behind the commitment
+    pub fn verify_opening(commitment: &Commitment, opening: &Opening) -> bool {
+        let expected = commit(opening.secret.expose(), &opening.salt);
+        ct_eq(&expected.0, &commitment.0)
     }
 }
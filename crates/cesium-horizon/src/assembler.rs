@@ -0,0 +1,212 @@
+use std::collections::{HashMap, HashSet, VecDeque};
+
+use cesium_crypto::{
+    erasure::{self, PayloadDigest, Shard},
+    errors::CryptoError,
+};
+
+// Payload digests come from the sender, so only this many payloads are
+// collected at once and the oldest one makes room for a new one
+pub const MAX_PENDING_PAYLOADS: usize = 64;
+// Rebuilt payloads are remembered to drop their late shards, up to this many
+pub const MAX_COMPLETED_PAYLOADS: usize = 1024;
+// Rebuild attempts made for a single shard, each tries another set of shards
+pub const MAX_DECODE_ATTEMPTS: usize = 64;
+
+// Shards are collected per header, a shard that claims a digest with another
+// layout is kept apart and can not spoil the shards of the real payload
+type PayloadKey = (PayloadDigest, u8, u8, u32);
+
+fn payload_key(shard: &Shard) -> PayloadKey {
+    (
+        *shard.digest(),
+        shard.data_shards(),
+        shard.total_shards(),
+        shard.payload_len(),
+    )
+}
+
+// Steps `picked`, sorted indices below `n`, to the next subset of the same
+// size. Returns false once every subset was visited.
+fn next_subset(picked: &mut [usize], n: usize) -> bool {
+    let k = picked.len();
+    for i in (0..k).rev() {
+        if picked[i] < n - k + i {
+            picked[i] += 1;
+            for j in i + 1..k {
+                picked[j] = picked[j - 1] + 1;
+            }
+            return true;
+        }
+    }
+    false
+}
+
+/// Collects the erasure coded shards of broadcasts and rebuilds each payload
+/// once enough of them arrived, lost shards do not lose the payload
+#[derive(Default)]
+pub struct ShardAssembler {
+    pending: HashMap<PayloadKey, Vec<Shard>>,
+    pending_order: VecDeque<PayloadKey>,
+    completed: HashSet<PayloadDigest>,
+    completed_order: VecDeque<PayloadDigest>,
+}
+
+impl ShardAssembler {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Returns the payload when this shard completes it, shards of payloads
+    /// that were already rebuilt are ignored
+    pub fn insert(&mut self, bytes: &[u8]) -> Result<Option<Vec<u8>>, CryptoError> {
+        let shard = Shard::from_bytes(bytes)?;
+        let digest = *shard.digest();
+        if self.completed.contains(&digest) {
+            return Ok(None);
+        }
+
+        let key = payload_key(&shard);
+        if !self.pending.contains_key(&key) {
+            if self.pending.len() >= MAX_PENDING_PAYLOADS {
+                if let Some(oldest) = self.pending_order.pop_front() {
+                    self.pending.remove(&oldest);
+                }
+            }
+            self.pending_order.push_back(key);
+        }
+
+        let shards = self.pending.entry(key).or_default();
+        if shards.iter().any(|other| other.index() == shard.index()) {
+            return Ok(None);
+        }
+        let data_shards = shard.data_shards() as usize;
+        shards.push(shard);
+        if shards.len() < data_shards {
+            return Ok(None);
+        }
+
+        // Every set without the new shard was tried before, so only the sets
+        // with it are. A corrupt shard fails the sets it is in and the others
+        // still rebuild the payload.
+        let newest = shards.len() - 1;
+        let mut picked: Vec<usize> = (0..data_shards - 1).collect();
+        let mut payload = None;
+        for _ in 0..MAX_DECODE_ATTEMPTS {
+            let subset: Vec<Shard> = picked
+                .iter()
+                .chain([&newest])
+                .map(|&i| shards[i].clone())
+                .collect();
+            if let Ok(decoded) = erasure::decode(&subset) {
+                payload = Some(decoded);
+                break;
+            }
+            if !next_subset(&mut picked, newest) {
+                break;
+            }
+        }
+
+        // Without a rebuild the shards are kept for the next one to arrive
+        let Some(payload) = payload else {
+            return Ok(None);
+        };
+
+        // Shards that claimed the digest with another header are done too
+        self.pending.retain(|key, _| key.0 != digest);
+        self.pending_order.retain(|key| key.0 != digest);
+        if self.completed.len() >= MAX_COMPLETED_PAYLOADS {
+            if let Some(oldest) = self.completed_order.pop_front() {
+                self.completed.remove(&oldest);
+            }
+        }
+        self.completed.insert(digest);
+        self.completed_order.push_back(digest);
+        Ok(Some(payload))
+    }
+
+    pub fn pending(&self) -> usize {
+        self.pending.len()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_recover_lost_shards() {
+        let payload: Vec<u8> = (0..500u32).map(|i| (i % 256) as u8).collect();
+        let shards = erasure::encode(&payload, 3, 5).unwrap();
+
+        // The first and third datagram are lost
+        let mut assembler = ShardAssembler::new();
+        assert_eq!(assembler.insert(&shards[1].to_bytes()).unwrap(), None);
+        assert_eq!(assembler.insert(&shards[1].to_bytes()).unwrap(), None);
+        assert_eq!(assembler.insert(&shards[3].to_bytes()).unwrap(), None);
+        assert_eq!(assembler.pending(), 1);
+        assert_eq!(
+            assembler.insert(&shards[4].to_bytes()).unwrap(),
+            Some(payload)
+        );
+
+        // Late shards of a rebuilt payload are dropped
+        assert_eq!(assembler.insert(&shards[0].to_bytes()).unwrap(), None);
+        assert_eq!(assembler.pending(), 0);
+
+        assert!(assembler.insert(&[1, 2, 3]).is_err());
+    }
+
+    #[test]
+    fn test_bad_shards() {
+        let payload: Vec<u8> = (0..500u32).map(|i| (i % 256) as u8).collect();
+        let shards = erasure::encode(&payload, 3, 5).unwrap();
+
+        // A corrupt shard arrives among three honest ones
+        let mut corrupt = shards[0].to_bytes();
+        corrupt[erasure::HEADER_BYTE_LEN] ^= 1;
+        // A forged one claims the digest with another length
+        let mut forged = shards[2].to_bytes();
+        forged[8..12].copy_from_slice(&499u32.to_le_bytes());
+
+        let mut assembler = ShardAssembler::new();
+        assert_eq!(assembler.insert(&corrupt).unwrap(), None);
+        assert_eq!(assembler.insert(&forged).unwrap(), None);
+        assert_eq!(assembler.insert(&shards[1].to_bytes()).unwrap(), None);
+        assert_eq!(assembler.insert(&shards[3].to_bytes()).unwrap(), None);
+        assert_eq!(
+            assembler.insert(&shards[4].to_bytes()).unwrap(),
+            Some(payload)
+        );
+        assert_eq!(assembler.pending(), 0);
+    }
+
+    #[test]
+    fn test_pending_limit() {
+        let mut assembler = ShardAssembler::new();
+        let first = erasure::encode(&[0u8; 64], 2, 3).unwrap();
+        assembler.insert(&first[0].to_bytes()).unwrap();
+
+        // Unfinished payloads can not pile up, the oldest one is dropped
+        for i in 1..=MAX_PENDING_PAYLOADS as u32 {
+            let shards = erasure::encode(&i.to_le_bytes(), 2, 3).unwrap();
+            assembler.insert(&shards[0].to_bytes()).unwrap();
+        }
+        assert_eq!(assembler.pending(), MAX_PENDING_PAYLOADS);
+        assert_eq!(assembler.insert(&first[1].to_bytes()).unwrap(), None);
+        assert_eq!(assembler.pending(), MAX_PENDING_PAYLOADS);
+    }
+
+    #[test]
+    fn test_next_subset() {
+        let mut picked = vec![0, 1];
+        let mut subsets = vec![picked.clone()];
+        while next_subset(&mut picked, 4) {
+            subsets.push(picked.clone());
+        }
+        assert_eq!(subsets, [[0, 1], [0, 2], [0, 3], [1, 2], [1, 3], [2, 3]]);
+
+        // Choosing nothing is a single, empty subset
+        assert!(!next_subset(&mut [], 3));
+    }
+}
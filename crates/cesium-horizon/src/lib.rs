@@ -1,2 +1,3 @@
+pub mod assembler;
 pub mod model;
 pub mod network;
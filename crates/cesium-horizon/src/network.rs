@@ -6,6 +6,7 @@ use std::{
     time::Duration,
 };
 
+use cesium_crypto::erasure;
 use dashmap::DashMap;
 use tokio::{
     io::AsyncWriteExt,
@@ -14,7 +15,10 @@ use tokio::{
     time::sleep,
 };
 
-use crate::model::{Node, Packet};
+use crate::{
+    assembler::ShardAssembler,
+    model::{Node, Packet},
+};
 
 pub struct HorizonNetwork {
     nodes: HashMap<String, Node>,
@@ -139,7 +143,30 @@ impl HorizonNetwork {
         Ok(())
     }
 
-    pub async fn handle_packets(&self, node: Node, mut rx: mpsc::Receiver<Packet>) {
+    /// Broadcasts the payload as erasure coded shards, one packet each, so a
+    /// node that receives any `data_shards` of them can rebuild it
+    pub async fn broadcast_shards(
+        &self,
+        origin: String,
+        data: &[u8],
+        data_shards: u8,
+        total_shards: u8,
+    ) -> Result<(), Box<dyn std::error::Error>> {
+        for shard in erasure::encode(data, data_shards, total_shards)? {
+            self.broadcast(origin.clone(), shard.to_bytes()).await?;
+        }
+        Ok(())
+    }
+
+    /// Forwards the packets a node receives, payloads rebuilt from the
+    /// shards of `broadcast_shards` are sent to `payloads`
+    pub async fn handle_packets(
+        &self,
+        node: Node,
+        mut rx: mpsc::Receiver<Packet>,
+        payloads: mpsc::Sender<Vec<u8>>,
+    ) {
+        let mut assembler = ShardAssembler::new();
         while let Some(packet) = rx.recv().await {
             let packet_id = packet.id;
 
@@ -161,6 +188,11 @@ impl HorizonNetwork {
                 //     node.id, packet.id, packet.origin
                 // );
 
+                // Packets that are not shards are plain broadcasts
+                if let Ok(Some(payload)) = assembler.insert(&packet.data) {
+                    let _ = payloads.send(payload).await;
+                }
+
                 // Get neighbors to forward to
                 let neighbors = node.neighbors_ips.lock().await.clone();
 
@@ -234,6 +266,44 @@ mod tests {
         }
     }
 
+    #[tokio::test]
+    async fn test_handle_shards() {
+        let mut network = HorizonNetwork::new(3, "224.0.0.5", 12345);
+        let rx = network.add_node("node1".to_string(), 0).await;
+        let node = network.nodes.remove("node1").unwrap();
+        let tx = node.tx.clone();
+        let (payload_tx, mut payload_rx) = mpsc::channel(10);
+
+        // A plain packet and all but the lost shards arrive
+        let payload: Vec<u8> = (0..200u8).collect();
+        let shards = erasure::encode(&payload, 3, 5).unwrap();
+        let data = [
+            vec![1, 2, 3],
+            shards[1].to_bytes(),
+            shards[3].to_bytes(),
+            shards[4].to_bytes(),
+        ];
+        for data in data {
+            let packet = Packet {
+                id: rand::random(),
+                data,
+                retransmit_count: 0,
+                origin: "node2".to_string(),
+            };
+            tx.send(packet).await.unwrap();
+        }
+
+        let rebuilt = timeout(Duration::from_secs(3), async {
+            tokio::select! {
+                _ = network.handle_packets(node, rx, payload_tx) => None,
+                rebuilt = payload_rx.recv() => rebuilt,
+            }
+        })
+        .await
+        .unwrap();
+        assert_eq!(rebuilt, Some(payload));
+    }
+
     #[tokio::test]
     async fn test_broadcast_invalid_host() {
         let mut network = HorizonNetwork::new(3, "224.0.0.5", 12345);
@@ -1,8 +1,12 @@
 use std::rc::Rc;
 
-use cesium_crypto::mldsa::da::{DABytes, DisplayAddress, DA_BYTE_LEN};
+use cesium_crypto::{
+    mldsa::da::{DABytes, DisplayAddress, DA_BYTE_LEN},
+    scheme::{AnyPublicKey, AnySignature, Scheme},
+};
 use cesium_storage::{errors::StorageError, RocksDBStore};
 use selenide_runtime::errors::RuntimeError;
+use sha3::{Digest, Sha3_256};
 
 macro_rules! bounds_check {
     ($bytes:expr, $DA_BYTE_LEN:expr) => {
@@ -16,6 +20,8 @@ macro_rules! bounds_check {
 pub enum AccountError {
     ByteMismatch,
     AccountNotFound,
+    InvalidMultisigPolicy,
+    SupplyOverflow,
     StorageError(StorageError),
 }
 
@@ -24,6 +30,8 @@ impl std::fmt::Display for AccountError {
         match self {
             AccountError::ByteMismatch => write!(f, "Byte mismatch"),
             AccountError::AccountNotFound => write!(f, "Account not found"),
+            AccountError::InvalidMultisigPolicy => write!(f, "Invalid multisig policy"),
+            AccountError::SupplyOverflow => write!(f, "Total supply overflow"),
             AccountError::StorageError(e) => e.fmt(f),
         }
     }
@@ -40,6 +48,8 @@ impl std::error::Error for AccountError {
         match self {
             AccountError::ByteMismatch => None,
             AccountError::AccountNotFound => None,
+            AccountError::InvalidMultisigPolicy => None,
+            AccountError::SupplyOverflow => None,
             AccountError::StorageError(e) => Some(e),
         }
     }
@@ -237,6 +247,10 @@ impl ContractAccount {
     }
 }
 
+// Data accounts are kept under their own key, so the ID of another account
+// can not be read as one
+const DATA_KEY_PREFIX: &[u8] = b"data:";
+
 pub struct DataAccount {
     id: DABytes,
     owner: DABytes,
@@ -257,8 +271,15 @@ impl DataAccount {
         }
     }
 
+    fn storage_key(id: &DABytes) -> Vec<u8> {
+        [DATA_KEY_PREFIX, id].concat()
+    }
+
     pub async fn from_id(id: DABytes) -> Result<DataAccount, AccountError> {
-        match RocksDBStore::instance().async_get(id.to_vec()).await {
+        match RocksDBStore::instance()
+            .async_get(Self::storage_key(&id))
+            .await
+        {
             Ok(result) => match result {
                 Some(bytes) => Ok(DataAccount::from_bytes(&bytes)?),
                 None => Err(AccountError::AccountNotFound),
@@ -282,6 +303,18 @@ impl DataAccount {
         da.as_str()
     }
 
+    pub fn owner(&self) -> &DABytes {
+        &self.owner
+    }
+
+    pub fn updater(&self) -> &DABytes {
+        &self.updater
+    }
+
+    pub fn set_owner(&mut self, owner: DABytes) {
+        self.owner = owner;
+    }
+
     pub fn data(&self) -> &[u8] {
         &self.data
     }
@@ -289,7 +322,7 @@ impl DataAccount {
     pub async fn write(&self) -> Result<(), StorageError> {
         let bytes = self.to_bytes();
         RocksDBStore::instance()
-            .async_put(self.id.to_vec(), bytes)
+            .async_put(Self::storage_key(&self.id), bytes)
             .await
     }
 
@@ -333,6 +366,10 @@ impl DataAccount {
     }
 }
 
+// Currencies are kept under their own key, so the ID of another account can
+// not be read as a currency
+const CURRENCY_KEY_PREFIX: &[u8] = b"currency:";
+
 pub struct CurrencyAccount {
     id: DABytes,
     owner: DABytes,
@@ -343,6 +380,7 @@ pub struct CurrencyAccount {
     short_name: String,
     long_name_len: u32,
     long_name: String,
+    total_supply: u128,
 }
 
 impl CurrencyAccount {
@@ -365,19 +403,47 @@ impl CurrencyAccount {
             short_name,
             long_name_len: long_name.len() as u32,
             long_name,
+            total_supply: 0,
         }
     }
 
-    pub async fn from_id(_id: DABytes) -> Result<CurrencyAccount, AccountError> {
-        match RocksDBStore::instance().async_get(_id.to_vec()).await {
+    fn storage_key(id: &DABytes) -> Vec<u8> {
+        [CURRENCY_KEY_PREFIX, id].concat()
+    }
+
+    pub async fn from_id(id: DABytes) -> Result<CurrencyAccount, AccountError> {
+        match RocksDBStore::instance()
+            .async_get(Self::storage_key(&id))
+            .await
+        {
             Ok(result) => match result {
                 Some(bytes) => Ok(CurrencyAccount::from_bytes(&bytes)?),
-                None => Err(AccountError::AccountNotFound),
+                None => CurrencyAccount::from_legacy_id(id).await,
             },
             Err(e) => Err(AccountError::StorageError(e)),
         }
     }
 
+    // Currencies written before they had their own key are stored under the
+    // plain ID, the next write moves them. Other accounts share that key, so
+    // only a record that encodes back to the same bytes is taken.
+    async fn from_legacy_id(id: DABytes) -> Result<CurrencyAccount, AccountError> {
+        let bytes = match RocksDBStore::instance().async_get(id.to_vec()).await {
+            Ok(Some(bytes)) => bytes,
+            Ok(None) => return Err(AccountError::AccountNotFound),
+            Err(e) => return Err(AccountError::StorageError(e)),
+        };
+        let account =
+            CurrencyAccount::from_bytes(&bytes).map_err(|_| AccountError::AccountNotFound)?;
+
+        let canonical = account.to_bytes();
+        let without_supply = &canonical[..canonical.len() - 16];
+        if account.id != id || (bytes != canonical && bytes != without_supply) {
+            return Err(AccountError::AccountNotFound);
+        }
+        Ok(account)
+    }
+
     pub fn address(&self) -> String {
         let da = DisplayAddress::from_bytes(&self.id).unwrap();
         da.as_str()
@@ -407,10 +473,47 @@ impl CurrencyAccount {
         })
     }
 
+    pub fn owner(&self) -> &DABytes {
+        &self.owner
+    }
+
+    pub fn minter(&self) -> Option<&DABytes> {
+        self.minter.as_ref()
+    }
+
+    pub fn total_supply(&self) -> u128 {
+        self.total_supply
+    }
+
+    /// Adds newly minted units to the total supply
+    pub fn mint(&mut self, amount: u128) -> Result<(), AccountError> {
+        self.total_supply = self
+            .total_supply
+            .checked_add(amount)
+            .ok_or(AccountError::SupplyOverflow)?;
+        Ok(())
+    }
+
+    pub fn set_short_name(&mut self, short_name: String) {
+        self.short_name_len = short_name.len() as u32;
+        self.short_name = short_name;
+    }
+
+    pub fn set_long_name(&mut self, long_name: String) {
+        self.long_name_len = long_name.len() as u32;
+        self.long_name = long_name;
+    }
+
+    /// Gives up the mint authority for good, the supply is fixed afterwards
+    pub fn revoke_minter(&mut self) {
+        self.minter_len = 0;
+        self.minter = None;
+    }
+
     pub async fn write(&self) -> Result<(), StorageError> {
         let bytes = self.to_bytes();
         RocksDBStore::instance()
-            .async_put(self.id.to_vec(), bytes)
+            .async_put(Self::storage_key(&self.id), bytes)
             .await
     }
 
@@ -427,6 +530,7 @@ impl CurrencyAccount {
         bytes.extend_from_slice(self.short_name.as_bytes());
         bytes.extend_from_slice(&self.long_name_len.to_le_bytes());
         bytes.extend_from_slice(self.long_name.as_bytes());
+        bytes.extend_from_slice(&self.total_supply.to_le_bytes());
         bytes
     }
 
@@ -462,7 +566,8 @@ impl CurrencyAccount {
 
         bounds_check!(bytes, offset + short_name_len as usize);
         let short_name =
-            String::from_utf8(bytes[offset..offset + short_name_len as usize].to_vec()).unwrap();
+            String::from_utf8(bytes[offset..offset + short_name_len as usize].to_vec())
+                .map_err(|_| AccountError::ByteMismatch)?;
         offset = offset + short_name_len as usize;
 
         bounds_check!(bytes, offset + 4);
@@ -470,8 +575,17 @@ impl CurrencyAccount {
         offset = offset + 4;
 
         bounds_check!(bytes, offset + long_name_len as usize);
-        let long_name =
-            String::from_utf8(bytes[offset..offset + long_name_len as usize].to_vec()).unwrap();
+        let long_name = String::from_utf8(bytes[offset..offset + long_name_len as usize].to_vec())
+            .map_err(|_| AccountError::ByteMismatch)?;
+        offset += long_name_len as usize;
+
+        // Records written before the supply was tracked end here
+        let total_supply = if bytes.len() == offset {
+            0
+        } else {
+            bounds_check!(bytes, offset + 16);
+            u128::from_le_bytes(bytes[offset..offset + 16].try_into().unwrap())
+        };
 
         Ok(CurrencyAccount {
             id,
@@ -483,12 +597,826 @@ impl CurrencyAccount {
             short_name,
             long_name_len,
             long_name,
+            total_supply,
+        })
+    }
+}
+
+// Bundles are kept under their own key, so the ID of another account can not
+// be read as a bundle
+const NFT_BUNDLE_KEY_PREFIX: &[u8] = b"bundle:";
+
+/// A collection of NFTs, only its update authority can change it or mint
+/// NFTs into it
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct NFTBundleAccount {
+    id: DABytes,
+    update_authority: DABytes,
+    name_len: u32,
+    name: String,
+    uri_len: u32,
+    uri: String,
+    minted: u64,
+}
+
+impl NFTBundleAccount {
+    #[must_use]
+    pub fn new(
+        id: DABytes,
+        update_authority: DABytes,
+        name: String,
+        uri: String,
+    ) -> NFTBundleAccount {
+        NFTBundleAccount {
+            id,
+            update_authority,
+            name_len: name.len() as u32,
+            name,
+            uri_len: uri.len() as u32,
+            uri,
+            minted: 0,
+        }
+    }
+
+    fn storage_key(id: &DABytes) -> Vec<u8> {
+        [NFT_BUNDLE_KEY_PREFIX, id].concat()
+    }
+
+    pub async fn from_id(id: DABytes) -> Result<NFTBundleAccount, AccountError> {
+        match RocksDBStore::instance()
+            .async_get(Self::storage_key(&id))
+            .await
+        {
+            Ok(result) => match result {
+                Some(bytes) => Ok(NFTBundleAccount::from_bytes(&bytes)?),
+                None => Err(AccountError::AccountNotFound),
+            },
+            Err(e) => Err(AccountError::StorageError(e)),
+        }
+    }
+
+    pub fn address(&self) -> String {
+        let da = DisplayAddress::from_bytes(&self.id).unwrap();
+        da.as_str()
+    }
+
+    pub fn update_authority(&self) -> &DABytes {
+        &self.update_authority
+    }
+
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    pub fn uri(&self) -> &str {
+        &self.uri
+    }
+
+    /// Number of NFTs minted into the bundle
+    pub fn minted(&self) -> u64 {
+        self.minted
+    }
+
+    pub fn set_update_authority(&mut self, update_authority: DABytes) {
+        self.update_authority = update_authority;
+    }
+
+    pub fn set_name(&mut self, name: String) {
+        self.name_len = name.len() as u32;
+        self.name = name;
+    }
+
+    pub fn set_uri(&mut self, uri: String) {
+        self.uri_len = uri.len() as u32;
+        self.uri = uri;
+    }
+
+    pub fn record_mint(&mut self) {
+        self.minted += 1;
+    }
+
+    pub async fn write(&self) -> Result<(), StorageError> {
+        let bytes = self.to_bytes();
+        RocksDBStore::instance()
+            .async_put(Self::storage_key(&self.id), bytes)
+            .await
+    }
+
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let mut bytes = Vec::new();
+        bytes.extend_from_slice(&self.id);
+        bytes.extend_from_slice(&self.update_authority);
+        bytes.extend_from_slice(&self.name_len.to_le_bytes());
+        bytes.extend_from_slice(self.name.as_bytes());
+        bytes.extend_from_slice(&self.uri_len.to_le_bytes());
+        bytes.extend_from_slice(self.uri.as_bytes());
+        bytes.extend_from_slice(&self.minted.to_le_bytes());
+        bytes
+    }
+
+    pub fn from_bytes(bytes: &[u8]) -> Result<NFTBundleAccount, AccountError> {
+        bounds_check!(bytes, DA_BYTE_LEN);
+        let id: [u8; DA_BYTE_LEN] = bytes[0..DA_BYTE_LEN].try_into().unwrap();
+        let mut offset = DA_BYTE_LEN;
+
+        bounds_check!(bytes, offset + DA_BYTE_LEN);
+        let update_authority: [u8; DA_BYTE_LEN] =
+            bytes[offset..offset + DA_BYTE_LEN].try_into().unwrap();
+        offset += DA_BYTE_LEN;
+
+        bounds_check!(bytes, offset + 4);
+        let name_len = u32::from_le_bytes(bytes[offset..offset + 4].try_into().unwrap());
+        offset += 4;
+
+        bounds_check!(bytes, offset + name_len as usize);
+        let name = String::from_utf8(bytes[offset..offset + name_len as usize].to_vec())
+            .map_err(|_| AccountError::ByteMismatch)?;
+        offset += name_len as usize;
+
+        bounds_check!(bytes, offset + 4);
+        let uri_len = u32::from_le_bytes(bytes[offset..offset + 4].try_into().unwrap());
+        offset += 4;
+
+        bounds_check!(bytes, offset + uri_len as usize);
+        let uri = String::from_utf8(bytes[offset..offset + uri_len as usize].to_vec())
+            .map_err(|_| AccountError::ByteMismatch)?;
+        offset += uri_len as usize;
+
+        bounds_check!(bytes, offset + 8);
+        let minted = u64::from_le_bytes(bytes[offset..offset + 8].try_into().unwrap());
+
+        Ok(NFTBundleAccount {
+            id,
+            update_authority,
+            name_len,
+            name,
+            uri_len,
+            uri,
+            minted,
         })
     }
 }
 
+pub const MAX_MULTISIG_MEMBERS: usize = 16;
+const MULTISIG_DOMAIN: &[u8] = b"cesium-multisig";
+
+/// M-of-N account, its address is derived from the policy so anyone holding
+/// the member keys and threshold can check a transaction against it
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MultisigAccount {
+    id: DABytes,
+    threshold: u8,
+    members: Vec<AnyPublicKey>,
+}
+
+impl MultisigAccount {
+    /// Members are stored in canonical order, so the same set of keys always
+    /// gives the same address
+    pub fn new(
+        mut members: Vec<AnyPublicKey>,
+        threshold: u8,
+    ) -> Result<MultisigAccount, AccountError> {
+        members.sort_by_key(|member| member.to_bytes());
+        if members.is_empty()
+            || members.len() > MAX_MULTISIG_MEMBERS
+            || threshold == 0
+            || threshold as usize > members.len()
+            || members.windows(2).any(|pair| pair[0] == pair[1])
+        {
+            return Err(AccountError::InvalidMultisigPolicy);
+        }
+
+        Ok(MultisigAccount {
+            id: Self::derive_id(&members, threshold),
+            threshold,
+            members,
+        })
+    }
+
+    fn derive_id(members: &[AnyPublicKey], threshold: u8) -> DABytes {
+        let mut hasher = Sha3_256::new();
+        hasher.update(MULTISIG_DOMAIN);
+        hasher.update([threshold, members.len() as u8]);
+        for member in members {
+            hasher.update(member.to_bytes());
+        }
+        hasher.finalize().into()
+    }
+
+    pub async fn from_id(id: DABytes) -> Result<MultisigAccount, AccountError> {
+        match RocksDBStore::instance().async_get(id.to_vec()).await {
+            Ok(result) => match result {
+                Some(bytes) => Ok(MultisigAccount::from_bytes(&bytes)?),
+                None => Err(AccountError::AccountNotFound),
+            },
+            Err(e) => Err(AccountError::StorageError(e)),
+        }
+    }
+
+    pub fn id(&self) -> &DABytes {
+        &self.id
+    }
+
+    pub fn address(&self) -> String {
+        let da = DisplayAddress::from_bytes(&self.id).unwrap();
+        da.as_str()
+    }
+
+    pub fn threshold(&self) -> u8 {
+        self.threshold
+    }
+
+    pub fn members(&self) -> &[AnyPublicKey] {
+        &self.members
+    }
+
+    pub fn member_index(&self, pub_key: &AnyPublicKey) -> Option<u8> {
+        self.members
+            .iter()
+            .position(|member| member == pub_key)
+            .map(|index| index as u8)
+    }
+
+    /// Checks the signers without verifying the signatures, every index has
+    /// to belong to a distinct member and there have to be at least
+    /// `threshold` of them
+    pub fn meets_threshold(&self, signatures: &[(u8, AnySignature)]) -> bool {
+        let mut signed = vec![false; self.members.len()];
+        for (index, _) in signatures {
+            match signed.get_mut(*index as usize) {
+                Some(seen) if !*seen => *seen = true,
+                _ => return false,
+            }
+        }
+
+        signatures.len() >= self.threshold as usize
+    }
+
+    /// Signatures are paired with the index of the signing member. Every
+    /// signature has to be valid and at least `threshold` distinct members
+    /// have to sign.
+    pub fn verify(&self, msg: &[u8], signatures: &[(u8, AnySignature)]) -> bool {
+        self.meets_threshold(signatures)
+            && signatures.iter().all(|(index, sig)| {
+                self.members[*index as usize]
+                    .verify(msg, sig)
+                    .unwrap_or(false)
+            })
+    }
+
+    pub async fn write(&self) -> Result<(), StorageError> {
+        let bytes = self.to_bytes();
+        RocksDBStore::instance()
+            .async_put(self.id.to_vec(), bytes)
+            .await
+    }
+
+    /// Policy encoding without the id, which is implied by it
+    pub fn policy_bytes(&self) -> Vec<u8> {
+        let mut bytes = vec![self.threshold, self.members.len() as u8];
+        for member in &self.members {
+            bytes.extend_from_slice(&member.to_bytes());
+        }
+        bytes
+    }
+
+    /// Parses a policy and returns it with the number of bytes read
+    pub fn from_policy_bytes(bytes: &[u8]) -> Result<(MultisigAccount, usize), AccountError> {
+        bounds_check!(bytes, 2);
+        let threshold = bytes[0];
+        let member_count = bytes[1] as usize;
+        let mut offset = 2;
+
+        let mut members = Vec::with_capacity(member_count);
+        for _ in 0..member_count {
+            bounds_check!(bytes, offset + 1);
+            let scheme = Scheme::from_u8(bytes[offset]).ok_or(AccountError::ByteMismatch)?;
+            let len = 1 + scheme
+                .pub_byte_len()
+                .map_err(|_| AccountError::ByteMismatch)?;
+            bounds_check!(bytes, offset + len);
+            let member = AnyPublicKey::from_bytes(&bytes[offset..offset + len])
+                .map_err(|_| AccountError::ByteMismatch)?;
+            members.push(member);
+            offset += len;
+        }
+
+        // Only the canonical encoding is accepted
+        let account = MultisigAccount::new(members.clone(), threshold)?;
+        if account.members != members {
+            return Err(AccountError::InvalidMultisigPolicy);
+        }
+
+        Ok((account, offset))
+    }
+
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let mut bytes = Vec::new();
+        bytes.extend_from_slice(&self.id);
+        bytes.extend_from_slice(&self.policy_bytes());
+        bytes
+    }
+
+    pub fn from_bytes(bytes: &[u8]) -> Result<MultisigAccount, AccountError> {
+        bounds_check!(bytes, DA_BYTE_LEN);
+        let (account, _) = MultisigAccount::from_policy_bytes(&bytes[DA_BYTE_LEN..])?;
+        if bytes[0..DA_BYTE_LEN] != account.id {
+            return Err(AccountError::ByteMismatch);
+        }
+
+        Ok(account)
+    }
+}
+
+// Nonces are kept under their own key, a user and a multisig account are both
+// stored under their address
+const NONCE_KEY_PREFIX: &[u8] = b"nonce:";
+
+/// The next nonce a signer has to use, a signer without one starts at zero
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct NonceAccount {
+    id: DABytes,
+    nonce: u64,
+}
+
+impl NonceAccount {
+    #[must_use]
+    pub fn new(id: DABytes, nonce: u64) -> NonceAccount {
+        NonceAccount { id, nonce }
+    }
+
+    fn storage_key(id: &DABytes) -> Vec<u8> {
+        [NONCE_KEY_PREFIX, id].concat()
+    }
+
+    pub async fn from_id(id: DABytes) -> Result<NonceAccount, AccountError> {
+        match RocksDBStore::instance()
+            .async_get(Self::storage_key(&id))
+            .await
+        {
+            Ok(result) => match result {
+                Some(bytes) => Ok(NonceAccount::from_bytes(&bytes)?),
+                None => Ok(NonceAccount::new(id, 0)),
+            },
+            Err(e) => Err(AccountError::StorageError(e)),
+        }
+    }
+
+    pub fn address(&self) -> String {
+        let da = DisplayAddress::from_bytes(&self.id).unwrap();
+        da.as_str()
+    }
+
+    pub fn nonce(&self) -> u64 {
+        self.nonce
+    }
+
+    pub fn set_nonce(&mut self, nonce: u64) {
+        self.nonce = nonce;
+    }
+
+    pub async fn write(&self) -> Result<(), StorageError> {
+        let bytes = self.to_bytes();
+        RocksDBStore::instance()
+            .async_put(Self::storage_key(&self.id), bytes)
+            .await
+    }
+
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let mut bytes = Vec::new();
+        bytes.extend_from_slice(&self.id);
+        bytes.extend_from_slice(&self.nonce.to_le_bytes());
+        bytes
+    }
+
+    pub fn from_bytes(bytes: &[u8]) -> Result<NonceAccount, AccountError> {
+        bounds_check!(bytes, DA_BYTE_LEN + 8);
+        let id: [u8; DA_BYTE_LEN] = bytes[0..DA_BYTE_LEN].try_into().unwrap();
+        let nonce = u64::from_le_bytes(bytes[DA_BYTE_LEN..DA_BYTE_LEN + 8].try_into().unwrap());
+
+        Ok(NonceAccount { id, nonce })
+    }
+}
+
+// Balances are kept under their own key, one per owner and currency
+const BALANCE_KEY_PREFIX: &[u8] = b"balance:";
+
+/// The balance an owner holds of a currency, an owner without one holds
+/// nothing
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct BalanceAccount {
+    owner: DABytes,
+    currency: DABytes,
+    balance: u128,
+}
+
+impl BalanceAccount {
+    #[must_use]
+    pub fn new(owner: DABytes, currency: DABytes, balance: u128) -> BalanceAccount {
+        BalanceAccount {
+            owner,
+            currency,
+            balance,
+        }
+    }
+
+    fn storage_key(owner: &DABytes, currency: &DABytes) -> Vec<u8> {
+        [BALANCE_KEY_PREFIX, owner, currency].concat()
+    }
+
+    pub async fn from_id(
+        owner: DABytes,
+        currency: DABytes,
+    ) -> Result<BalanceAccount, AccountError> {
+        match RocksDBStore::instance()
+            .async_get(Self::storage_key(&owner, &currency))
+            .await
+        {
+            Ok(result) => match result {
+                Some(bytes) => Ok(BalanceAccount::from_bytes(&bytes)?),
+                None => Ok(BalanceAccount::new(owner, currency, 0)),
+            },
+            Err(e) => Err(AccountError::StorageError(e)),
+        }
+    }
+
+    pub fn balance(&self) -> u128 {
+        self.balance
+    }
+
+    pub fn set_balance(&mut self, balance: u128) {
+        self.balance = balance;
+    }
+
+    pub async fn write(&self) -> Result<(), StorageError> {
+        let bytes = self.to_bytes();
+        RocksDBStore::instance()
+            .async_put(Self::storage_key(&self.owner, &self.currency), bytes)
+            .await
+    }
+
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let mut bytes = Vec::new();
+        bytes.extend_from_slice(&self.owner);
+        bytes.extend_from_slice(&self.currency);
+        bytes.extend_from_slice(&self.balance.to_le_bytes());
+        bytes
+    }
+
+    pub fn from_bytes(bytes: &[u8]) -> Result<BalanceAccount, AccountError> {
+        bounds_check!(bytes, DA_BYTE_LEN * 2 + 16);
+        let owner: [u8; DA_BYTE_LEN] = bytes[0..DA_BYTE_LEN].try_into().unwrap();
+        let currency: [u8; DA_BYTE_LEN] = bytes[DA_BYTE_LEN..DA_BYTE_LEN * 2].try_into().unwrap();
+        let balance = u128::from_le_bytes(
+            bytes[DA_BYTE_LEN * 2..DA_BYTE_LEN * 2 + 16]
+                .try_into()
+                .unwrap(),
+        );
+
+        Ok(BalanceAccount {
+            owner,
+            currency,
+            balance,
+        })
+    }
+}
+
+// The NFTs of an owner are indexed under their own key so wallets can list
+// them without scanning every data account
+const NFT_INDEX_KEY_PREFIX: &[u8] = b"nfts:";
+
+/// Addresses of the NFTs held by an owner, an owner without NFTs has an empty
+/// index
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct NFTOwnerIndex {
+    owner: DABytes,
+    nfts: Vec<DABytes>,
+}
+
+impl NFTOwnerIndex {
+    #[must_use]
+    pub fn new(owner: DABytes, nfts: Vec<DABytes>) -> NFTOwnerIndex {
+        NFTOwnerIndex { owner, nfts }
+    }
+
+    fn storage_key(owner: &DABytes) -> Vec<u8> {
+        [NFT_INDEX_KEY_PREFIX, owner].concat()
+    }
+
+    pub async fn from_id(owner: DABytes) -> Result<NFTOwnerIndex, AccountError> {
+        match RocksDBStore::instance()
+            .async_get(Self::storage_key(&owner))
+            .await
+        {
+            Ok(result) => match result {
+                Some(bytes) => Ok(NFTOwnerIndex::from_bytes(&bytes)?),
+                None => Ok(NFTOwnerIndex::new(owner, Vec::new())),
+            },
+            Err(e) => Err(AccountError::StorageError(e)),
+        }
+    }
+
+    pub fn nfts(&self) -> &[DABytes] {
+        &self.nfts
+    }
+
+    pub fn insert(&mut self, nft: DABytes) {
+        if !self.nfts.contains(&nft) {
+            self.nfts.push(nft);
+        }
+    }
+
+    pub fn remove(&mut self, nft: &DABytes) {
+        self.nfts.retain(|id| id != nft);
+    }
+
+    pub async fn write(&self) -> Result<(), StorageError> {
+        let bytes = self.to_bytes();
+        RocksDBStore::instance()
+            .async_put(Self::storage_key(&self.owner), bytes)
+            .await
+    }
+
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let mut bytes = Vec::new();
+        bytes.extend_from_slice(&self.owner);
+        bytes.extend_from_slice(&(self.nfts.len() as u32).to_le_bytes());
+        for nft in &self.nfts {
+            bytes.extend_from_slice(nft);
+        }
+        bytes
+    }
+
+    pub fn from_bytes(bytes: &[u8]) -> Result<NFTOwnerIndex, AccountError> {
+        bounds_check!(bytes, DA_BYTE_LEN + 4);
+        let owner: [u8; DA_BYTE_LEN] = bytes[0..DA_BYTE_LEN].try_into().unwrap();
+        let count = u32::from_le_bytes(bytes[DA_BYTE_LEN..DA_BYTE_LEN + 4].try_into().unwrap());
+        let offset = DA_BYTE_LEN + 4;
+
+        bounds_check!(bytes, offset + count as usize * DA_BYTE_LEN);
+        let nfts = bytes[offset..offset + count as usize * DA_BYTE_LEN]
+            .chunks_exact(DA_BYTE_LEN)
+            .map(|id| id.try_into().unwrap())
+            .collect();
+
+        Ok(NFTOwnerIndex { owner, nfts })
+    }
+}
+
+// Accounts are written to serde formats through plain records, addresses are
+// bech32m in JSON and the length prefixes of the byte encodings are dropped
+#[cfg(feature = "serde")]
+mod serde_repr {
+    use std::rc::Rc;
+
+    use cesium_crypto::{
+        mldsa::da::{DABytes, DisplayAddress},
+        scheme::AnyPublicKey,
+        serializer::hex_bytes,
+    };
+    use serde::{de::Error, Deserialize, Deserializer, Serialize, Serializer};
+
+    use super::{
+        AccountError, ContractAccount, CurrencyAccount, DataAccount, MultisigAccount,
+        NFTBundleAccount, UserAccount,
+    };
+
+    fn display(id: &DABytes) -> DisplayAddress {
+        DisplayAddress::from_bytes(id).unwrap()
+    }
+
+    macro_rules! serde_via {
+        ($account:ty, $repr:ident) => {
+            impl Serialize for $account {
+                fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+                    $repr::from(self).serialize(serializer)
+                }
+            }
+
+            impl<'de> Deserialize<'de> for $account {
+                fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+                    <$account>::try_from($repr::deserialize(deserializer)?)
+                        .map_err(D::Error::custom)
+                }
+            }
+        };
+    }
+
+    #[derive(Serialize, Deserialize)]
+    struct UserAccountRepr {
+        address: DisplayAddress,
+        data_accounts: Vec<DisplayAddress>,
+    }
+
+    impl From<&UserAccount> for UserAccountRepr {
+        fn from(account: &UserAccount) -> Self {
+            UserAccountRepr {
+                address: display(&account.id),
+                data_accounts: account.data_account_ids.iter().map(display).collect(),
+            }
+        }
+    }
+
+    impl TryFrom<UserAccountRepr> for UserAccount {
+        type Error = AccountError;
+
+        fn try_from(repr: UserAccountRepr) -> Result<Self, AccountError> {
+            let data_account_ids = repr.data_accounts.iter().map(|da| *da.as_bytes()).collect();
+            Ok(UserAccount::new(
+                *repr.address.as_bytes(),
+                Rc::new(data_account_ids),
+            ))
+        }
+    }
+
+    serde_via!(UserAccount, UserAccountRepr);
+
+    #[derive(Serialize, Deserialize)]
+    struct ContractAccountRepr {
+        address: DisplayAddress,
+        state_account: Option<DisplayAddress>,
+        #[serde(with = "hex_bytes")]
+        program: Vec<u8>,
+    }
+
+    impl From<&ContractAccount> for ContractAccountRepr {
+        fn from(account: &ContractAccount) -> Self {
+            ContractAccountRepr {
+                address: display(&account.id),
+                state_account: account.state_account_id.as_ref().map(display),
+                program: account.program_binary.to_vec(),
+            }
+        }
+    }
+
+    impl TryFrom<ContractAccountRepr> for ContractAccount {
+        type Error = AccountError;
+
+        fn try_from(repr: ContractAccountRepr) -> Result<Self, AccountError> {
+            Ok(ContractAccount::new(
+                *repr.address.as_bytes(),
+                Rc::new(repr.program),
+                repr.state_account.map(|da| *da.as_bytes()),
+            ))
+        }
+    }
+
+    serde_via!(ContractAccount, ContractAccountRepr);
+
+    #[derive(Serialize, Deserialize)]
+    struct DataAccountRepr {
+        address: DisplayAddress,
+        owner: DisplayAddress,
+        updater: DisplayAddress,
+        #[serde(with = "hex_bytes")]
+        data: Vec<u8>,
+    }
+
+    impl From<&DataAccount> for DataAccountRepr {
+        fn from(account: &DataAccount) -> Self {
+            DataAccountRepr {
+                address: display(&account.id),
+                owner: display(&account.owner),
+                updater: display(&account.updater),
+                data: account.data.clone(),
+            }
+        }
+    }
+
+    impl TryFrom<DataAccountRepr> for DataAccount {
+        type Error = AccountError;
+
+        fn try_from(repr: DataAccountRepr) -> Result<Self, AccountError> {
+            Ok(DataAccount::new(
+                *repr.address.as_bytes(),
+                *repr.owner.as_bytes(),
+                *repr.updater.as_bytes(),
+                repr.data,
+            ))
+        }
+    }
+
+    serde_via!(DataAccount, DataAccountRepr);
+
+    #[derive(Serialize, Deserialize)]
+    struct CurrencyAccountRepr {
+        address: DisplayAddress,
+        owner: DisplayAddress,
+        short_name: String,
+        long_name: String,
+        decimals: u8,
+        minter: Option<DisplayAddress>,
+        total_supply: u128,
+    }
+
+    impl From<&CurrencyAccount> for CurrencyAccountRepr {
+        fn from(account: &CurrencyAccount) -> Self {
+            CurrencyAccountRepr {
+                address: display(&account.id),
+                owner: display(&account.owner),
+                short_name: account.short_name.clone(),
+                long_name: account.long_name.clone(),
+                decimals: account.decimals,
+                minter: account.minter.as_ref().map(display),
+                total_supply: account.total_supply,
+            }
+        }
+    }
+
+    impl TryFrom<CurrencyAccountRepr> for CurrencyAccount {
+        type Error = AccountError;
+
+        fn try_from(repr: CurrencyAccountRepr) -> Result<Self, AccountError> {
+            let mut account = CurrencyAccount::new(
+                *repr.address.as_bytes(),
+                *repr.owner.as_bytes(),
+                repr.short_name,
+                repr.long_name,
+                repr.decimals,
+                repr.minter.map(|da| *da.as_bytes()),
+            );
+            account.total_supply = repr.total_supply;
+            Ok(account)
+        }
+    }
+
+    serde_via!(CurrencyAccount, CurrencyAccountRepr);
+
+    #[derive(Serialize, Deserialize)]
+    struct NFTBundleAccountRepr {
+        address: DisplayAddress,
+        update_authority: DisplayAddress,
+        name: String,
+        uri: String,
+        minted: u64,
+    }
+
+    impl From<&NFTBundleAccount> for NFTBundleAccountRepr {
+        fn from(account: &NFTBundleAccount) -> Self {
+            NFTBundleAccountRepr {
+                address: display(&account.id),
+                update_authority: display(&account.update_authority),
+                name: account.name.clone(),
+                uri: account.uri.clone(),
+                minted: account.minted,
+            }
+        }
+    }
+
+    impl TryFrom<NFTBundleAccountRepr> for NFTBundleAccount {
+        type Error = AccountError;
+
+        fn try_from(repr: NFTBundleAccountRepr) -> Result<Self, AccountError> {
+            let mut account = NFTBundleAccount::new(
+                *repr.address.as_bytes(),
+                *repr.update_authority.as_bytes(),
+                repr.name,
+                repr.uri,
+            );
+            account.minted = repr.minted;
+            Ok(account)
+        }
+    }
+
+    serde_via!(NFTBundleAccount, NFTBundleAccountRepr);
+
+    #[derive(Serialize, Deserialize)]
+    struct MultisigAccountRepr {
+        address: DisplayAddress,
+        threshold: u8,
+        members: Vec<AnyPublicKey>,
+    }
+
+    impl From<&MultisigAccount> for MultisigAccountRepr {
+        fn from(account: &MultisigAccount) -> Self {
+            MultisigAccountRepr {
+                address: display(&account.id),
+                threshold: account.threshold,
+                members: account.members.clone(),
+            }
+        }
+    }
+
+    // The address is derived from the policy, so it has to match it
+    impl TryFrom<MultisigAccountRepr> for MultisigAccount {
+        type Error = AccountError;
+
+        fn try_from(repr: MultisigAccountRepr) -> Result<Self, AccountError> {
+            let account = MultisigAccount::new(repr.members, repr.threshold)?;
+            if account.id != *repr.address.as_bytes() {
+                return Err(AccountError::InvalidMultisigPolicy);
+            }
+            Ok(account)
+        }
+    }
+
+    serde_via!(MultisigAccount, MultisigAccountRepr);
+}
+
 #[cfg(test)]
 mod tests {
+    use cesium_crypto::{falcon, mldsa::keypair::SignerPair, scheme::Signer};
+
     use super::*;
 
     #[test]
@@ -549,7 +1477,7 @@ mod tests {
         let long_name = "Alpha Beta Charlie".to_string();
         let decimals = 2;
         let minter = Some(owner);
-        let currency_account = CurrencyAccount::new(
+        let mut currency_account = CurrencyAccount::new(
             id,
             owner,
             short_name.clone(),
@@ -557,6 +1485,7 @@ mod tests {
             decimals,
             minter,
         );
+        currency_account.mint(500).unwrap();
 
         let bytes = currency_account.to_bytes();
         let currency_account2 = CurrencyAccount::from_bytes(&bytes).unwrap();
@@ -575,6 +1504,14 @@ mod tests {
             currency_account2.minter_address(),
             currency_account.minter_address()
         );
+        assert_eq!(currency_account2.total_supply(), 500);
+
+        // The supply can not wrap around
+        assert!(matches!(
+            currency_account.mint(u128::MAX),
+            Err(AccountError::SupplyOverflow)
+        ));
+        assert_eq!(currency_account.total_supply(), 500);
     }
 
     #[tokio::test]
@@ -661,6 +1598,76 @@ mod tests {
         );
     }
 
+    #[tokio::test]
+    async fn test_currency_account_type() {
+        let id = *DisplayAddress::new().as_bytes();
+        let owner = *DisplayAddress::new().as_bytes();
+
+        // Another account under the same ID is not a currency
+        DataAccount::new(id, owner, owner, vec![0xFF; 64])
+            .write()
+            .await
+            .unwrap();
+        assert!(matches!(
+            CurrencyAccount::from_id(id).await,
+            Err(AccountError::AccountNotFound)
+        ));
+
+        // Names that are not UTF-8 are an error, not a panic
+        let currency = CurrencyAccount::new(id, owner, "ABC".into(), "Alpha".into(), 2, None);
+        let mut bytes = currency.to_bytes();
+        bytes[DA_BYTE_LEN * 2 + 1 + 4 + 4] = 0xFF;
+        assert!(matches!(
+            CurrencyAccount::from_bytes(&bytes),
+            Err(AccountError::ByteMismatch)
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_legacy_currency_account() {
+        let id = *DisplayAddress::new().as_bytes();
+        let owner = *DisplayAddress::new().as_bytes();
+        let currency =
+            CurrencyAccount::new(id, owner, "ABC".into(), "Alpha".into(), 2, Some(owner));
+
+        // The old record has no supply and sits under the plain ID
+        let bytes = currency.to_bytes();
+        let legacy = &bytes[..bytes.len() - 16];
+        let decoded = CurrencyAccount::from_bytes(legacy).unwrap();
+        assert_eq!(decoded.short_name(), "ABC");
+        assert_eq!(decoded.total_supply(), 0);
+        RocksDBStore::instance()
+            .async_put(id.to_vec(), legacy.to_vec())
+            .await
+            .unwrap();
+
+        let mut account = CurrencyAccount::from_id(id).await.unwrap();
+        assert_eq!(account.minter(), Some(&owner));
+        assert_eq!(account.total_supply(), 0);
+
+        // Written back it moves to its own key with the supply
+        account.mint(7).unwrap();
+        account.write().await.unwrap();
+        assert_eq!(
+            CurrencyAccount::from_id(id).await.unwrap().total_supply(),
+            7
+        );
+
+        // Records that are not exactly a currency are not taken as one
+        let other = *DisplayAddress::new().as_bytes();
+        let mut bytes =
+            CurrencyAccount::new(other, owner, "ABC".into(), "Alpha".into(), 2, None).to_bytes();
+        bytes.push(0);
+        RocksDBStore::instance()
+            .async_put(other.to_vec(), bytes)
+            .await
+            .unwrap();
+        assert!(matches!(
+            CurrencyAccount::from_id(other).await,
+            Err(AccountError::AccountNotFound)
+        ));
+    }
+
     #[tokio::test]
     async fn test_storage_account_not_found() {
         let id = *DisplayAddress::new().as_bytes();
@@ -677,4 +1684,231 @@ mod tests {
         let result = CurrencyAccount::from_id(id).await;
         assert!(matches!(result, Err(AccountError::AccountNotFound)));
     }
+
+    #[test]
+    fn test_multisig_account() {
+        let acc1 = SignerPair::create();
+        let acc2 = falcon::keypair::SignerPair::create();
+        let acc3 = SignerPair::create();
+        let members = vec![acc1.public_key(), acc2.public_key(), acc3.public_key()];
+        let account = MultisigAccount::new(members.clone(), 2).unwrap();
+
+        // The address does not depend on the order of the members
+        let reversed = MultisigAccount::new(members.iter().rev().cloned().collect(), 2).unwrap();
+        assert_eq!(account.address(), reversed.address());
+        let other = MultisigAccount::new(members.clone(), 3).unwrap();
+        assert_ne!(account.address(), other.address());
+
+        let bytes = account.to_bytes();
+        let account2 = MultisigAccount::from_bytes(&bytes).unwrap();
+        assert_eq!(account2, account);
+
+        let mut bad_id = bytes.clone();
+        bad_id[0] ^= 1;
+        assert!(MultisigAccount::from_bytes(&bad_id).is_err());
+        assert!(MultisigAccount::from_bytes(&bytes[..bytes.len() - 1]).is_err());
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn test_account_serde() {
+        let id = DisplayAddress::new();
+        let owner = DisplayAddress::new();
+        let currency_account = CurrencyAccount::new(
+            *id.as_bytes(),
+            *owner.as_bytes(),
+            "ABC".to_string(),
+            "Alpha Beta Charlie".to_string(),
+            2,
+            None,
+        );
+
+        // Addresses are written the way users see them
+        let json = serde_json::to_value(&currency_account).unwrap();
+        assert_eq!(json["address"], id.as_str());
+        assert_eq!(json["owner"], owner.as_str());
+        assert!(json["minter"].is_null());
+
+        let currency_account2: CurrencyAccount = serde_json::from_value(json).unwrap();
+        assert_eq!(currency_account2.to_bytes(), currency_account.to_bytes());
+
+        let members = vec![
+            SignerPair::create().public_key(),
+            falcon::keypair::SignerPair::create().public_key(),
+        ];
+        let account = MultisigAccount::new(members, 1).unwrap();
+        let mut json = serde_json::to_value(&account).unwrap();
+        let account2: MultisigAccount = serde_json::from_value(json.clone()).unwrap();
+        assert_eq!(account2, account);
+
+        // The address has to match the policy
+        json["threshold"] = 2.into();
+        assert!(serde_json::from_value::<MultisigAccount>(json).is_err());
+    }
+
+    #[tokio::test]
+    async fn test_nonce_account() {
+        let id = *DisplayAddress::new().as_bytes();
+
+        // A signer that never sent a transaction starts at zero
+        let mut account = NonceAccount::from_id(id).await.unwrap();
+        assert_eq!(account.nonce(), 0);
+
+        account.set_nonce(3);
+        account.write().await.unwrap();
+        assert_eq!(NonceAccount::from_id(id).await.unwrap(), account);
+
+        let bytes = account.to_bytes();
+        assert_eq!(NonceAccount::from_bytes(&bytes).unwrap(), account);
+        assert!(NonceAccount::from_bytes(&bytes[..bytes.len() - 1]).is_err());
+    }
+
+    #[tokio::test]
+    async fn test_balance_account() {
+        let owner = *DisplayAddress::new().as_bytes();
+        let currency = *DisplayAddress::new().as_bytes();
+
+        // Balances are per currency, an owner starts without any
+        let mut account = BalanceAccount::from_id(owner, currency).await.unwrap();
+        assert_eq!(account.balance(), 0);
+
+        account.set_balance(500);
+        account.write().await.unwrap();
+        assert_eq!(
+            BalanceAccount::from_id(owner, currency).await.unwrap(),
+            account
+        );
+        assert_eq!(
+            BalanceAccount::from_id(owner, *DisplayAddress::new().as_bytes())
+                .await
+                .unwrap()
+                .balance(),
+            0
+        );
+
+        let bytes = account.to_bytes();
+        assert_eq!(BalanceAccount::from_bytes(&bytes).unwrap(), account);
+        assert!(BalanceAccount::from_bytes(&bytes[..bytes.len() - 1]).is_err());
+    }
+
+    #[tokio::test]
+    async fn test_nft_bundle_account() {
+        let id = *DisplayAddress::new().as_bytes();
+        let authority = *DisplayAddress::new().as_bytes();
+        let mut bundle = NFTBundleAccount::new(
+            id,
+            authority,
+            "Bundle".to_string(),
+            "https://127.0.0.1".to_string(),
+        );
+        bundle.record_mint();
+
+        let bytes = bundle.to_bytes();
+        assert_eq!(NFTBundleAccount::from_bytes(&bytes).unwrap(), bundle);
+        assert!(NFTBundleAccount::from_bytes(&bytes[..bytes.len() - 1]).is_err());
+
+        bundle.write().await.unwrap();
+        let bundle2 = NFTBundleAccount::from_id(id).await.unwrap();
+        assert_eq!(bundle2.update_authority(), &authority);
+        assert_eq!(bundle2.name(), "Bundle");
+        assert_eq!(bundle2.minted(), 1);
+
+        // A bundle and a data account under the same ID are not confused
+        assert!(matches!(
+            DataAccount::from_id(id).await,
+            Err(AccountError::AccountNotFound)
+        ));
+        DataAccount::new(id, authority, authority, bundle.to_bytes())
+            .write()
+            .await
+            .unwrap();
+        assert_eq!(NFTBundleAccount::from_id(id).await.unwrap(), bundle2);
+        assert_eq!(
+            DataAccount::from_id(id).await.unwrap().data(),
+            bundle.to_bytes()
+        );
+    }
+
+    #[tokio::test]
+    async fn test_nft_owner_index() {
+        let owner = *DisplayAddress::new().as_bytes();
+        let nft1 = *DisplayAddress::new().as_bytes();
+        let nft2 = *DisplayAddress::new().as_bytes();
+
+        let mut index = NFTOwnerIndex::from_id(owner).await.unwrap();
+        assert!(index.nfts().is_empty());
+
+        index.insert(nft1);
+        index.insert(nft2);
+        index.insert(nft1);
+        index.remove(&nft2);
+        index.write().await.unwrap();
+
+        let index2 = NFTOwnerIndex::from_id(owner).await.unwrap();
+        assert_eq!(index2.nfts(), &[nft1]);
+        assert_eq!(
+            NFTOwnerIndex::from_bytes(&index2.to_bytes()).unwrap(),
+            index2
+        );
+    }
+
+    #[test]
+    fn test_multisig_policy() {
+        let acc1 = SignerPair::create();
+        let acc2 = SignerPair::create();
+        let members = vec![acc1.public_key(), acc2.public_key()];
+
+        for threshold in [0, 3] {
+            assert!(matches!(
+                MultisigAccount::new(members.clone(), threshold),
+                Err(AccountError::InvalidMultisigPolicy)
+            ));
+        }
+        assert!(MultisigAccount::new(vec![], 1).is_err());
+        assert!(MultisigAccount::new(vec![acc1.public_key(), acc1.public_key()], 1).is_err());
+    }
+
+    #[test]
+    fn test_multisig_verify() {
+        let acc1 = SignerPair::create();
+        let acc2 = SignerPair::create();
+        let acc3 = SignerPair::create();
+        let account = MultisigAccount::new(
+            vec![acc1.public_key(), acc2.public_key(), acc3.public_key()],
+            2,
+        )
+        .unwrap();
+
+        let msg = b"cesium";
+        let sign = |kp: &SignerPair| {
+            let index = account.member_index(&kp.public_key()).unwrap();
+            (index, kp.sign_any(msg))
+        };
+
+        assert!(!account.verify(msg, &[sign(&acc1)]));
+        assert!(!account.verify(msg, &[sign(&acc1), sign(&acc1)]));
+        assert!(account.verify(msg, &[sign(&acc1), sign(&acc3)]));
+        assert!(account.verify(msg, &[sign(&acc1), sign(&acc2), sign(&acc3)]));
+        assert!(!account.verify(b"other", &[sign(&acc1), sign(&acc3)]));
+
+        // A signature under the wrong index is rejected
+        let (index, sig) = sign(&acc1);
+        let wrong = ((index + 1) % 3, sig);
+        assert!(!account.verify(msg, &[wrong, sign(&acc2), sign(&acc3)]));
+        assert!(!account.verify(msg, &[(3, sign(&acc1).1), sign(&acc2)]));
+    }
+
+    #[tokio::test]
+    async fn test_storage_multisig_account() {
+        let members = vec![
+            SignerPair::create().public_key(),
+            SignerPair::create().public_key(),
+        ];
+        let account = MultisigAccount::new(members, 1).unwrap();
+
+        account.write().await.unwrap();
+
+        let account2 = MultisigAccount::from_id(*account.id()).await.unwrap();
+        assert_eq!(account2, account);
+    }
 }
@@ -1,5 +1,6 @@
 use cesium_crypto::mldsa::da::{DABytes, DA_BYTE_LEN};
 
+/// The data of a holder account that holds an NFT
 pub struct NFTHolderData {
     name_len: u32,
     name: String,
@@ -7,18 +8,54 @@ pub struct NFTHolderData {
     uri: String,
     creator_count: u32,
     creators: Vec<DABytes>,
+    bundle_len: u32,
+    bundle: Option<DABytes>,
 }
 
 macro_rules! bounds_check {
     ($bytes:expr, $DA_BYTE_LEN:expr) => {
         if $bytes.len() < $DA_BYTE_LEN {
-            // TODO: Return an error instead of panicking
-            panic!("Out of bounds NFT metadata bytes");
+            return Err("Out of bounds NFT metadata bytes".into());
         }
     };
 }
 
 impl NFTHolderData {
+    #[must_use]
+    pub fn new(
+        name: String,
+        uri: String,
+        creators: Vec<DABytes>,
+        bundle: Option<DABytes>,
+    ) -> NFTHolderData {
+        NFTHolderData {
+            name_len: name.len() as u32,
+            name,
+            url_len: uri.len() as u32,
+            uri,
+            creator_count: creators.len() as u32,
+            creators,
+            bundle_len: bundle.is_some() as u32,
+            bundle,
+        }
+    }
+
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    pub fn uri(&self) -> &str {
+        &self.uri
+    }
+
+    pub fn creators(&self) -> &[DABytes] {
+        &self.creators
+    }
+
+    pub fn bundle(&self) -> Option<&DABytes> {
+        self.bundle.as_ref()
+    }
+
     pub fn try_from_bytes(bytes: &[u8]) -> Result<Self, Box<dyn std::error::Error + Send + Sync>> {
         // Each field is prefixed with a usize length
         let mut offset = 0;
@@ -46,10 +83,22 @@ impl NFTHolderData {
         let mut creators = Vec::new();
         for _ in 0..creator_count {
             let pk: [u8; DA_BYTE_LEN] = bytes[offset..offset + DA_BYTE_LEN].try_into().unwrap();
-            offset = offset + DA_BYTE_LEN;
+            offset += DA_BYTE_LEN;
             creators.push(pk);
         }
 
+        // The bundle is optional, like the minter of a currency
+        bounds_check!(bytes, offset + 4);
+        let bundle_len = u32::from_le_bytes(bytes[offset..offset + 4].try_into()?);
+        offset += 4;
+
+        let bundle = if bundle_len > 0 {
+            bounds_check!(bytes, offset + DA_BYTE_LEN);
+            Some(bytes[offset..offset + DA_BYTE_LEN].try_into()?)
+        } else {
+            None
+        };
+
         Ok(Self {
             name_len,
             name,
@@ -57,6 +106,8 @@ impl NFTHolderData {
             uri,
             creator_count,
             creators,
+            bundle_len,
+            bundle,
         })
     }
 
@@ -70,6 +121,10 @@ impl NFTHolderData {
         for creator in &self.creators {
             bytes.extend_from_slice(creator);
         }
+        bytes.extend_from_slice(&self.bundle_len.to_le_bytes());
+        if let Some(bundle) = &self.bundle {
+            bytes.extend_from_slice(bundle);
+        }
         bytes
     }
 }
@@ -84,19 +139,12 @@ mod tests {
     fn test_nft_metadata() {
         let name = "Test NFT".to_string();
         let uri = "https://127.0.0.1".to_string();
-        let creator_count = 2;
         let creators = vec![
             *DisplayAddress::new().as_bytes(),
             *DisplayAddress::new().as_bytes(),
         ];
-        let metadata = NFTHolderData {
-            name_len: name.len() as u32,
-            name,
-            url_len: uri.len() as u32,
-            uri,
-            creator_count,
-            creators,
-        };
+        let bundle = Some(*DisplayAddress::new().as_bytes());
+        let metadata = NFTHolderData::new(name, uri, creators, bundle);
 
         let bytes = metadata.to_bytes();
         let metadata2 = NFTHolderData::try_from_bytes(&bytes).unwrap();
@@ -105,5 +153,9 @@ mod tests {
         assert_eq!(metadata.uri, metadata2.uri);
         assert_eq!(metadata.creator_count, metadata2.creator_count);
         assert_eq!(metadata.creators, metadata2.creators);
+        assert_eq!(metadata.bundle, metadata2.bundle);
+
+        // Cut short data is an error, not a panic
+        assert!(NFTHolderData::try_from_bytes(&bytes[..bytes.len() - 1]).is_err());
     }
 }
@@ -1,6 +1,8 @@
 use cesium_crypto::errors::CryptoError;
 use tokio::task::JoinError;
 
+use crate::accounts::AccountError;
+
 #[derive(Debug)]
 pub enum InstructionError {
     NoInstructions,
@@ -9,7 +11,19 @@ pub enum InstructionError {
     ByteMismatch,
     InsufficientFunds,
     OutOfGas,
+    CurrencyExists,
+    InvalidDecimals,
+    InvalidCurrencyName,
+    NotCurrencyOwner,
+    NotCurrencyMinter,
+    NFTBundleExists,
+    NFTExists,
+    InvalidNFTMetadata,
+    NotUpdateAuthority,
+    NotNFTOwner,
+    NotNFTCreator,
     CryptoError(CryptoError),
+    AccountError(AccountError),
     JoinError(JoinError),
 }
 
@@ -24,7 +38,23 @@ impl std::fmt::Display for InstructionError {
             InstructionError::ByteMismatch => write!(f, "Byte mismatch"),
             InstructionError::InsufficientFunds => write!(f, "Insufficient funds"),
             InstructionError::OutOfGas => write!(f, "Out of gas"),
+            InstructionError::CurrencyExists => write!(f, "Currency already exists"),
+            InstructionError::InvalidDecimals => write!(f, "Invalid currency decimals"),
+            InstructionError::InvalidCurrencyName => write!(f, "Invalid currency name"),
+            InstructionError::NotCurrencyOwner => write!(f, "Signer is not the currency owner"),
+            InstructionError::NotCurrencyMinter => {
+                write!(f, "Signer is not the currency mint authority")
+            }
+            InstructionError::NFTBundleExists => write!(f, "NFT bundle already exists"),
+            InstructionError::NFTExists => write!(f, "NFT already exists"),
+            InstructionError::InvalidNFTMetadata => write!(f, "Invalid NFT metadata"),
+            InstructionError::NotUpdateAuthority => {
+                write!(f, "Signer is not the bundle update authority")
+            }
+            InstructionError::NotNFTOwner => write!(f, "Signer is not the NFT owner"),
+            InstructionError::NotNFTCreator => write!(f, "Signer is not an NFT creator"),
             InstructionError::CryptoError(e) => e.fmt(f),
+            InstructionError::AccountError(e) => e.fmt(f),
             InstructionError::JoinError(e) => e.fmt(f),
         }
     }
@@ -36,6 +66,12 @@ impl From<CryptoError> for InstructionError {
     }
 }
 
+impl From<AccountError> for InstructionError {
+    fn from(e: AccountError) -> Self {
+        InstructionError::AccountError(e)
+    }
+}
+
 impl From<JoinError> for InstructionError {
     fn from(e: JoinError) -> Self {
         InstructionError::JoinError(e)
@@ -1,13 +1,21 @@
 use std::sync::Arc;
 
 use cesium_crypto::mldsa::da::{DABytes, DA_BYTE_LEN};
-use cesium_standards::{BASE_TX_FEE, NATIVE_TOKEN_BYTES};
+use cesium_standards::{
+    StandardToken, BASE_TX_FEE, MAX_DECIMALS, MIN_DECIMALS, NATIVE_TOKEN_BYTES,
+};
 use dashmap::DashMap;
 use tokio::sync::Mutex;
 
+use crate::{
+    accounts::{AccountError, BalanceAccount, CurrencyAccount, DataAccount, NFTBundleAccount},
+    data::nft::NFTHolderData,
+};
+
 use super::{
     errors::InstructionError,
     instruction::{Instruction, InstructionType},
+    pending::PendingState,
 };
 
 macro_rules! bounds_check {
@@ -20,29 +28,49 @@ macro_rules! bounds_check {
 
 // TODO: Make this function prettier and easier to maintain
 pub async fn execute_instructions(
-    _signer_da: &DABytes,
+    signer_da: &DABytes,
+    fee_payer_da: &DABytes,
     instructions: &[Instruction],
     provided_gas: u128,
+    priority_fee: u128,
 ) -> Result<(), InstructionError> {
     if provided_gas < BASE_TX_FEE {
         return Err(InstructionError::OutOfGas);
     }
-    // spender_balances holds the accurate balance of the spender with modifications made
-    // across instructions
-    let signer_balances: Arc<DashMap<[u8; DA_BYTE_LEN], u128>> = Arc::new(DashMap::new());
-    // TODO: Get the balances from the disk
-    let disk_balance: u128 = 0;
-    if disk_balance < provided_gas {
+    // The fee payer covers the provided gas and the priority fee upfront
+    let disk_balance = BalanceAccount::from_id(*fee_payer_da, *NATIVE_TOKEN_BYTES)
+        .await?
+        .balance();
+    let upfront = provided_gas
+        .checked_add(priority_fee)
+        .ok_or(InstructionError::InsufficientFunds)?;
+    if disk_balance < upfront {
         return Err(InstructionError::InsufficientFunds);
     }
+    let fee_payer_balances: Arc<DashMap<[u8; DA_BYTE_LEN], u128>> = Arc::new(DashMap::new());
+    fee_payer_balances.insert(*NATIVE_TOKEN_BYTES, disk_balance - priority_fee);
+
+    // spender_balances holds the accurate balance of the spender with modifications made
+    // across instructions, a signer paying its own fees shares the balances
+    let signer_balances: Arc<DashMap<[u8; DA_BYTE_LEN], u128>> = if signer_da == fee_payer_da {
+        Arc::clone(&fee_payer_balances)
+    } else {
+        Arc::new(DashMap::new())
+    };
 
     let mut used_gas: u128 = BASE_TX_FEE;
-    let can_run_concurrently =
-        preprocess_instructions(&signer_balances, &mut used_gas, provided_gas, instructions)?;
+    let can_run_concurrently = preprocess_instructions(
+        &fee_payer_balances,
+        &mut used_gas,
+        provided_gas,
+        instructions,
+    )?;
 
     // recipient_delta_balances holds the delta balances of the recipients
     let recipient_delta_balances: Arc<DashMap<[u8; DA_BYTE_LEN], u128>> = Arc::new(DashMap::new());
     let used_gas: Arc<Mutex<u128>> = Arc::new(Mutex::new(used_gas)); // Used gas is updated by contract calls
+                                                                     // Account changes are held back until every instruction succeeded
+    let state: Arc<Mutex<PendingState>> = Arc::new(Mutex::new(PendingState::new()));
 
     // The actual execution of the instructions
     if can_run_concurrently {
@@ -51,17 +79,21 @@ pub async fn execute_instructions(
 
         // Add tasks to the futures
         for instr in instructions {
+            let signer_da = *signer_da;
             let signer_balances = Arc::clone(&signer_balances);
             let recipient_delta_balances = Arc::clone(&recipient_delta_balances);
 
             // Add as task to futures
             let instr = instr.clone(); // TODO: Optimize this
             let used_gas = Arc::clone(&used_gas);
+            let state = Arc::clone(&state);
             futures.push(tokio::spawn(async move {
                 execute_instruction(
+                    &signer_da,
                     &signer_balances,
                     &recipient_delta_balances,
                     &used_gas,
+                    &state,
                     &instr,
                 )
                 .await?;
@@ -78,15 +110,24 @@ pub async fn execute_instructions(
         // Run the instructions sequentially
         for instr in instructions {
             execute_instruction(
+                signer_da,
                 &signer_balances,
                 &recipient_delta_balances,
                 &used_gas,
+                &state,
                 &instr,
             )
             .await?;
         }
     }
 
+    state
+        .lock()
+        .await
+        .commit()
+        .await
+        .map_err(AccountError::StorageError)?;
+
     // TODO: Update unconfirmed balances
     println!("{:?}", signer_balances);
     println!("{:?}", recipient_delta_balances);
@@ -94,22 +135,21 @@ pub async fn execute_instructions(
     Ok(())
 }
 
+// Instruction costs are gas, so they are taken from the fee payer
 fn preprocess_instructions(
-    signer_balances: &Arc<DashMap<[u8; DA_BYTE_LEN], u128>>,
+    fee_payer_balances: &Arc<DashMap<[u8; DA_BYTE_LEN], u128>>,
     used_gas: &mut u128,
     provided_gas: u128,
     instructions: &[Instruction],
 ) -> Result<bool, InstructionError> {
     let mut can_run_concurrently = true;
-    let mut has_currency_mint = false;
-    let mut has_currency_transfer = false;
 
     for instr in instructions {
         // We do the cost calculate here to avoid having to wrap used_gas
         // in a mutex
         let i_cost = instr.instruction_type.get_base_cost();
-        let balance = signer_balances.get(NATIVE_TOKEN_BYTES).unwrap();
-        if *balance < i_cost {
+        let balance = *fee_payer_balances.get(NATIVE_TOKEN_BYTES).unwrap();
+        if balance < i_cost {
             return Err(InstructionError::InsufficientFunds);
         } else if *used_gas + i_cost > provided_gas {
             return Err(InstructionError::OutOfGas);
@@ -117,20 +157,20 @@ fn preprocess_instructions(
 
         // Update the spender balances
         *used_gas += i_cost;
-        *signer_balances.get_mut(NATIVE_TOKEN_BYTES).unwrap() -= i_cost;
-
-        if instr.instruction_type == InstructionType::ContractCall {
-            can_run_concurrently = false;
-        } else if instr.instruction_type == InstructionType::CurrencyMint {
-            has_currency_mint = true;
-            if has_currency_transfer {
-                can_run_concurrently = false;
-            }
-        } else if instr.instruction_type == InstructionType::CurrencyTransfer {
-            has_currency_transfer = true;
-            if has_currency_mint {
-                can_run_concurrently = false;
-            }
+        *fee_payer_balances.get_mut(NATIVE_TOKEN_BYTES).unwrap() -= i_cost;
+
+        // Currency and NFT accounts are read and written back, so
+        // instructions touching them have to see each other's changes in order
+        match instr.instruction_type {
+            InstructionType::ContractCall
+            | InstructionType::CurrencyCreate
+            | InstructionType::CurrencyMint
+            | InstructionType::CurrencyUpdate
+            | InstructionType::NFTBundleCreate
+            | InstructionType::NFTBundleUpdate
+            | InstructionType::NFTMint
+            | InstructionType::NFTTransfer => can_run_concurrently = false,
+            _ => {}
         }
     }
 
@@ -138,9 +178,11 @@ fn preprocess_instructions(
 }
 
 async fn execute_instruction(
+    signer_da: &DABytes,
     signer_balances: &Arc<DashMap<[u8; DA_BYTE_LEN], u128>>,
     recipient_delta_balances: &Arc<DashMap<[u8; DA_BYTE_LEN], u128>>,
     used_gas: &Arc<Mutex<u128>>,
+    state: &Arc<Mutex<PendingState>>,
     instr: &Instruction,
 ) -> Result<(), InstructionError> {
     match instr.instruction_type {
@@ -151,28 +193,34 @@ async fn execute_instruction(
             contract_deploy().await?;
         }
         InstructionType::CurrencyTransfer => {
-            currency_transfer(&signer_balances, &recipient_delta_balances, &instr.data).await?;
+            currency_transfer(
+                signer_da,
+                &signer_balances,
+                &recipient_delta_balances,
+                &instr.data,
+            )
+            .await?;
         }
         InstructionType::CurrencyCreate => {
-            currency_create().await?;
+            currency_create(signer_da, &mut *state.lock().await, &instr.data).await?;
         }
         InstructionType::CurrencyMint => {
-            currency_mint().await?;
+            currency_mint(signer_da, &mut *state.lock().await, &instr.data).await?;
         }
         InstructionType::CurrencyUpdate => {
-            currency_update().await?;
+            currency_update(signer_da, &mut *state.lock().await, &instr.data).await?;
         }
         InstructionType::NFTBundleCreate => {
-            nft_bundle_create().await?;
+            nft_bundle_create(signer_da, &mut *state.lock().await, &instr.data).await?;
         }
         InstructionType::NFTBundleUpdate => {
-            nft_bundle_update().await?;
+            nft_bundle_update(signer_da, &mut *state.lock().await, &instr.data).await?;
         }
         InstructionType::NFTMint => {
-            nft_mint().await?;
+            nft_mint(signer_da, &mut *state.lock().await, &instr.data).await?;
         }
         InstructionType::NFTTransfer => {
-            nft_transfer().await?;
+            nft_transfer(signer_da, &mut *state.lock().await, &instr.data).await?;
         }
     }
 
@@ -197,6 +245,7 @@ async fn contract_deploy() -> Result<(), InstructionError> {
 }
 
 async fn currency_transfer(
+    signer_da: &DABytes,
     signer_balances: &Arc<DashMap<[u8; DA_BYTE_LEN], u128>>,
     recipient_delta_balances: &Arc<DashMap<[u8; DA_BYTE_LEN], u128>>,
     instr_data: &[u8],
@@ -222,8 +271,9 @@ async fn currency_transfer(
             return Err(InstructionError::InsufficientFunds);
         }
     } else {
-        // TODO: Get current balance from disk
-        let disk_balance = 0;
+        let disk_balance = BalanceAccount::from_id(*signer_da, currency)
+            .await?
+            .balance();
         if disk_balance < amount {
             return Err(InstructionError::InsufficientFunds);
         }
@@ -240,38 +290,342 @@ async fn currency_transfer(
     Ok(())
 }
 
-async fn currency_create() -> Result<(), InstructionError> {
-    todo!()
+// Strings are a u32 length followed by UTF-8, returns the string and the
+// bytes read
+fn read_string(bytes: &[u8]) -> Result<(String, usize), InstructionError> {
+    bounds_check!(bytes, 4);
+    let len = u32::from_le_bytes(bytes[0..4].try_into().unwrap()) as usize;
+    bounds_check!(bytes, 4 + len);
+    let string = String::from_utf8(bytes[4..4 + len].to_vec())
+        .map_err(|_| InstructionError::ByteMismatch)?;
+    Ok((string, 4 + len))
 }
 
-async fn currency_mint() -> Result<(), InstructionError> {
-    todo!()
+// Flags are a single byte, 0 or 1, any other value is not a canonical
+// encoding
+fn read_flag(bytes: &[u8]) -> Result<bool, InstructionError> {
+    bounds_check!(bytes, 1);
+    match bytes[0] {
+        0 => Ok(false),
+        1 => Ok(true),
+        _ => Err(InstructionError::ByteMismatch),
+    }
 }
 
-async fn currency_update() -> Result<(), InstructionError> {
-    todo!()
+async fn currency_create(
+    signer_da: &DABytes,
+    state: &mut PendingState,
+    instr_data: &[u8],
+) -> Result<(), InstructionError> {
+    let mut offset = 0;
+    // Get the address of the new currency
+    bounds_check!(instr_data, DA_BYTE_LEN);
+    let currency: [u8; DA_BYTE_LEN] = instr_data[offset..offset + DA_BYTE_LEN].try_into().unwrap();
+    offset += DA_BYTE_LEN;
+
+    // Get the decimals and whether a minter follows
+    bounds_check!(instr_data, offset + 1);
+    let decimals = instr_data[offset];
+    let has_minter = read_flag(&instr_data[offset + 1..])?;
+    offset += 2;
+    if !(MIN_DECIMALS..=MAX_DECIMALS).contains(&decimals) {
+        return Err(InstructionError::InvalidDecimals);
+    }
+
+    let minter = if has_minter {
+        bounds_check!(instr_data, offset + DA_BYTE_LEN);
+        let minter: [u8; DA_BYTE_LEN] =
+            instr_data[offset..offset + DA_BYTE_LEN].try_into().unwrap();
+        offset += DA_BYTE_LEN;
+        Some(minter)
+    } else {
+        None
+    };
+
+    let (short_name, len) = read_string(&instr_data[offset..])?;
+    offset += len;
+    let (long_name, _) = read_string(&instr_data[offset..])?;
+    if short_name.is_empty() || long_name.is_empty() {
+        return Err(InstructionError::InvalidCurrencyName);
+    }
+
+    // Standard tokens and existing currencies keep their address
+    if currency == *NATIVE_TOKEN_BYTES
+        || StandardToken::iter().any(|token| *token.address_bytes() == currency)
+    {
+        return Err(InstructionError::CurrencyExists);
+    }
+    match state.currency(currency).await {
+        Ok(_) => return Err(InstructionError::CurrencyExists),
+        Err(AccountError::AccountNotFound) => {}
+        Err(e) => return Err(e.into()),
+    }
+
+    // The signer owns the currency, the supply starts at zero
+    state.insert_currency(
+        currency,
+        CurrencyAccount::new(
+            currency, *signer_da, short_name, long_name, decimals, minter,
+        ),
+    );
+
+    Ok(())
 }
 
-async fn nft_bundle_create() -> Result<(), InstructionError> {
-    todo!()
+async fn currency_mint(
+    signer_da: &DABytes,
+    state: &mut PendingState,
+    instr_data: &[u8],
+) -> Result<(), InstructionError> {
+    let mut offset = 0;
+    // Get the currency that is minted
+    bounds_check!(instr_data, DA_BYTE_LEN);
+    let currency: [u8; DA_BYTE_LEN] = instr_data[offset..offset + DA_BYTE_LEN].try_into().unwrap();
+    offset += DA_BYTE_LEN;
+
+    // Get the amount which is the next 16 bytes
+    bounds_check!(instr_data, offset + 16);
+    let amount = u128::from_le_bytes(instr_data[offset..offset + 16].try_into().unwrap());
+    offset += 16;
+
+    // Get the recipient which is the next DA_BYTE_LEN bytes
+    bounds_check!(instr_data, offset + DA_BYTE_LEN);
+    let recipient: [u8; DA_BYTE_LEN] = instr_data[offset..offset + DA_BYTE_LEN].try_into().unwrap();
+
+    // Only the mint authority can mint, a currency without one is fixed
+    let account = state.currency(currency).await?;
+    if account.minter() != Some(signer_da) {
+        return Err(InstructionError::NotCurrencyMinter);
+    }
+    account.mint(amount)?;
+
+    // Minted units are saved with the supply, so both always agree
+    let balance = state.balance(recipient, currency).await?;
+    let credited = balance
+        .balance()
+        .checked_add(amount)
+        .ok_or(AccountError::SupplyOverflow)?;
+    balance.set_balance(credited);
+
+    Ok(())
 }
 
-async fn nft_bundle_update() -> Result<(), InstructionError> {
-    todo!()
+async fn currency_update(
+    signer_da: &DABytes,
+    state: &mut PendingState,
+    instr_data: &[u8],
+) -> Result<(), InstructionError> {
+    let mut offset = 0;
+    // Get the currency that is updated
+    bounds_check!(instr_data, DA_BYTE_LEN);
+    let currency: [u8; DA_BYTE_LEN] = instr_data[offset..offset + DA_BYTE_LEN].try_into().unwrap();
+    offset += DA_BYTE_LEN;
+
+    // Empty names are left as they are
+    let (short_name, len) = read_string(&instr_data[offset..])?;
+    offset += len;
+    let (long_name, len) = read_string(&instr_data[offset..])?;
+    offset += len;
+
+    let revoke_minter = read_flag(&instr_data[offset..])?;
+
+    let account = state.currency(currency).await?;
+    if account.owner() != signer_da {
+        return Err(InstructionError::NotCurrencyOwner);
+    }
+    if !short_name.is_empty() {
+        account.set_short_name(short_name);
+    }
+    if !long_name.is_empty() {
+        account.set_long_name(long_name);
+    }
+    if revoke_minter {
+        account.revoke_minter();
+    }
+
+    Ok(())
 }
 
-async fn nft_mint() -> Result<(), InstructionError> {
-    todo!()
+async fn nft_bundle_create(
+    signer_da: &DABytes,
+    state: &mut PendingState,
+    instr_data: &[u8],
+) -> Result<(), InstructionError> {
+    let mut offset = 0;
+    // Get the address of the new bundle
+    bounds_check!(instr_data, DA_BYTE_LEN);
+    let bundle: [u8; DA_BYTE_LEN] = instr_data[offset..offset + DA_BYTE_LEN].try_into().unwrap();
+    offset += DA_BYTE_LEN;
+
+    // Get the update authority which is the next DA_BYTE_LEN bytes
+    bounds_check!(instr_data, offset + DA_BYTE_LEN);
+    let update_authority: [u8; DA_BYTE_LEN] =
+        instr_data[offset..offset + DA_BYTE_LEN].try_into().unwrap();
+    offset += DA_BYTE_LEN;
+
+    let (name, len) = read_string(&instr_data[offset..])?;
+    offset += len;
+    let (uri, _) = read_string(&instr_data[offset..])?;
+    if name.is_empty() {
+        return Err(InstructionError::InvalidNFTMetadata);
+    }
+    // The creator of a bundle is its first update authority
+    if &update_authority != signer_da {
+        return Err(InstructionError::NotUpdateAuthority);
+    }
+
+    match state.bundle(bundle).await {
+        Ok(_) => return Err(InstructionError::NFTBundleExists),
+        Err(AccountError::AccountNotFound) => {}
+        Err(e) => return Err(e.into()),
+    }
+
+    state.insert_bundle(
+        bundle,
+        NFTBundleAccount::new(bundle, update_authority, name, uri),
+    );
+
+    Ok(())
 }
 
-async fn nft_transfer() -> Result<(), InstructionError> {
-    todo!()
+async fn nft_bundle_update(
+    signer_da: &DABytes,
+    state: &mut PendingState,
+    instr_data: &[u8],
+) -> Result<(), InstructionError> {
+    let mut offset = 0;
+    // Get the bundle that is updated
+    bounds_check!(instr_data, DA_BYTE_LEN);
+    let bundle: [u8; DA_BYTE_LEN] = instr_data[offset..offset + DA_BYTE_LEN].try_into().unwrap();
+    offset += DA_BYTE_LEN;
+
+    // Empty fields are left as they are
+    let (name, len) = read_string(&instr_data[offset..])?;
+    offset += len;
+    let (uri, len) = read_string(&instr_data[offset..])?;
+    offset += len;
+
+    let has_update_authority = read_flag(&instr_data[offset..])?;
+    offset += 1;
+    let update_authority = if has_update_authority {
+        bounds_check!(instr_data, offset + DA_BYTE_LEN);
+        let update_authority: [u8; DA_BYTE_LEN] =
+            instr_data[offset..offset + DA_BYTE_LEN].try_into().unwrap();
+        Some(update_authority)
+    } else {
+        None
+    };
+
+    let account = state.bundle(bundle).await?;
+    if account.update_authority() != signer_da {
+        return Err(InstructionError::NotUpdateAuthority);
+    }
+    if !name.is_empty() {
+        account.set_name(name);
+    }
+    if !uri.is_empty() {
+        account.set_uri(uri);
+    }
+    if let Some(update_authority) = update_authority {
+        account.set_update_authority(update_authority);
+    }
+
+    Ok(())
+}
+
+// Mints an NFT to the recipient, the signer has to be one of its creators so
+// no one is listed as a creator without signing. Without a bundle that is the
+// only check, anyone can mint their own NFT. Minting into a bundle is up to
+// its update authority.
+async fn nft_mint(
+    signer_da: &DABytes,
+    state: &mut PendingState,
+    instr_data: &[u8],
+) -> Result<(), InstructionError> {
+    let mut offset = 0;
+    // Get the address of the new NFT
+    bounds_check!(instr_data, DA_BYTE_LEN);
+    let nft: [u8; DA_BYTE_LEN] = instr_data[offset..offset + DA_BYTE_LEN].try_into().unwrap();
+    offset += DA_BYTE_LEN;
+
+    // Get the recipient which is the next DA_BYTE_LEN bytes
+    bounds_check!(instr_data, offset + DA_BYTE_LEN);
+    let recipient: [u8; DA_BYTE_LEN] = instr_data[offset..offset + DA_BYTE_LEN].try_into().unwrap();
+    offset += DA_BYTE_LEN;
+
+    // The rest is the data of the holder account
+    let metadata = NFTHolderData::try_from_bytes(&instr_data[offset..])
+        .map_err(|_| InstructionError::InvalidNFTMetadata)?;
+    if metadata.name().is_empty() {
+        return Err(InstructionError::InvalidNFTMetadata);
+    }
+    if !metadata.creators().contains(signer_da) {
+        return Err(InstructionError::NotNFTCreator);
+    }
+
+    match state.data_account(nft).await {
+        Ok(_) => return Err(InstructionError::NFTExists),
+        Err(AccountError::AccountNotFound) => {}
+        Err(e) => return Err(e.into()),
+    }
+
+    // Only the update authority of a bundle can mint into it
+    if let Some(bundle) = metadata.bundle() {
+        let bundle = state.bundle(*bundle).await?;
+        if bundle.update_authority() != signer_da {
+            return Err(InstructionError::NotUpdateAuthority);
+        }
+        bundle.record_mint();
+    }
+
+    // The minter stays the updater of the holder account
+    state.insert_data_account(
+        nft,
+        DataAccount::new(nft, recipient, *signer_da, metadata.to_bytes()),
+    );
+    state.nft_index(recipient).await?.insert(nft);
+
+    Ok(())
+}
+
+async fn nft_transfer(
+    signer_da: &DABytes,
+    state: &mut PendingState,
+    instr_data: &[u8],
+) -> Result<(), InstructionError> {
+    let mut offset = 0;
+    // Get the NFT that is transferred
+    bounds_check!(instr_data, DA_BYTE_LEN);
+    let nft: [u8; DA_BYTE_LEN] = instr_data[offset..offset + DA_BYTE_LEN].try_into().unwrap();
+    offset += DA_BYTE_LEN;
+
+    // Get the recipient which is the next DA_BYTE_LEN bytes
+    bounds_check!(instr_data, offset + DA_BYTE_LEN);
+    let recipient: [u8; DA_BYTE_LEN] = instr_data[offset..offset + DA_BYTE_LEN].try_into().unwrap();
+
+    // Only holder accounts of NFTs can be moved this way
+    let account = state.data_account(nft).await?;
+    NFTHolderData::try_from_bytes(account.data())
+        .map_err(|_| InstructionError::InvalidNFTMetadata)?;
+    if account.owner() != signer_da {
+        return Err(InstructionError::NotNFTOwner);
+    }
+    account.set_owner(recipient);
+
+    // The NFT leaves the sender's index first, so a transfer to oneself
+    // keeps it listed
+    state.nft_index(*signer_da).await?.remove(&nft);
+    state.nft_index(recipient).await?.insert(nft);
+
+    Ok(())
 }
 
 #[cfg(test)]
 mod tests {
     use cesium_crypto::mldsa::da::DisplayAddress;
 
+    use crate::accounts::NFTOwnerIndex;
+
     use super::*;
 
     #[tokio::test]
@@ -285,7 +639,7 @@ mod tests {
             &currency, 1000, &recipient,
         ));
 
-        let result = execute_instructions(&signer, &instructions, 1000).await;
+        let result = execute_instructions(&signer, &signer, &instructions, 1000, 0).await;
         assert!(result
             .err()
             .unwrap()
@@ -293,6 +647,389 @@ mod tests {
             .eq(&InstructionError::InsufficientFunds.to_string()));
     }
 
+    // Gives the account enough of the native token to pay for the tests
+    async fn fund(owner: &DABytes) {
+        BalanceAccount::new(*owner, *NATIVE_TOKEN_BYTES, 1_000_000)
+            .write()
+            .await
+            .unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_execute_balances() {
+        let signer = DisplayAddress::new_bytes();
+        let currency = DisplayAddress::new_bytes();
+        let recipient = DisplayAddress::new_bytes();
+        fund(&signer).await;
+        BalanceAccount::new(signer, currency, 10)
+            .write()
+            .await
+            .unwrap();
+
+        // Both the fee balance and the sent currency come from disk
+        let transfer = |amount| {
+            vec![Instruction::new_currency_transfer(
+                &currency, amount, &recipient,
+            )]
+        };
+        execute_instructions(&signer, &signer, &transfer(10), BASE_TX_FEE, 0)
+            .await
+            .unwrap();
+        assert!(matches!(
+            execute_instructions(&signer, &signer, &transfer(11), BASE_TX_FEE, 0).await,
+            Err(InstructionError::InsufficientFunds)
+        ));
+
+        // The fee payer pays the gas, not the signer
+        let unfunded = DisplayAddress::new_bytes();
+        assert!(matches!(
+            execute_instructions(&signer, &unfunded, &transfer(10), BASE_TX_FEE, 0).await,
+            Err(InstructionError::InsufficientFunds)
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_currency_lifecycle() {
+        let owner = DisplayAddress::new_bytes();
+        let minter = DisplayAddress::new_bytes();
+        let currency = DisplayAddress::new_bytes();
+        let recipient = DisplayAddress::new_bytes();
+        fund(&owner).await;
+        fund(&minter).await;
+
+        let create = vec![Instruction::new_currency_create(
+            &currency,
+            "ABC",
+            "Alpha Beta Charlie",
+            MIN_DECIMALS,
+            Some(&minter),
+        )];
+        execute_instructions(&owner, &owner, &create, BASE_TX_FEE, 0)
+            .await
+            .unwrap();
+        assert!(matches!(
+            execute_instructions(&owner, &owner, &create, BASE_TX_FEE, 0).await,
+            Err(InstructionError::CurrencyExists)
+        ));
+
+        // Only the minter mints, the supply and the recipient follow every mint
+        let mint = Instruction::new_currency_mint(&currency, 250, &recipient);
+        assert!(matches!(
+            execute_instructions(&owner, &owner, &[mint.clone()], BASE_TX_FEE, 0).await,
+            Err(InstructionError::NotCurrencyMinter)
+        ));
+        execute_instructions(
+            &minter,
+            &minter,
+            &[mint.clone(), mint.clone()],
+            BASE_TX_FEE,
+            0,
+        )
+        .await
+        .unwrap();
+        assert_eq!(
+            CurrencyAccount::from_id(currency)
+                .await
+                .unwrap()
+                .total_supply(),
+            500
+        );
+        assert_eq!(
+            BalanceAccount::from_id(recipient, currency)
+                .await
+                .unwrap()
+                .balance(),
+            500
+        );
+
+        // The owner renames and gives up the mint authority
+        let update = Instruction::new_currency_update(&currency, Some("ABD"), None, true);
+        assert!(matches!(
+            execute_instructions(&minter, &minter, &[update.clone()], BASE_TX_FEE, 0).await,
+            Err(InstructionError::NotCurrencyOwner)
+        ));
+        execute_instructions(&owner, &owner, &[update], BASE_TX_FEE, 0)
+            .await
+            .unwrap();
+        let account = CurrencyAccount::from_id(currency).await.unwrap();
+        assert_eq!(account.short_name(), "ABD");
+        assert_eq!(account.long_name(), "Alpha Beta Charlie");
+        assert!(account.minter().is_none());
+        assert!(matches!(
+            execute_instructions(&minter, &minter, &[mint], BASE_TX_FEE, 0).await,
+            Err(InstructionError::NotCurrencyMinter)
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_currency_staged_writes() {
+        let owner = DisplayAddress::new_bytes();
+        let currency = DisplayAddress::new_bytes();
+        let recipient = DisplayAddress::new_bytes();
+        fund(&owner).await;
+
+        // Later instructions see the currency created before them
+        let create = Instruction::new_currency_create(&currency, "ABC", "Alpha", 12, Some(&owner));
+        let mint = Instruction::new_currency_mint(&currency, 100, &recipient);
+        execute_instructions(&owner, &owner, &[create, mint], BASE_TX_FEE, 0)
+            .await
+            .unwrap();
+        assert_eq!(
+            CurrencyAccount::from_id(currency)
+                .await
+                .unwrap()
+                .total_supply(),
+            100
+        );
+
+        // A failing instruction undoes the ones before it
+        let other = DisplayAddress::new_bytes();
+        let create = Instruction::new_currency_create(&other, "ABC", "Alpha", 12, None);
+        let update = Instruction::new_currency_update(&currency, Some("XYZ"), None, false);
+        let mint = Instruction::new_currency_mint(&other, 100, &recipient);
+        assert!(matches!(
+            execute_instructions(&owner, &owner, &[update, create, mint], BASE_TX_FEE, 0).await,
+            Err(InstructionError::NotCurrencyMinter)
+        ));
+        assert!(matches!(
+            CurrencyAccount::from_id(other).await,
+            Err(AccountError::AccountNotFound)
+        ));
+        assert_eq!(
+            CurrencyAccount::from_id(currency)
+                .await
+                .unwrap()
+                .short_name(),
+            "ABC"
+        );
+    }
+
+    #[tokio::test]
+    async fn test_currency_create_limits() {
+        let owner = DisplayAddress::new_bytes();
+
+        for decimals in [MIN_DECIMALS - 1, MAX_DECIMALS + 1] {
+            let create = Instruction::new_currency_create(
+                &DisplayAddress::new_bytes(),
+                "ABC",
+                "Alpha",
+                decimals,
+                None,
+            );
+            assert!(matches!(
+                currency_create(&owner, &mut PendingState::new(), &create.data).await,
+                Err(InstructionError::InvalidDecimals)
+            ));
+        }
+
+        let create =
+            Instruction::new_currency_create(&DisplayAddress::new_bytes(), "", "Alpha", 12, None);
+        assert!(matches!(
+            currency_create(&owner, &mut PendingState::new(), &create.data).await,
+            Err(InstructionError::InvalidCurrencyName)
+        ));
+
+        // Flags are 0 or 1, nothing else
+        let mut create = Instruction::new_currency_create(
+            &DisplayAddress::new_bytes(),
+            "ABC",
+            "Alpha",
+            12,
+            None,
+        );
+        create.data[DA_BYTE_LEN + 1] = 2;
+        assert!(matches!(
+            currency_create(&owner, &mut PendingState::new(), &create.data).await,
+            Err(InstructionError::ByteMismatch)
+        ));
+        let mut update =
+            Instruction::new_currency_update(&DisplayAddress::new_bytes(), None, None, true);
+        *update.data.last_mut().unwrap() = 2;
+        assert!(matches!(
+            currency_update(&owner, &mut PendingState::new(), &update.data).await,
+            Err(InstructionError::ByteMismatch)
+        ));
+
+        let create =
+            Instruction::new_currency_create(NATIVE_TOKEN_BYTES, "CSM", "Cesium", 12, None);
+        assert!(matches!(
+            currency_create(&owner, &mut PendingState::new(), &create.data).await,
+            Err(InstructionError::CurrencyExists)
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_nft_lifecycle() {
+        let authority = DisplayAddress::new_bytes();
+        let holder = DisplayAddress::new_bytes();
+        let recipient = DisplayAddress::new_bytes();
+        let bundle = DisplayAddress::new_bytes();
+        let nft = DisplayAddress::new_bytes();
+        fund(&authority).await;
+        fund(&holder).await;
+
+        // A bundle is created by its update authority
+        let create =
+            Instruction::new_nft_bundle_create(&bundle, &authority, "Bundle", "https://127.0.0.1");
+        assert!(matches!(
+            execute_instructions(&holder, &holder, &[create.clone()], BASE_TX_FEE, 0).await,
+            Err(InstructionError::NotUpdateAuthority)
+        ));
+        execute_instructions(&authority, &authority, &[create.clone()], BASE_TX_FEE, 0)
+            .await
+            .unwrap();
+        assert!(matches!(
+            execute_instructions(&authority, &authority, &[create], BASE_TX_FEE, 0).await,
+            Err(InstructionError::NFTBundleExists)
+        ));
+
+        let update = Instruction::new_nft_bundle_update(&bundle, Some("Renamed"), None, None);
+        let mut bad_flag = update.clone();
+        *bad_flag.data.last_mut().unwrap() = 2;
+        assert!(matches!(
+            execute_instructions(&authority, &authority, &[bad_flag], BASE_TX_FEE, 0).await,
+            Err(InstructionError::ByteMismatch)
+        ));
+        assert!(matches!(
+            execute_instructions(&holder, &holder, &[update.clone()], BASE_TX_FEE, 0).await,
+            Err(InstructionError::NotUpdateAuthority)
+        ));
+        execute_instructions(&authority, &authority, &[update], BASE_TX_FEE, 0)
+            .await
+            .unwrap();
+
+        // Minting into the bundle is up to its update authority, even for
+        // another creator
+        let metadata = NFTHolderData::new(
+            "Test NFT".to_string(),
+            "https://127.0.0.1/1".to_string(),
+            vec![authority, holder],
+            Some(bundle),
+        );
+        let mint = Instruction::new_nft_mint(&nft, &holder, &metadata);
+        assert!(matches!(
+            execute_instructions(&holder, &holder, &[mint.clone()], BASE_TX_FEE, 0).await,
+            Err(InstructionError::NotUpdateAuthority)
+        ));
+        execute_instructions(&authority, &authority, &[mint.clone()], BASE_TX_FEE, 0)
+            .await
+            .unwrap();
+        assert!(matches!(
+            execute_instructions(&authority, &authority, &[mint], BASE_TX_FEE, 0).await,
+            Err(InstructionError::NFTExists)
+        ));
+
+        let account = NFTBundleAccount::from_id(bundle).await.unwrap();
+        assert_eq!(account.name(), "Renamed");
+        assert_eq!(account.uri(), "https://127.0.0.1");
+        assert_eq!(account.minted(), 1);
+        assert_eq!(NFTOwnerIndex::from_id(holder).await.unwrap().nfts(), &[nft]);
+
+        // Only the holder moves the NFT, the indexes follow it
+        let transfer = Instruction::new_nft_transfer(&nft, &recipient);
+        assert!(matches!(
+            execute_instructions(&authority, &authority, &[transfer.clone()], BASE_TX_FEE, 0).await,
+            Err(InstructionError::NotNFTOwner)
+        ));
+        execute_instructions(&holder, &holder, &[transfer], BASE_TX_FEE, 0)
+            .await
+            .unwrap();
+        assert!(NFTOwnerIndex::from_id(holder)
+            .await
+            .unwrap()
+            .nfts()
+            .is_empty());
+        assert_eq!(
+            NFTOwnerIndex::from_id(recipient).await.unwrap().nfts(),
+            &[nft]
+        );
+        let account = DataAccount::from_id(nft).await.unwrap();
+        assert_eq!(account.owner(), &recipient);
+        let metadata = NFTHolderData::try_from_bytes(account.data()).unwrap();
+        assert_eq!(metadata.name(), "Test NFT");
+        assert_eq!(metadata.bundle(), Some(&bundle));
+    }
+
+    #[tokio::test]
+    async fn test_nft_mint_creators() {
+        let creator = DisplayAddress::new_bytes();
+        let other = DisplayAddress::new_bytes();
+        let nft = DisplayAddress::new_bytes();
+        fund(&creator).await;
+        fund(&other).await;
+
+        // Without a bundle anyone mints, but only as one of the creators
+        let metadata = NFTHolderData::new(
+            "Test NFT".to_string(),
+            "https://127.0.0.1/1".to_string(),
+            vec![creator],
+            None,
+        );
+        let mint = Instruction::new_nft_mint(&nft, &other, &metadata);
+        assert!(matches!(
+            execute_instructions(&other, &other, &[mint.clone()], BASE_TX_FEE, 0).await,
+            Err(InstructionError::NotNFTCreator)
+        ));
+        execute_instructions(&creator, &creator, &[mint], BASE_TX_FEE, 0)
+            .await
+            .unwrap();
+        let account = DataAccount::from_id(nft).await.unwrap();
+        assert_eq!(account.owner(), &other);
+        assert_eq!(account.updater(), &creator);
+    }
+
+    #[tokio::test]
+    async fn test_nft_staged_writes() {
+        let authority = DisplayAddress::new_bytes();
+        let holder = DisplayAddress::new_bytes();
+        let bundle = DisplayAddress::new_bytes();
+        let nft = DisplayAddress::new_bytes();
+        fund(&authority).await;
+
+        // Minted and moved on within one transaction
+        let metadata = NFTHolderData::new(
+            "Test NFT".to_string(),
+            "https://127.0.0.1/1".to_string(),
+            vec![authority],
+            Some(bundle),
+        );
+        let instructions = [
+            Instruction::new_nft_bundle_create(&bundle, &authority, "Bundle", "https://127.0.0.1"),
+            Instruction::new_nft_mint(&nft, &authority, &metadata),
+            Instruction::new_nft_transfer(&nft, &holder),
+        ];
+        execute_instructions(&authority, &authority, &instructions, BASE_TX_FEE, 0)
+            .await
+            .unwrap();
+        assert!(NFTOwnerIndex::from_id(authority)
+            .await
+            .unwrap()
+            .nfts()
+            .is_empty());
+        assert_eq!(NFTOwnerIndex::from_id(holder).await.unwrap().nfts(), &[nft]);
+
+        // A failing transfer undoes the mint before it
+        let other = DisplayAddress::new_bytes();
+        let instructions = [
+            Instruction::new_nft_mint(&other, &authority, &metadata),
+            Instruction::new_nft_transfer(&nft, &authority),
+        ];
+        assert!(matches!(
+            execute_instructions(&authority, &authority, &instructions, BASE_TX_FEE, 0).await,
+            Err(InstructionError::NotNFTOwner)
+        ));
+        assert!(matches!(
+            DataAccount::from_id(other).await,
+            Err(AccountError::AccountNotFound)
+        ));
+        assert_eq!(NFTBundleAccount::from_id(bundle).await.unwrap().minted(), 1);
+        assert!(NFTOwnerIndex::from_id(authority)
+            .await
+            .unwrap()
+            .nfts()
+            .is_empty());
+    }
+
     #[tokio::test]
     async fn test_execute_insufficient_gas_upfront() {
         let signer = DisplayAddress::new_bytes();
@@ -303,7 +1040,7 @@ mod tests {
         instructions.push(Instruction::new_currency_transfer(&currency, 0, &recipient));
 
         assert!(BASE_TX_FEE > 0);
-        let result = execute_instructions(&signer, &instructions, 0).await;
+        let result = execute_instructions(&signer, &signer, &instructions, 0, 0).await;
         assert!(result
             .err()
             .unwrap()
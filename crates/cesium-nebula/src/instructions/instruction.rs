@@ -1,6 +1,6 @@
 use cesium_crypto::mldsa::da::DA_BYTE_LEN;
 
-use crate::instructions::errors::InstructionError;
+use crate::{data::nft::NFTHolderData, instructions::errors::InstructionError};
 
 macro_rules! bounds_check {
     ($bytes:expr, $pub_byte_len:expr) => {
@@ -11,6 +11,7 @@ macro_rules! bounds_check {
 }
 
 #[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum InstructionType {
     // Smart contracts
     ContractCall,
@@ -76,6 +77,11 @@ impl InstructionType {
 }
 
 #[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(from = "InstructionRepr", into = "InstructionRepr")
+)]
 pub struct Instruction {
     pub instruction_type: InstructionType,
     pub data_length: u32,
@@ -104,6 +110,130 @@ impl Instruction {
         Instruction::new(InstructionType::CurrencyTransfer, data)
     }
 
+    // Names are written with their u32 length in front, the minter is
+    // flagged by a leading byte
+    pub fn new_currency_create(
+        currency: &[u8; DA_BYTE_LEN],
+        short_name: &str,
+        long_name: &str,
+        decimals: u8,
+        minter: Option<&[u8; DA_BYTE_LEN]>,
+    ) -> Instruction {
+        let mut data = Vec::new();
+        data.extend(currency.to_vec());
+        data.push(decimals);
+        match minter {
+            Some(minter) => {
+                data.push(1);
+                data.extend(minter.to_vec());
+            }
+            None => data.push(0),
+        }
+        data.extend((short_name.len() as u32).to_le_bytes());
+        data.extend(short_name.as_bytes());
+        data.extend((long_name.len() as u32).to_le_bytes());
+        data.extend(long_name.as_bytes());
+
+        Instruction::new(InstructionType::CurrencyCreate, data)
+    }
+
+    pub fn new_currency_mint(
+        currency: &[u8; DA_BYTE_LEN],
+        amount: u128,
+        recipient: &[u8; DA_BYTE_LEN],
+    ) -> Instruction {
+        let mut data = Vec::new();
+        data.extend(currency.to_vec());
+        data.extend(amount.to_le_bytes());
+        data.extend(recipient.to_vec());
+
+        Instruction::new(InstructionType::CurrencyMint, data)
+    }
+
+    // A name left as `None` is written with length 0 and keeps its value
+    pub fn new_currency_update(
+        currency: &[u8; DA_BYTE_LEN],
+        short_name: Option<&str>,
+        long_name: Option<&str>,
+        revoke_minter: bool,
+    ) -> Instruction {
+        let mut data = Vec::new();
+        data.extend(currency.to_vec());
+        for name in [short_name, long_name] {
+            let name = name.unwrap_or_default();
+            data.extend((name.len() as u32).to_le_bytes());
+            data.extend(name.as_bytes());
+        }
+        data.push(revoke_minter as u8);
+
+        Instruction::new(InstructionType::CurrencyUpdate, data)
+    }
+
+    // The update authority has to be the signer of the transaction
+    pub fn new_nft_bundle_create(
+        bundle: &[u8; DA_BYTE_LEN],
+        update_authority: &[u8; DA_BYTE_LEN],
+        name: &str,
+        uri: &str,
+    ) -> Instruction {
+        let mut data = Vec::new();
+        data.extend(bundle.to_vec());
+        data.extend(update_authority.to_vec());
+        for field in [name, uri] {
+            data.extend((field.len() as u32).to_le_bytes());
+            data.extend(field.as_bytes());
+        }
+
+        Instruction::new(InstructionType::NFTBundleCreate, data)
+    }
+
+    // Like the currency update, a `None` field keeps its value
+    pub fn new_nft_bundle_update(
+        bundle: &[u8; DA_BYTE_LEN],
+        name: Option<&str>,
+        uri: Option<&str>,
+        update_authority: Option<&[u8; DA_BYTE_LEN]>,
+    ) -> Instruction {
+        let mut data = Vec::new();
+        data.extend(bundle.to_vec());
+        for field in [name, uri] {
+            let field = field.unwrap_or_default();
+            data.extend((field.len() as u32).to_le_bytes());
+            data.extend(field.as_bytes());
+        }
+        match update_authority {
+            Some(update_authority) => {
+                data.push(1);
+                data.extend(update_authority.to_vec());
+            }
+            None => data.push(0),
+        }
+
+        Instruction::new(InstructionType::NFTBundleUpdate, data)
+    }
+
+    // The NFT is followed by its recipient and the data of the holder account
+    pub fn new_nft_mint(
+        nft: &[u8; DA_BYTE_LEN],
+        recipient: &[u8; DA_BYTE_LEN],
+        metadata: &NFTHolderData,
+    ) -> Instruction {
+        let mut data = Vec::new();
+        data.extend(nft.to_vec());
+        data.extend(recipient.to_vec());
+        data.extend(metadata.to_bytes());
+
+        Instruction::new(InstructionType::NFTMint, data)
+    }
+
+    pub fn new_nft_transfer(nft: &[u8; DA_BYTE_LEN], recipient: &[u8; DA_BYTE_LEN]) -> Instruction {
+        let mut data = Vec::new();
+        data.extend(nft.to_vec());
+        data.extend(recipient.to_vec());
+
+        Instruction::new(InstructionType::NFTTransfer, data)
+    }
+
     pub fn to_bytes(&self) -> Vec<u8> {
         let mut bytes = Vec::new();
 
@@ -148,6 +278,32 @@ impl Instruction {
     }
 }
 
+// The data length is implied by the data in serde formats
+#[cfg(feature = "serde")]
+#[derive(serde::Serialize, serde::Deserialize)]
+struct InstructionRepr {
+    instruction_type: InstructionType,
+    #[serde(with = "cesium_crypto::serializer::hex_bytes")]
+    data: Vec<u8>,
+}
+
+#[cfg(feature = "serde")]
+impl From<InstructionRepr> for Instruction {
+    fn from(repr: InstructionRepr) -> Self {
+        Instruction::new(repr.instruction_type, repr.data)
+    }
+}
+
+#[cfg(feature = "serde")]
+impl From<Instruction> for InstructionRepr {
+    fn from(instruction: Instruction) -> Self {
+        InstructionRepr {
+            instruction_type: instruction.instruction_type,
+            data: instruction.data,
+        }
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -1,3 +1,4 @@
 pub mod errors;
 pub mod execute;
 pub mod instruction;
+pub mod pending;
@@ -0,0 +1,102 @@
+use std::collections::{hash_map::Entry, HashMap};
+
+use cesium_crypto::mldsa::da::DABytes;
+use cesium_storage::errors::StorageError;
+
+use crate::accounts::{
+    AccountError, BalanceAccount, CurrencyAccount, DataAccount, NFTBundleAccount, NFTOwnerIndex,
+};
+
+/// Accounts read and changed by the instructions of a transaction. Changes
+/// are only kept here, they are written once every instruction succeeded so
+/// a failing transaction leaves no trace.
+#[derive(Default)]
+pub struct PendingState {
+    currencies: HashMap<DABytes, CurrencyAccount>,
+    bundles: HashMap<DABytes, NFTBundleAccount>,
+    data_accounts: HashMap<DABytes, DataAccount>,
+    nft_indexes: HashMap<DABytes, NFTOwnerIndex>,
+    // Keyed by owner and currency
+    balances: HashMap<(DABytes, DABytes), BalanceAccount>,
+}
+
+impl PendingState {
+    #[must_use]
+    pub fn new() -> PendingState {
+        PendingState::default()
+    }
+
+    /// The currency as changed so far, loaded from disk on first use
+    pub async fn currency(&mut self, id: DABytes) -> Result<&mut CurrencyAccount, AccountError> {
+        match self.currencies.entry(id) {
+            Entry::Occupied(entry) => Ok(entry.into_mut()),
+            Entry::Vacant(entry) => Ok(entry.insert(CurrencyAccount::from_id(id).await?)),
+        }
+    }
+
+    pub fn insert_currency(&mut self, id: DABytes, account: CurrencyAccount) {
+        self.currencies.insert(id, account);
+    }
+
+    pub async fn bundle(&mut self, id: DABytes) -> Result<&mut NFTBundleAccount, AccountError> {
+        match self.bundles.entry(id) {
+            Entry::Occupied(entry) => Ok(entry.into_mut()),
+            Entry::Vacant(entry) => Ok(entry.insert(NFTBundleAccount::from_id(id).await?)),
+        }
+    }
+
+    pub fn insert_bundle(&mut self, id: DABytes, account: NFTBundleAccount) {
+        self.bundles.insert(id, account);
+    }
+
+    pub async fn data_account(&mut self, id: DABytes) -> Result<&mut DataAccount, AccountError> {
+        match self.data_accounts.entry(id) {
+            Entry::Occupied(entry) => Ok(entry.into_mut()),
+            Entry::Vacant(entry) => Ok(entry.insert(DataAccount::from_id(id).await?)),
+        }
+    }
+
+    pub fn insert_data_account(&mut self, id: DABytes, account: DataAccount) {
+        self.data_accounts.insert(id, account);
+    }
+
+    pub async fn nft_index(&mut self, owner: DABytes) -> Result<&mut NFTOwnerIndex, AccountError> {
+        match self.nft_indexes.entry(owner) {
+            Entry::Occupied(entry) => Ok(entry.into_mut()),
+            Entry::Vacant(entry) => Ok(entry.insert(NFTOwnerIndex::from_id(owner).await?)),
+        }
+    }
+
+    pub async fn balance(
+        &mut self,
+        owner: DABytes,
+        currency: DABytes,
+    ) -> Result<&mut BalanceAccount, AccountError> {
+        match self.balances.entry((owner, currency)) {
+            Entry::Occupied(entry) => Ok(entry.into_mut()),
+            Entry::Vacant(entry) => {
+                Ok(entry.insert(BalanceAccount::from_id(owner, currency).await?))
+            }
+        }
+    }
+
+    /// Write every changed account
+    pub async fn commit(&self) -> Result<(), StorageError> {
+        for account in self.currencies.values() {
+            account.write().await?;
+        }
+        for account in self.bundles.values() {
+            account.write().await?;
+        }
+        for account in self.data_accounts.values() {
+            account.write().await?;
+        }
+        for index in self.nft_indexes.values() {
+            index.write().await?;
+        }
+        for account in self.balances.values() {
+            account.write().await?;
+        }
+        Ok(())
+    }
+}
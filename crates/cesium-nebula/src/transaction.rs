@@ -1,26 +1,63 @@
-use std::time::SystemTime;
+use std::{ops::Range, time::SystemTime};
 
 use cesium_crypto::{
+    address,
+    batch::BatchVerifier,
     errors::CryptoError,
-    mldsa::{
-        da::DisplayAddress,
-        keypair::{SignerPair, VerifierPair, ViewOperations},
-        PublicKeyBytes, PUB_BYTE_LEN,
-    },
+    mldsa::da::{DABytes, DA_BYTE_LEN},
+    scheme::{AnyPublicKey, AnySignature, Scheme, Signer},
 };
+use cesium_standards::CHAIN_ID;
+use cesium_storage::{errors::StorageError, RocksDBStore};
+use sha3::{Digest, Sha3_256};
 
-use crate::instructions::{
-    errors::InstructionError,
-    instruction::{Instruction, InstructionType},
+use crate::{
+    accounts::{AccountError, MultisigAccount},
+    instructions::{
+        errors::InstructionError,
+        execute::execute_instructions,
+        instruction::{Instruction, InstructionType},
+    },
 };
 
+// The wire format starts with its version, `from_bytes` picks the layout
+// from it
+pub const TX_VERSION_1: u8 = 1;
+pub const TX_VERSION: u8 = TX_VERSION_1;
+
+// Written in place of the signer's scheme tag when the transaction is signed
+// by a multisig account
+pub const MULTISIG_TAG: u8 = 0xFF;
+
+// A transaction ID is the SHA3-256 of the signing payload and the signer, the
+// signatures are left out so every validator derives the same ID for the same
+// transaction. IDs are written as hex.
+pub const TX_ID_BYTE_LEN: usize = 32;
+const TX_ID_DOMAIN: &[u8] = b"cesium-tx";
+// Stored transactions are kept apart from accounts, which are keyed by address
+const TX_KEY_PREFIX: &[u8] = b"tx:";
+
+pub type TxIdBytes = [u8; TX_ID_BYTE_LEN];
+
+// Seconds a new transaction stays valid unless the sender picks its own expiry
+pub const DEFAULT_TX_LIFETIME: u64 = 600;
+
 #[derive(Debug)]
 pub enum TransactionError {
     NotSigned,
     InstructionError(InstructionError),
     ByteMismatch,
+    UnsupportedVersion(u8),
+    WrongNetwork(u32),
     CryptoError(CryptoError),
     InvalidSignature,
+    NotMultisigMember,
+    FeePayerMismatch,
+    FeePayerNotSigned,
+    AccountError(AccountError),
+    InvalidId,
+    TransactionNotFound,
+    StorageError(StorageError),
 }
 
 impl std::fmt::Display for TransactionError {
@@ -29,8 +66,29 @@ impl std::fmt::Display for TransactionError {
             TransactionError::NotSigned => write!(f, "Transaction is not signed"),
             TransactionError::InstructionError(e) => e.fmt(f),
             TransactionError::ByteMismatch => write!(f, "Byte mismatch"),
+            TransactionError::UnsupportedVersion(version) => {
+                write!(f, "Unsupported transaction version {}", version)
+            }
+            TransactionError::WrongNetwork(chain_id) => write!(
+                f,
+                "Transaction is for chain {} but this is chain {}",
+                chain_id, CHAIN_ID
+            ),
             TransactionError::CryptoError(e) => e.fmt(f),
             TransactionError::InvalidSignature => write!(f, "Invalid signature"),
+            TransactionError::NotMultisigMember => {
+                write!(f, "Signer is not a member of the multisig account")
+            }
+            TransactionError::FeePayerMismatch => {
+                write!(f, "Key is not the fee payer of the transaction")
+            }
+            TransactionError::FeePayerNotSigned => {
+                write!(f, "Transaction is not signed by its fee payer")
+            }
+            TransactionError::AccountError(e) => e.fmt(f),
+            TransactionError::InvalidId => write!(f, "Invalid transaction id"),
+            TransactionError::TransactionNotFound => write!(f, "Transaction not found"),
+            TransactionError::StorageError(e) => e.fmt(f),
         }
     }
 }
@@ -47,6 +105,50 @@ impl From<CryptoError> for TransactionError {
     }
 }
 
+impl From<AccountError> for TransactionError {
+    fn from(e: AccountError) -> Self {
+        TransactionError::AccountError(e)
+    }
+}
+
+impl From<StorageError> for TransactionError {
+    fn from(e: StorageError) -> Self {
+        TransactionError::StorageError(e)
+    }
+}
+
+/// Parses a hex transaction ID
+pub fn parse_id(id: &str) -> Result<TxIdBytes, TransactionError> {
+    hex::decode(id)
+        .ok()
+        .and_then(|bytes| bytes.try_into().ok())
+        .ok_or(TransactionError::InvalidId)
+}
+
+fn storage_key(id: &TxIdBytes) -> Vec<u8> {
+    [TX_KEY_PREFIX, id].concat()
+}
+
+/// Accounts are keyed by the address bytes of their key, SPHINCS+ addresses
+/// are 48 bytes long and are keyed by their SHA3-256 instead
+pub fn account_id(key: &AnyPublicKey) -> Option<DABytes> {
+    let da_len = key.scheme().da_byte_len().ok()?;
+    let da = address::decode(&key.display_address().ok()?, da_len).ok()?;
+    if da_len == DA_BYTE_LEN {
+        return da.try_into().ok();
+    }
+    Some(Sha3_256::digest(&da).into())
+}
+
+/// Current unix time in seconds, the clock transactions are created and
+/// expired against
+pub fn unix_now() -> u64 {
+    SystemTime::now()
+        .duration_since(SystemTime::UNIX_EPOCH)
+        .unwrap()
+        .as_secs()
+}
+
 macro_rules! bounds_check {
     ($bytes:expr, $pub_byte_len:expr) => {
         if $bytes.len() < $pub_byte_len {
@@ -55,31 +157,67 @@ macro_rules! bounds_check {
     };
 }
 
-#[derive(Debug)]
+/// Optional fields are flagged with a single 0 or 1 byte, any other value is
+/// not a canonical encoding
+fn read_flag(bytes: &[u8], offset: usize) -> Result<bool, TransactionError> {
+    bounds_check!(bytes, offset + 1);
+    match bytes[offset] {
+        0 => Ok(false),
+        1 => Ok(true),
+        _ => Err(TransactionError::ByteMismatch),
+    }
+}
+
+/// Signatures collected for a multisig account, each paired with the index
+/// of the signing member
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct MultisigDigest {
+    pub account: MultisigAccount,
+    pub signatures: Vec<(u8, AnySignature)>,
+}
+
+#[derive(Debug, Clone)]
 pub struct Transaction {
+    // The network the transaction is valid on
+    pub chain_id: u32,
     pub instructions_count: u64,
     pub instructions: Vec<Instruction>,
     pub reserved_gas: u128,
     pub priority_fee: u128,
     pub timestamp: u64,
-    pub signer: Option<PublicKeyBytes>,
-    pub digest: Option<Vec<u8>>,
+    // Counts the transactions of the signer, each nonce is accepted once and
+    // in order
+    pub nonce: u64,
+    // Unix time in seconds after which the transaction is no longer accepted
+    pub expires_at: u64,
+    // Pays the gas and priority fee when set, the instructions still act with
+    // the authority of the signer. The payer is part of what both parties sign.
+    pub fee_payer: Option<AnyPublicKey>,
+    pub fee_payer_digest: Option<AnySignature>,
+    pub signer: Option<AnyPublicKey>,
+    pub digest: Option<AnySignature>,
+    pub multisig: Option<MultisigDigest>,
 }
 
 impl Transaction {
     #[must_use]
     pub fn new(reserved_gas: u128, priority_fee: u128) -> Transaction {
+        let timestamp = unix_now();
         Transaction {
+            chain_id: CHAIN_ID,
             instructions_count: 0,
             instructions: Vec::new(),
             reserved_gas,
             priority_fee,
-            timestamp: SystemTime::now()
-                .duration_since(SystemTime::UNIX_EPOCH)
-                .unwrap()
-                .as_secs(),
+            timestamp,
+            nonce: 0,
+            expires_at: timestamp + DEFAULT_TX_LIFETIME,
+            fee_payer: None,
+            fee_payer_digest: None,
             signer: None,
             digest: None,
+            multisig: None,
         }
     }
 
@@ -94,25 +232,88 @@ impl Transaction {
             return Err(TransactionError::NotSigned);
         }
 
-        let mut bytes = Vec::new();
-        bytes.extend(self.instructions_count.to_le_bytes());
-        for instruction in &self.instructions {
-            bytes.extend(instruction.to_bytes());
+        let mut bytes = self.to_sig_bytes();
+        if self.fee_payer.is_some() {
+            // The fee payer may sign after the signer, so its signature is
+            // flagged
+            match &self.fee_payer_digest {
+                Some(sig) => {
+                    bytes.push(1);
+                    bytes.extend(sig.as_bytes());
+                }
+                None => bytes.push(0),
+            }
+        }
+        if let Some(multisig) = &self.multisig {
+            // The policy is followed by the signatures, each signature uses the
+            // scheme of its member
+            bytes.push(MULTISIG_TAG);
+            bytes.extend(multisig.account.policy_bytes());
+            bytes.push(multisig.signatures.len() as u8);
+            for (index, sig) in &multisig.signatures {
+                bytes.push(*index);
+                bytes.extend(sig.as_bytes());
+            }
+        } else {
+            // The signer is prefixed with its scheme, the digest uses the same scheme
+            bytes.extend(self.signer.as_ref().unwrap().to_bytes());
+            bytes.extend(self.digest.as_ref().unwrap().as_bytes());
         }
-        bytes.extend(self.reserved_gas.to_le_bytes());
-        bytes.extend(self.priority_fee.to_le_bytes());
-        bytes.extend(self.timestamp.to_le_bytes());
-        bytes.extend(self.signer.unwrap());
-        bytes.extend(self.digest.as_ref().unwrap());
         Ok(bytes)
     }
 
+    /// A transaction is valid up to and including the second it expires at
+    pub fn is_expired_at(&self, now: u64) -> bool {
+        now > self.expires_at
+    }
+
+    pub fn is_expired(&self) -> bool {
+        self.is_expired_at(unix_now())
+    }
+
     pub fn signer_da(&self) -> Option<String> {
-        self.signer.map(|s| DisplayAddress::from_pk(&s).as_str())
+        if let Some(multisig) = &self.multisig {
+            return Some(multisig.account.address());
+        }
+        self.signer.as_ref().and_then(|s| s.display_address().ok())
+    }
+
+    /// Account ID of the signer, nonces are tracked per account
+    pub fn signer_id(&self) -> Option<DABytes> {
+        if let Some(multisig) = &self.multisig {
+            return Some(*multisig.account.id());
+        }
+        account_id(self.signer.as_ref()?)
+    }
+
+    /// Account ID of the account that pays for the transaction, this is
+    /// the signer unless a fee payer is set
+    pub fn fee_payer_id(&self) -> Option<DABytes> {
+        match &self.fee_payer {
+            Some(fee_payer) => account_id(fee_payer),
+            None => self.signer_id(),
+        }
     }
 
     pub fn from_bytes(bytes: &[u8]) -> Result<Self, TransactionError> {
+        bounds_check!(bytes, 1);
+        match bytes[0] {
+            TX_VERSION_1 => Self::from_bytes_v1(&bytes[1..]),
+            version => Err(TransactionError::UnsupportedVersion(version)),
+        }
+    }
+
+    fn from_bytes_v1(bytes: &[u8]) -> Result<Self, TransactionError> {
         let mut offset = 0;
+
+        // A transaction for another network is refused before anything else
+        bounds_check!(bytes, offset + 4);
+        let chain_id = u32::from_le_bytes(bytes[offset..offset + 4].try_into().unwrap());
+        offset += 4;
+        if chain_id != CHAIN_ID {
+            return Err(TransactionError::WrongNetwork(chain_id));
+        }
+
         bounds_check!(bytes, offset + 8);
         let instructions_count = u64::from_le_bytes(bytes[offset..offset + 8].try_into().unwrap());
         offset += 8;
@@ -134,6 +335,7 @@ impl Transaction {
                 u32::from_le_bytes(bytes[offset..offset + 4].try_into().unwrap()) as usize;
             offset += 4;
             // Now we know how much instruction data we need to read for this instruction
+            bounds_check!(bytes, offset + data_len);
             let data = bytes[offset..offset + data_len].to_vec();
             offset += data_len;
 
@@ -160,40 +362,181 @@ impl Transaction {
         let timestamp = u64::from_le_bytes(bytes[offset..offset + 8].try_into().unwrap());
         offset += 8;
 
-        let mut signer: Option<PublicKeyBytes> = None;
+        // We now read the nonce which is a u64 (8 bytes)
+        bounds_check!(bytes, offset + 8);
+        let nonce = u64::from_le_bytes(bytes[offset..offset + 8].try_into().unwrap());
+        offset += 8;
+
+        // We now read the expiry which is a u64 (8 bytes)
+        bounds_check!(bytes, offset + 8);
+        let expires_at = u64::from_le_bytes(bytes[offset..offset + 8].try_into().unwrap());
+        offset += 8;
+
+        // The fee payer is flagged, it is followed by its flagged signature
+        let has_fee_payer = read_flag(bytes, offset)?;
+        offset += 1;
+
+        let mut fee_payer = None;
+        let mut fee_payer_digest = None;
+        if has_fee_payer {
+            bounds_check!(bytes, offset + 1);
+            let scheme = Scheme::from_u8(bytes[offset])
+                .ok_or(CryptoError::UnsupportedScheme(bytes[offset]))?;
+            let pub_byte_len = scheme.pub_byte_len()?;
+            let sig_byte_len = scheme.sig_byte_len()?;
+            offset += 1;
+
+            bounds_check!(bytes, offset + pub_byte_len);
+            fee_payer = Some(AnyPublicKey::new(
+                scheme,
+                &bytes[offset..offset + pub_byte_len],
+            )?);
+            offset += pub_byte_len;
+
+            let has_digest = read_flag(bytes, offset)?;
+            offset += 1;
+            if has_digest {
+                bounds_check!(bytes, offset + sig_byte_len);
+                fee_payer_digest = Some(AnySignature::new(
+                    scheme,
+                    &bytes[offset..offset + sig_byte_len],
+                )?);
+                offset += sig_byte_len;
+            }
+        }
+
+        let mut signer = None;
         let mut digest = None;
+        let mut multisig = None;
+
+        // Check if the transaction is signed, the first byte is the scheme of the signer
+        // or the multisig tag
+        if bytes.len() > offset && bytes[offset] == MULTISIG_TAG {
+            offset += 1;
+            let (account, len) = MultisigAccount::from_policy_bytes(&bytes[offset..])?;
+            offset += len;
+
+            bounds_check!(bytes, offset + 1);
+            let sig_count = bytes[offset];
+            offset += 1;
+
+            let mut signatures = Vec::with_capacity(sig_count as usize);
+            for _ in 0..sig_count {
+                bounds_check!(bytes, offset + 1);
+                let index = bytes[offset];
+                offset += 1;
+
+                let scheme = account
+                    .members()
+                    .get(index as usize)
+                    .ok_or(TransactionError::ByteMismatch)?
+                    .scheme();
+                let sig_byte_len = scheme.sig_byte_len()?;
+                bounds_check!(bytes, offset + sig_byte_len);
+                let sig = AnySignature::new(scheme, &bytes[offset..offset + sig_byte_len])?;
+                offset += sig_byte_len;
+                signatures.push((index, sig));
+            }
+
+            if bytes.len() != offset {
+                return Err(TransactionError::ByteMismatch);
+            }
+            multisig = Some(MultisigDigest {
+                account,
+                signatures,
+            });
+        } else if bytes.len() > offset {
+            let scheme = Scheme::from_u8(bytes[offset])
+                .ok_or(CryptoError::UnsupportedScheme(bytes[offset]))?;
+            let pub_byte_len = scheme.pub_byte_len()?;
+            let sig_byte_len = scheme.sig_byte_len()?;
+            offset += 1;
+
+            bounds_check!(bytes, offset + pub_byte_len);
+            signer = Some(AnyPublicKey::new(
+                scheme,
+                &bytes[offset..offset + pub_byte_len],
+            )?);
+            offset += pub_byte_len;
 
-        // Check if the transaction is signed
-        if bytes.len() - offset >= PUB_BYTE_LEN {
-            signer = Some(bytes[offset..offset + PUB_BYTE_LEN].try_into().unwrap());
-            offset += PUB_BYTE_LEN;
+            // The digest is a detached signature, so it has a fixed length
+            bounds_check!(bytes, offset + sig_byte_len);
+            digest = Some(AnySignature::new(
+                scheme,
+                &bytes[offset..offset + sig_byte_len],
+            )?);
+            offset += sig_byte_len;
 
-            digest = Some(bytes[offset..].to_vec());
+            if bytes.len() != offset {
+                return Err(TransactionError::ByteMismatch);
+            }
         }
 
         // We have read all the bytes, we can now construct the transaction
         Ok(Transaction {
+            chain_id,
             instructions_count,
             instructions,
             reserved_gas,
             priority_fee,
             timestamp,
+            nonce,
+            expires_at,
+            fee_payer,
+            fee_payer_digest,
             signer,
             digest,
+            multisig,
         })
     }
 
+    /// A multisig transaction counts as signed once it carries a policy, so
+    /// partially signed transactions can be passed between members. Whether
+    /// the threshold is met is checked by `verify`.
     pub fn is_signed(&self) -> bool {
-        self.signer.is_some() && self.digest.is_some()
+        self.multisig.is_some() || (self.signer.is_some() && self.digest.is_some())
     }
 
-    pub fn create_id(&self) -> Result<String, TransactionError> {
-        let id = DisplayAddress::new_from_seed(self.timestamp.to_le_bytes().as_ref());
-        Ok(id.as_str())
+    /// The content address of the transaction, it needs to be signed so the
+    /// signer is known
+    pub fn id_bytes(&self) -> Result<TxIdBytes, TransactionError> {
+        let mut hasher = Sha3_256::new();
+        hasher.update(TX_ID_DOMAIN);
+        hasher.update(self.to_sig_bytes());
+        if let Some(multisig) = &self.multisig {
+            hasher.update([MULTISIG_TAG]);
+            hasher.update(multisig.account.policy_bytes());
+        } else if let Some(signer) = &self.signer {
+            hasher.update(signer.to_bytes());
+        } else {
+            return Err(TransactionError::NotSigned);
+        }
+        Ok(hasher.finalize().into())
+    }
+
+    pub fn id(&self) -> Result<String, TransactionError> {
+        Ok(hex::encode(self.id_bytes()?))
+    }
+
+    /// Stores the signed transaction under its ID
+    pub async fn write(&self) -> Result<(), TransactionError> {
+        let key = storage_key(&self.id_bytes()?);
+        RocksDBStore::instance()
+            .async_put(key, self.to_bytes()?)
+            .await?;
+        Ok(())
+    }
+
+    pub async fn from_id(id: &TxIdBytes) -> Result<Transaction, TransactionError> {
+        match RocksDBStore::instance().async_get(storage_key(id)).await? {
+            Some(bytes) => Transaction::from_bytes(&bytes),
+            None => Err(TransactionError::TransactionNotFound),
+        }
     }
 
     pub fn to_sig_bytes(&self) -> Vec<u8> {
-        let mut bytes = Vec::new();
+        let mut bytes = vec![TX_VERSION];
+        bytes.extend(self.chain_id.to_le_bytes());
         bytes.extend(self.instructions_count.to_le_bytes());
         for instruction in &self.instructions {
             bytes.extend(instruction.to_bytes());
@@ -201,41 +544,262 @@ impl Transaction {
         bytes.extend(self.reserved_gas.to_le_bytes());
         bytes.extend(self.priority_fee.to_le_bytes());
         bytes.extend(self.timestamp.to_le_bytes());
+        bytes.extend(self.nonce.to_le_bytes());
+        bytes.extend(self.expires_at.to_le_bytes());
+        match &self.fee_payer {
+            Some(fee_payer) => {
+                bytes.push(1);
+                bytes.extend(fee_payer.to_bytes());
+            }
+            None => bytes.push(0),
+        }
 
         bytes
     }
 
-    pub fn sign(&mut self, kp: &SignerPair) -> Result<(), TransactionError> {
+    pub fn sign(&mut self, kp: &impl Signer) -> Result<(), TransactionError> {
         let message = self.to_sig_bytes();
-        let result = kp.sign(&message);
+        let result = kp.sign_any(&message);
 
-        self.signer = Some(*kp.pub_key_bytes());
+        self.signer = Some(kp.public_key());
         self.digest = Some(result);
+        self.multisig = None;
+        Ok(())
+    }
+
+    /// Runs the instructions with the authority of the signer, the gas and
+    /// priority fee are charged to the fee payer
+    pub async fn execute(&self) -> Result<(), TransactionError> {
+        let signer = self.signer_id().ok_or(TransactionError::NotSigned)?;
+        let fee_payer = self.fee_payer_id().ok_or(TransactionError::NotSigned)?;
+        execute_instructions(
+            &signer,
+            &fee_payer,
+            &self.instructions,
+            self.reserved_gas,
+            self.priority_fee,
+        )
+        .await?;
+        Ok(())
+    }
+
+    /// Signs as the fee payer, which has to be set before the signer signs
+    pub fn sign_fee_payer(&mut self, kp: &impl Signer) -> Result<(), TransactionError> {
+        if self.fee_payer.as_ref() != Some(&kp.public_key()) {
+            return Err(TransactionError::FeePayerMismatch);
+        }
+
+        let message = self.to_sig_bytes();
+        self.fee_payer_digest = Some(kp.sign_any(&message));
+        Ok(())
+    }
+
+    /// Adds the signature of one member of `account`, replacing any earlier
+    /// signature by the same member
+    pub fn sign_multisig(
+        &mut self,
+        account: &MultisigAccount,
+        kp: &impl Signer,
+    ) -> Result<(), TransactionError> {
+        let index = account
+            .member_index(&kp.public_key())
+            .ok_or(TransactionError::NotMultisigMember)?;
+        let message = self.to_sig_bytes();
+        let result = kp.sign_any(&message);
+
+        if self.multisig.as_ref().map(|m| &m.account) != Some(account) {
+            self.multisig = Some(MultisigDigest {
+                account: account.clone(),
+                signatures: Vec::new(),
+            });
+        }
+
+        let multisig = self.multisig.as_mut().unwrap();
+        multisig.signatures.retain(|(i, _)| *i != index);
+        multisig.signatures.push((index, result));
+        multisig.signatures.sort_by_key(|(i, _)| *i);
+
+        self.signer = None;
+        self.digest = None;
         Ok(())
     }
 
-    pub fn verify_ext(&self, kp: &VerifierPair) -> Result<bool, TransactionError> {
+    pub fn verify_ext(&self, pk: &AnyPublicKey) -> Result<bool, TransactionError> {
         if self.digest.is_none() {
             return Err(TransactionError::NotSigned);
         }
 
         let msg = self.to_sig_bytes();
-        Ok(kp.verify(&msg, &self.digest.as_ref().unwrap())?)
+        Ok(pk.verify(&msg, self.digest.as_ref().unwrap())?)
     }
 
     pub fn verify(&self) -> Result<bool, TransactionError> {
+        if let Some(fee_payer) = &self.fee_payer {
+            let digest = self
+                .fee_payer_digest
+                .as_ref()
+                .ok_or(TransactionError::FeePayerNotSigned)?;
+            if !fee_payer.verify(&self.to_sig_bytes(), digest)? {
+                return Ok(false);
+            }
+        }
+
+        if let Some(multisig) = &self.multisig {
+            let msg = self.to_sig_bytes();
+            return Ok(multisig.account.verify(&msg, &multisig.signatures));
+        }
+
         if self.signer.is_none() || self.digest.is_none() {
             return Err(TransactionError::NotSigned);
         }
 
-        let kp = VerifierPair::from_bytes(&self.signer.unwrap())?;
         let msg = self.to_sig_bytes();
-        Ok(kp.verify(&msg, &self.digest.as_ref().unwrap()).unwrap())
+        Ok(self
+            .signer
+            .as_ref()
+            .unwrap()
+            .verify(&msg, self.digest.as_ref().unwrap())?)
+    }
+
+    /// Queues the signatures of the transaction in `batch` and returns the
+    /// indexes they take up, the transaction is valid once all of them verify.
+    /// Multisig signers are checked against the threshold here.
+    pub fn add_to_batch(
+        &self,
+        batch: &mut BatchVerifier,
+    ) -> Result<Range<usize>, TransactionError> {
+        let msg = self.to_sig_bytes();
+        let start = batch.len();
+
+        if let Some(fee_payer) = &self.fee_payer {
+            let digest = self
+                .fee_payer_digest
+                .as_ref()
+                .ok_or(TransactionError::FeePayerNotSigned)?;
+            batch.push(fee_payer.clone(), &msg, digest.clone());
+        }
+
+        if let Some(multisig) = &self.multisig {
+            if !multisig.account.meets_threshold(&multisig.signatures) {
+                return Err(TransactionError::InvalidSignature);
+            }
+            for (index, sig) in &multisig.signatures {
+                let member = multisig.account.members()[*index as usize].clone();
+                batch.push(member, &msg, sig.clone());
+            }
+        } else {
+            match (&self.signer, &self.digest) {
+                (Some(signer), Some(digest)) => {
+                    batch.push(signer.clone(), &msg, digest.clone());
+                }
+                _ => return Err(TransactionError::NotSigned),
+            }
+        }
+
+        Ok(start..batch.len())
+    }
+}
+
+// The instruction count is implied by the instructions in serde formats
+#[cfg(feature = "serde")]
+impl serde::Serialize for Transaction {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        use serde::ser::SerializeStruct;
+
+        let mut state = serializer.serialize_struct("Transaction", 12)?;
+        state.serialize_field("chain_id", &self.chain_id)?;
+        state.serialize_field("instructions", &self.instructions)?;
+        state.serialize_field("reserved_gas", &self.reserved_gas)?;
+        state.serialize_field("priority_fee", &self.priority_fee)?;
+        state.serialize_field("timestamp", &self.timestamp)?;
+        state.serialize_field("nonce", &self.nonce)?;
+        state.serialize_field("expires_at", &self.expires_at)?;
+        state.serialize_field("fee_payer", &self.fee_payer)?;
+        state.serialize_field("fee_payer_digest", &self.fee_payer_digest)?;
+        state.serialize_field("signer", &self.signer)?;
+        state.serialize_field("digest", &self.digest)?;
+        state.serialize_field("multisig", &self.multisig)?;
+        state.end()
+    }
+}
+
+#[cfg(feature = "serde")]
+#[derive(serde::Deserialize)]
+struct TransactionRepr {
+    chain_id: u32,
+    instructions: Vec<Instruction>,
+    reserved_gas: u128,
+    priority_fee: u128,
+    timestamp: u64,
+    nonce: u64,
+    expires_at: u64,
+    fee_payer: Option<AnyPublicKey>,
+    fee_payer_digest: Option<AnySignature>,
+    signer: Option<AnyPublicKey>,
+    digest: Option<AnySignature>,
+    multisig: Option<MultisigDigest>,
+}
+
+// Held to the same rules as `from_bytes`, the chain has to match, a multisig
+// signature has to belong to a member and the fee payer signs with its own
+// scheme
+#[cfg(feature = "serde")]
+impl<'de> serde::Deserialize<'de> for Transaction {
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        use serde::de::Error;
+
+        let repr = TransactionRepr::deserialize(deserializer)?;
+        if repr.chain_id != CHAIN_ID {
+            return Err(D::Error::custom(TransactionError::WrongNetwork(
+                repr.chain_id,
+            )));
+        }
+        if let Some(sig) = &repr.fee_payer_digest {
+            match &repr.fee_payer {
+                Some(fee_payer) if fee_payer.scheme() == sig.scheme() => {}
+                _ => return Err(D::Error::custom(TransactionError::ByteMismatch)),
+            }
+        }
+        if let Some(multisig) = &repr.multisig {
+            let members = multisig.account.members();
+            for (index, sig) in &multisig.signatures {
+                match members.get(*index as usize) {
+                    Some(member) if member.scheme() == sig.scheme() => {}
+                    _ => return Err(D::Error::custom(TransactionError::ByteMismatch)),
+                }
+            }
+        }
+
+        Ok(Transaction {
+            chain_id: repr.chain_id,
+            instructions_count: repr.instructions.len() as u64,
+            instructions: repr.instructions,
+            reserved_gas: repr.reserved_gas,
+            priority_fee: repr.priority_fee,
+            timestamp: repr.timestamp,
+            nonce: repr.nonce,
+            expires_at: repr.expires_at,
+            fee_payer: repr.fee_payer,
+            fee_payer_digest: repr.fee_payer_digest,
+            signer: repr.signer,
+            digest: repr.digest,
+            multisig: repr.multisig,
+        })
     }
 }
 
 #[cfg(test)]
 mod tests {
+    use cesium_crypto::{
+        falcon, hybrid,
+        mldsa::{
+            da::DisplayAddress,
+            keypair::{SignerPair, ViewOperations},
+            Level, PUB_BYTE_LEN, SIG_BYTE_LEN,
+        },
+        sphincsplus,
+    };
+
     use crate::instructions::instruction::InstructionType;
 
     use super::*;
@@ -275,4 +839,457 @@ mod tests {
         assert_eq!(tx.signer, tx2.signer);
         assert_eq!(tx.digest, tx2.digest);
     }
+
+    #[test]
+    fn test_tx_id() {
+        let acc = SignerPair::create();
+
+        let mut tx = Transaction::new(100, 10);
+        let instruction = Instruction::new(InstructionType::CurrencyTransfer, vec![1, 2, 3]);
+        tx.add_instruction(instruction).unwrap();
+        assert!(matches!(tx.id(), Err(TransactionError::NotSigned)));
+
+        // The id does not depend on the signature, signing again keeps it
+        tx.sign(&acc).unwrap();
+        let id = tx.id().unwrap();
+        let digest = tx.digest.clone();
+        tx.sign(&acc).unwrap();
+        assert_ne!(tx.digest, digest);
+        assert_eq!(tx.id().unwrap(), id);
+
+        let tx2 = Transaction::from_bytes(&tx.to_bytes().unwrap()).unwrap();
+        assert_eq!(tx2.id().unwrap(), id);
+        assert_eq!(parse_id(&id).unwrap(), tx.id_bytes().unwrap());
+        assert!(matches!(
+            parse_id(&id[2..]),
+            Err(TransactionError::InvalidId)
+        ));
+
+        // The content and the signer do
+        tx.priority_fee += 1;
+        assert_ne!(tx.id().unwrap(), id);
+        tx.priority_fee -= 1;
+        tx.sign(&SignerPair::create()).unwrap();
+        assert_ne!(tx.id().unwrap(), id);
+    }
+
+    #[test]
+    fn test_tx_nonce() {
+        let acc = SignerPair::create();
+
+        let mut tx = Transaction::new(100, 10);
+        let instruction = Instruction::new(InstructionType::CurrencyTransfer, vec![1, 2, 3]);
+        tx.add_instruction(instruction).unwrap();
+        tx.nonce = 7;
+        tx.sign(&acc).unwrap();
+
+        let tx2 = Transaction::from_bytes(&tx.to_bytes().unwrap()).unwrap();
+        assert_eq!(tx2.nonce, 7);
+        assert!(tx2.verify().unwrap());
+        assert_eq!(
+            tx2.signer_id().unwrap(),
+            *DisplayAddress::from_pk(acc.pub_key_bytes()).as_bytes()
+        );
+
+        // The nonce is signed, so a copy with another nonce is a forgery
+        let id = tx.id().unwrap();
+        tx.nonce += 1;
+        assert!(!tx.verify().unwrap());
+        assert_ne!(tx.id().unwrap(), id);
+
+        // SPHINCS+ addresses do not fit an account ID, they are hashed down
+        let acc = sphincsplus::keypair::SignerPair::create();
+        tx.sign(&acc).unwrap();
+        let tx2 = Transaction::from_bytes(&tx.to_bytes().unwrap()).unwrap();
+        let da = address::decode(&tx2.signer_da().unwrap(), sphincsplus::da::DA_BYTE_LEN).unwrap();
+        assert_eq!(tx2.signer_id(), Some(Sha3_256::digest(&da).into()));
+        assert_eq!(tx2.fee_payer_id(), tx2.signer_id());
+    }
+
+    #[test]
+    fn test_tx_version_and_network() {
+        let acc = SignerPair::create();
+
+        let mut tx = Transaction::new(100, 10);
+        let instruction = Instruction::new(InstructionType::CurrencyTransfer, vec![1, 2, 3]);
+        tx.add_instruction(instruction).unwrap();
+        tx.sign(&acc).unwrap();
+
+        let mut bytes = tx.to_bytes().unwrap();
+        assert_eq!(bytes[0], TX_VERSION);
+        assert_eq!(bytes[1..5], CHAIN_ID.to_le_bytes());
+        bytes[0] = 9;
+        assert!(matches!(
+            Transaction::from_bytes(&bytes),
+            Err(TransactionError::UnsupportedVersion(9))
+        ));
+        assert!(matches!(
+            Transaction::from_bytes(&[]),
+            Err(TransactionError::ByteMismatch)
+        ));
+
+        // Signed for another network, neither accepted nor replayable here
+        tx.chain_id = CHAIN_ID + 1;
+        assert!(!tx.verify().unwrap());
+        tx.sign(&acc).unwrap();
+        assert!(matches!(
+            Transaction::from_bytes(&tx.to_bytes().unwrap()),
+            Err(TransactionError::WrongNetwork(id)) if id == CHAIN_ID + 1
+        ));
+    }
+
+    #[test]
+    fn test_tx_malformed() {
+        let user = SignerPair::create();
+        let sponsor = falcon::keypair::SignerPair::create();
+
+        let mut tx = Transaction::new(100, 10);
+        let instruction = Instruction::new(InstructionType::CurrencyTransfer, vec![1, 2, 3]);
+        tx.add_instruction(instruction).unwrap();
+        tx.sign(&user).unwrap();
+        let bytes = tx.to_bytes().unwrap();
+        let sig_len = tx.to_sig_bytes().len();
+
+        // Cut short anywhere, even inside the instruction data, is an error
+        // and never a panic. Cut right after the signing payload it is an
+        // unsigned transaction.
+        for len in (0..bytes.len()).filter(|len| *len != sig_len) {
+            assert!(Transaction::from_bytes(&bytes[..len]).is_err());
+        }
+        assert!(matches!(
+            Transaction::from_bytes(&bytes[..20]),
+            Err(TransactionError::ByteMismatch)
+        ));
+
+        // The fee payer flag is the last byte of the signing payload
+        let mut flagged = bytes.clone();
+        flagged[sig_len - 1] = 2;
+        assert!(matches!(
+            Transaction::from_bytes(&flagged),
+            Err(TransactionError::ByteMismatch)
+        ));
+
+        // The fee payer signature flag follows the signing payload
+        tx.fee_payer = Some(sponsor.public_key());
+        tx.sign(&user).unwrap();
+        tx.sign_fee_payer(&sponsor).unwrap();
+        let mut flagged = tx.to_bytes().unwrap();
+        flagged[tx.to_sig_bytes().len()] = 2;
+        assert!(matches!(
+            Transaction::from_bytes(&flagged),
+            Err(TransactionError::ByteMismatch)
+        ));
+    }
+
+    #[test]
+    fn test_tx_fee_payer() {
+        let user = SignerPair::create();
+        let sponsor = falcon::keypair::SignerPair::create();
+
+        let mut tx = Transaction::new(100, 10);
+        let instruction = Instruction::new(InstructionType::CurrencyTransfer, vec![1, 2, 3]);
+        tx.add_instruction(instruction).unwrap();
+        tx.fee_payer = Some(sponsor.public_key());
+        tx.sign(&user).unwrap();
+
+        // The signed transaction travels to the sponsor before it pays
+        let mut tx = Transaction::from_bytes(&tx.to_bytes().unwrap()).unwrap();
+        let id = tx.id().unwrap();
+        assert!(matches!(
+            tx.verify(),
+            Err(TransactionError::FeePayerNotSigned)
+        ));
+        assert!(matches!(
+            tx.sign_fee_payer(&user),
+            Err(TransactionError::FeePayerMismatch)
+        ));
+
+        tx.sign_fee_payer(&sponsor).unwrap();
+        let tx2 = Transaction::from_bytes(&tx.to_bytes().unwrap()).unwrap();
+        assert!(tx2.verify().unwrap());
+        assert_eq!(tx2.id().unwrap(), id);
+        assert_eq!(
+            tx2.signer_id(),
+            Some(*DisplayAddress::from_pk(user.pub_key_bytes()).as_bytes())
+        );
+        assert_ne!(tx2.fee_payer_id(), tx2.signer_id());
+
+        let mut batch = BatchVerifier::new();
+        assert_eq!(tx2.add_to_batch(&mut batch).unwrap(), 0..2);
+        assert!(batch.verify().all_valid());
+
+        // The signer agreed to this fee payer, another one can not take over
+        let other = falcon::keypair::SignerPair::create();
+        let mut swapped = tx2.clone();
+        swapped.fee_payer = Some(other.public_key());
+        swapped.sign_fee_payer(&other).unwrap();
+        assert!(!swapped.verify().unwrap());
+
+        // A SPHINCS+ sponsor has a 48 byte address, it still pays
+        let sponsor = sphincsplus::keypair::SignerPair::create();
+        let mut tx = tx2.clone();
+        tx.fee_payer = Some(sponsor.public_key());
+        tx.sign(&user).unwrap();
+        tx.sign_fee_payer(&sponsor).unwrap();
+        let tx2 = Transaction::from_bytes(&tx.to_bytes().unwrap()).unwrap();
+        assert!(tx2.verify().unwrap());
+        let da = address::decode(
+            &sponsor.public_key().display_address().unwrap(),
+            sphincsplus::da::DA_BYTE_LEN,
+        )
+        .unwrap();
+        assert_eq!(tx2.fee_payer_id(), Some(Sha3_256::digest(&da).into()));
+    }
+
+    #[test]
+    fn test_tx_expiry() {
+        let acc = SignerPair::create();
+
+        let mut tx = Transaction::new(100, 10);
+        let instruction = Instruction::new(InstructionType::CurrencyTransfer, vec![1, 2, 3]);
+        tx.add_instruction(instruction).unwrap();
+        assert_eq!(tx.expires_at, tx.timestamp + DEFAULT_TX_LIFETIME);
+        assert!(!tx.is_expired());
+
+        tx.expires_at = tx.timestamp + 5;
+        tx.sign(&acc).unwrap();
+        let tx2 = Transaction::from_bytes(&tx.to_bytes().unwrap()).unwrap();
+        assert_eq!(tx2.expires_at, tx.expires_at);
+        assert!(!tx2.is_expired_at(tx.timestamp + 5));
+        assert!(tx2.is_expired_at(tx.timestamp + 6));
+
+        // The expiry is signed, it can not be pushed back by a relay
+        tx.expires_at += 60;
+        assert!(!tx.verify().unwrap());
+    }
+
+    #[tokio::test]
+    async fn test_tx_storage() {
+        let acc = SignerPair::create();
+
+        let mut tx = Transaction::new(100, 10);
+        let instruction = Instruction::new(InstructionType::CurrencyTransfer, vec![4, 5, 6]);
+        tx.add_instruction(instruction).unwrap();
+        tx.sign(&acc).unwrap();
+        tx.write().await.unwrap();
+
+        let id = tx.id_bytes().unwrap();
+        let stored = Transaction::from_id(&id).await.unwrap();
+        assert_eq!(stored.to_bytes().unwrap(), tx.to_bytes().unwrap());
+        assert!(matches!(
+            Transaction::from_id(&[0; TX_ID_BYTE_LEN]).await,
+            Err(TransactionError::TransactionNotFound)
+        ));
+    }
+
+    #[test]
+    fn test_tx_detached_digest() {
+        let acc = SignerPair::create();
+
+        let mut tx = Transaction::new(100, 10);
+        let instruction = Instruction::new(InstructionType::CurrencyTransfer, vec![0; 512]);
+        tx.add_instruction(instruction).unwrap();
+        tx.sign(&acc).unwrap();
+
+        // The digest should not grow with the size of the instructions
+        let digest = tx.digest.as_ref().unwrap();
+        assert_eq!(digest.as_bytes().len(), SIG_BYTE_LEN);
+
+        let mut bytes = tx.to_bytes().unwrap();
+        assert_eq!(
+            bytes.len(),
+            tx.to_sig_bytes().len() + 1 + PUB_BYTE_LEN + SIG_BYTE_LEN
+        );
+
+        // Trailing bytes after the digest are rejected
+        bytes.push(0);
+        assert!(matches!(
+            Transaction::from_bytes(&bytes),
+            Err(TransactionError::ByteMismatch)
+        ));
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn test_tx_serde() {
+        let acc = SignerPair::create();
+
+        let mut tx = Transaction::new(100, 10);
+        let instruction = Instruction::new(InstructionType::CurrencyTransfer, vec![1, 2, 3]);
+        tx.add_instruction(instruction).unwrap();
+        tx.sign(&acc).unwrap();
+
+        let json = serde_json::to_value(&tx).unwrap();
+        assert_eq!(json["instructions"][0]["data"], "010203");
+        assert_eq!(
+            json["instructions"][0]["instruction_type"],
+            "CurrencyTransfer"
+        );
+        assert!(json["multisig"].is_null());
+
+        let tx2: Transaction = serde_json::from_value(json).unwrap();
+        assert_eq!(tx2.instructions_count, 1);
+        assert_eq!(tx2.to_bytes().unwrap(), tx.to_bytes().unwrap());
+        assert!(tx2.verify().unwrap());
+    }
+
+    #[test]
+    fn test_tx_other_scheme() {
+        let acc = falcon::keypair::SignerPair::create();
+
+        let mut tx = Transaction::new(100, 10);
+        let instruction = Instruction::new(InstructionType::CurrencyTransfer, vec![1, 2, 3]);
+        tx.add_instruction(instruction).unwrap();
+
+        tx.sign(&acc).unwrap();
+
+        let bytes = tx.to_bytes().unwrap();
+        let tx2 = Transaction::from_bytes(&bytes).unwrap();
+
+        assert_eq!(tx2.signer.as_ref().unwrap().scheme(), Scheme::Falcon);
+        assert_eq!(tx.signer_da(), tx2.signer_da());
+        assert!(tx2.verify().unwrap());
+    }
+
+    #[test]
+    fn test_tx_mldsa_level() {
+        let acc = SignerPair::create_with_level(Level::MlDsa87);
+
+        let mut tx = Transaction::new(100, 10);
+        let instruction = Instruction::new(InstructionType::CurrencyTransfer, vec![1, 2, 3]);
+        tx.add_instruction(instruction).unwrap();
+
+        tx.sign(&acc).unwrap();
+
+        let bytes = tx.to_bytes().unwrap();
+        let tx2 = Transaction::from_bytes(&bytes).unwrap();
+
+        assert_eq!(tx2.signer.as_ref().unwrap().scheme(), Scheme::MlDsa87);
+        assert_eq!(tx.signer_da(), tx2.signer_da());
+        assert!(tx2.verify().unwrap());
+    }
+
+    #[test]
+    fn test_tx_hybrid() {
+        let acc = hybrid::keypair::SignerPair::create();
+
+        let mut tx = Transaction::new(100, 10);
+        let instruction = Instruction::new(InstructionType::CurrencyTransfer, vec![1, 2, 3]);
+        tx.add_instruction(instruction).unwrap();
+
+        tx.sign(&acc).unwrap();
+
+        let bytes = tx.to_bytes().unwrap();
+        let mut tx2 = Transaction::from_bytes(&bytes).unwrap();
+
+        assert_eq!(tx2.signer.as_ref().unwrap().scheme(), Scheme::Hybrid);
+        assert_eq!(tx.signer_da(), tx2.signer_da());
+        assert!(tx2.verify().unwrap());
+
+        // Tampering invalidates the signature
+        tx2.priority_fee += 1;
+        assert!(!tx2.verify().unwrap());
+    }
+
+    #[test]
+    fn test_tx_multisig() {
+        let acc1 = SignerPair::create();
+        let acc2 = falcon::keypair::SignerPair::create();
+        let acc3 = sphincsplus::keypair::SignerPair::create();
+        let account = MultisigAccount::new(
+            vec![acc1.public_key(), acc2.public_key(), acc3.public_key()],
+            2,
+        )
+        .unwrap();
+
+        let mut tx = Transaction::new(100, 10);
+        let instruction = Instruction::new(InstructionType::CurrencyTransfer, vec![1, 2, 3]);
+        tx.add_instruction(instruction).unwrap();
+
+        // A partially signed transaction can be passed on to the next member
+        tx.sign_multisig(&account, &acc1).unwrap();
+        assert!(tx.is_signed());
+        assert!(!tx.verify().unwrap());
+        let id = tx.id().unwrap();
+        let mut tx = Transaction::from_bytes(&tx.to_bytes().unwrap()).unwrap();
+
+        // Collecting signatures does not change the id
+        tx.sign_multisig(&account, &acc3).unwrap();
+        assert!(tx.verify().unwrap());
+        assert_eq!(tx.id().unwrap(), id);
+        assert_eq!(tx.signer_da(), Some(account.address()));
+
+        let tx2 = Transaction::from_bytes(&tx.to_bytes().unwrap()).unwrap();
+        assert_eq!(tx.multisig, tx2.multisig);
+        assert!(tx2.verify().unwrap());
+
+        let outsider = SignerPair::create();
+        assert!(matches!(
+            tx.sign_multisig(&account, &outsider),
+            Err(TransactionError::NotMultisigMember)
+        ));
+    }
+
+    #[test]
+    fn test_tx_multisig_tampered() {
+        let acc1 = SignerPair::create();
+        let acc2 = SignerPair::create();
+        let account = MultisigAccount::new(vec![acc1.public_key(), acc2.public_key()], 2).unwrap();
+
+        let mut tx = Transaction::new(100, 10);
+        let instruction = Instruction::new(InstructionType::CurrencyTransfer, vec![1, 2, 3]);
+        tx.add_instruction(instruction).unwrap();
+        tx.sign_multisig(&account, &acc1).unwrap();
+
+        // Signing twice with the same member does not count twice
+        tx.sign_multisig(&account, &acc1).unwrap();
+        assert_eq!(tx.multisig.as_ref().unwrap().signatures.len(), 1);
+        assert!(!tx.verify().unwrap());
+
+        tx.sign_multisig(&account, &acc2).unwrap();
+        assert!(tx.verify().unwrap());
+
+        tx.priority_fee = 11;
+        assert!(!tx.verify().unwrap());
+    }
+
+    #[test]
+    fn test_tx_batch() {
+        let acc1 = SignerPair::create();
+        let acc2 = falcon::keypair::SignerPair::create();
+        let account = MultisigAccount::new(vec![acc1.public_key(), acc2.public_key()], 2).unwrap();
+
+        let mut single = Transaction::new(100, 10);
+        single
+            .add_instruction(Instruction::new(InstructionType::CurrencyTransfer, vec![1]))
+            .unwrap();
+        single.sign(&acc1).unwrap();
+
+        let mut multisig = Transaction::new(100, 10);
+        multisig
+            .add_instruction(Instruction::new(InstructionType::CurrencyTransfer, vec![2]))
+            .unwrap();
+        multisig.sign_multisig(&account, &acc1).unwrap();
+
+        // Below the threshold the signatures are not even queued
+        let mut batch = BatchVerifier::new();
+        assert!(matches!(
+            multisig.add_to_batch(&mut batch),
+            Err(TransactionError::InvalidSignature)
+        ));
+        assert!(batch.is_empty());
+
+        multisig.sign_multisig(&account, &acc2).unwrap();
+        let single_range = single.add_to_batch(&mut batch).unwrap();
+        let multisig_range = multisig.add_to_batch(&mut batch).unwrap();
+        assert_eq!(single_range, 0..1);
+        assert_eq!(multisig_range, 1..3);
+        assert!(batch.verify().all_valid());
+
+        single.priority_fee = 0;
+        let mut batch = BatchVerifier::new();
+        single.add_to_batch(&mut batch).unwrap();
+        multisig.add_to_batch(&mut batch).unwrap();
+        assert_eq!(batch.verify().failed(), vec![0]);
+    }
 }
@@ -0,0 +1,158 @@
+use cesium_crypto::merkle::{Hash, MerkleProof, MerkleTree, HASH_BYTE_LEN};
+use cesium_storage::RocksDBStore;
+
+use super::{errors::GraphError, node::NODE_HEADER_BYTE_LEN};
+
+// Checkpoint layout, integers are little endian:
+// ROOT (32) | NODE_COUNT (4) | (NODE_LEN (4) | NODE)*
+// The root is the merkle root over the serialized nodes, it is what the
+// validator signs, so a single node can be proven without the rest.
+
+macro_rules! bounds_check {
+    ($bytes:expr, $len:expr) => {
+        if $bytes.len() < $len {
+            return Err(GraphError::NodeSerializationError(
+                "Checkpoint too short".to_string(),
+            ));
+        }
+    };
+}
+
+pub struct Checkpoint {
+    nodes: Vec<Vec<u8>>,
+    tree: MerkleTree,
+}
+
+impl Checkpoint {
+    /// Takes the nodes as serialized by `GraphNode::to_bytes`
+    pub fn new(nodes: Vec<Vec<u8>>) -> Self {
+        let tree = MerkleTree::new(&nodes);
+        Self { nodes, tree }
+    }
+
+    pub fn root(&self) -> Hash {
+        self.tree.root()
+    }
+
+    pub fn nodes(&self) -> &[Vec<u8>] {
+        &self.nodes
+    }
+
+    pub fn len(&self) -> usize {
+        self.nodes.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.nodes.is_empty()
+    }
+
+    /// Inclusion proof for the node with the given id, verified against the
+    /// root with the serialized node as leaf
+    pub fn proof(&self, node_id: &str) -> Option<MerkleProof> {
+        let index = self
+            .nodes
+            .iter()
+            .position(|node| Self::node_id(node) == Some(node_id.as_bytes()))?;
+        self.tree.proof(index)
+    }
+
+    // Nodes start with their header, then the length of their id followed
+    // by the id
+    fn node_id(node: &[u8]) -> Option<&[u8]> {
+        let node = node.get(NODE_HEADER_BYTE_LEN..)?;
+        let len = *node.first()? as usize;
+        node.get(1..1 + len)
+    }
+
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let mut bytes = Vec::new();
+        bytes.extend_from_slice(&self.root());
+        bytes.extend((self.nodes.len() as u32).to_le_bytes());
+        for node in &self.nodes {
+            bytes.extend((node.len() as u32).to_le_bytes());
+            bytes.extend_from_slice(node);
+        }
+        bytes
+    }
+
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self, GraphError> {
+        bounds_check!(bytes, HASH_BYTE_LEN + 4);
+        let root = &bytes[0..HASH_BYTE_LEN];
+        let mut offset = HASH_BYTE_LEN;
+        let count = u32::from_le_bytes(bytes[offset..offset + 4].try_into()?) as usize;
+        offset += 4;
+
+        let mut nodes = Vec::new();
+        for _ in 0..count {
+            bounds_check!(bytes, offset + 4);
+            let len = u32::from_le_bytes(bytes[offset..offset + 4].try_into()?) as usize;
+            offset += 4;
+            bounds_check!(bytes, offset + len);
+            nodes.push(bytes[offset..offset + len].to_vec());
+            offset += len;
+        }
+
+        let checkpoint = Self::new(nodes);
+        if checkpoint.root() != root {
+            return Err(GraphError::NodeSerializationError(
+                "Checkpoint root mismatch".to_string(),
+            ));
+        }
+        Ok(checkpoint)
+    }
+
+    /// Checkpoints are stored under the validator's signature of their root
+    pub fn load(key: &[u8]) -> Result<Option<Self>, GraphError> {
+        match RocksDBStore::instance().get(key) {
+            Ok(Some(bytes)) => Ok(Some(Self::from_bytes(&bytes)?)),
+            Ok(None) => Ok(None),
+            Err(e) => Err(GraphError::GetCheckpointError(e)),
+        }
+    }
+
+    pub fn save(&self, key: &[u8]) -> Result<(), GraphError> {
+        RocksDBStore::instance()
+            .put(key, &self.to_bytes())
+            .map_err(GraphError::PutCheckpointError)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn node(id: &str) -> Vec<u8> {
+        let mut bytes = vec![0; NODE_HEADER_BYTE_LEN];
+        bytes.push(id.len() as u8);
+        bytes.extend(id.as_bytes());
+        bytes.extend([1, 2, 3]);
+        bytes
+    }
+
+    #[test]
+    fn test_checkpoint_proof() {
+        let checkpoint = Checkpoint::new(vec![node("a"), node("bb"), node("ccc")]);
+        let root = checkpoint.root();
+
+        let proof = checkpoint.proof("bb").unwrap();
+        assert!(proof.verify(&root, &node("bb")));
+        assert!(!proof.verify(&root, &node("a")));
+        assert!(checkpoint.proof("dddd").is_none());
+    }
+
+    #[test]
+    fn test_checkpoint_bytes() {
+        let checkpoint = Checkpoint::new(vec![node("a"), node("bb")]);
+        let mut bytes = checkpoint.to_bytes();
+
+        let checkpoint2 = Checkpoint::from_bytes(&bytes).unwrap();
+        assert_eq!(checkpoint2.root(), checkpoint.root());
+        assert_eq!(checkpoint2.nodes(), checkpoint.nodes());
+
+        // The stored root has to match the nodes
+        let last = bytes.len() - 1;
+        bytes[last] ^= 1;
+        assert!(Checkpoint::from_bytes(&bytes).is_err());
+        assert!(Checkpoint::from_bytes(&bytes[..last]).is_err());
+    }
+}
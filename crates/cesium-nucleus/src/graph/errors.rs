@@ -1,6 +1,9 @@
 use std::{array::TryFromSliceError, fmt, string::FromUtf8Error};
 
-use cesium_nebula::{instructions::errors::InstructionError, transaction::TransactionError};
+use cesium_nebula::{
+    accounts::AccountError, instructions::errors::InstructionError, transaction::TransactionError,
+};
+use cesium_standards::CHAIN_ID;
 use cesium_storage::errors::StorageError;
 
 #[derive(Debug)]
@@ -8,11 +11,21 @@ pub enum GraphError {
     MissingGenesisNode,
     InvalidNodeInput,
     InvalidNodeId,
+    UnsupportedNodeVersion(u8),
+    WrongNetwork(u32),
+    DuplicateNode,
+    StaleNonce,
+    DuplicateNonce,
+    NonceTooFarAhead,
+    TransactionExpired(u64),
     ReferenceNodeMismatch,
     MissingSignature,
+    InvalidSignature,
     NodeSerializationError(String),
     PutCheckpointError(StorageError),
+    GetCheckpointError(StorageError),
     TransactionError(TransactionError),
+    AccountError(AccountError),
     InstructionError(InstructionError),
     FromUtf8Error(FromUtf8Error),
 }
@@ -24,13 +37,33 @@ impl fmt::Display for GraphError {
             GraphError::MissingGenesisNode => write!(f, "Missing genesis node"),
             GraphError::InvalidNodeInput => write!(f, "Invalid node input"),
             GraphError::InvalidNodeId => write!(f, "Invalid node id"),
+            GraphError::UnsupportedNodeVersion(version) => {
+                write!(f, "Unsupported node version {}", version)
+            }
+            GraphError::WrongNetwork(chain_id) => write!(
+                f,
+                "Node is for chain {} but this is chain {}",
+                chain_id, CHAIN_ID
+            ),
+            GraphError::DuplicateNode => write!(f, "Transaction is already in the graph"),
+            GraphError::StaleNonce => write!(f, "Nonce has already been used"),
+            GraphError::DuplicateNonce => {
+                write!(f, "A transaction with this nonce is already waiting")
+            }
+            GraphError::NonceTooFarAhead => write!(f, "Nonce is too far ahead"),
+            GraphError::TransactionExpired(expires_at) => {
+                write!(f, "Transaction expired at unix time {}", expires_at)
+            }
             GraphError::ReferenceNodeMismatch => write!(f, "Reference node mismatch"),
             GraphError::MissingSignature => write!(f, "Missing signature"),
+            GraphError::InvalidSignature => write!(f, "Invalid signature"),
             GraphError::NodeSerializationError(ref e) => {
                 write!(f, "Node serialization error: {}", e)
             }
             GraphError::PutCheckpointError(ref e) => write!(f, "Put checkpoint error: {}", e),
+            GraphError::GetCheckpointError(ref e) => write!(f, "Get checkpoint error: {}", e),
             GraphError::TransactionError(ref e) => e.fmt(f),
+            GraphError::AccountError(ref e) => e.fmt(f),
             GraphError::InstructionError(ref e) => e.fmt(f),
             GraphError::FromUtf8Error(ref e) => e.fmt(f),
         }
@@ -43,6 +76,12 @@ impl From<TransactionError> for GraphError {
     }
 }
 
+impl From<AccountError> for GraphError {
+    fn from(err: AccountError) -> Self {
+        GraphError::AccountError(err)
+    }
+}
+
 impl From<InstructionError> for GraphError {
     fn from(err: InstructionError) -> Self {
         GraphError::InstructionError(err)
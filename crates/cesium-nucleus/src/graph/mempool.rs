@@ -1,16 +1,41 @@
-use cesium_crypto::mldsa::{keypair::SignerPair, SIG_BYTE_LEN};
-use cesium_nebula::transaction::Transaction;
-use dashmap::DashMap;
-use std::sync::Arc;
+use cesium_crypto::{
+    batch::BatchVerifier,
+    mldsa::{da::DABytes, keypair::SignerPair},
+};
+use cesium_nebula::{
+    accounts::{AccountError, NonceAccount},
+    transaction::{unix_now, Transaction, TransactionError},
+};
+use dashmap::{mapref::entry::Entry, DashMap};
+use std::{
+    collections::{BTreeMap, BTreeSet},
+    ops::Range,
+    sync::Arc,
+};
+use tokio::sync::Mutex;
 
 use super::{
+    checkpoint::Checkpoint,
     errors::GraphError,
     node::{GraphNode, NodeId},
 };
 
+// How far ahead of the expected nonce a transaction may be held back
+pub const MAX_NONCE_GAP: u64 = 64;
+
+/// Nonce state of one signer, transactions that arrive ahead of their turn
+/// wait in `held` until the gap is filled
+struct SignerQueue {
+    account: NonceAccount,
+    held: BTreeMap<u64, Transaction>,
+}
+
 pub struct Graph<'a> {
     account: &'a SignerPair,
     nodes: Arc<DashMap<NodeId, Arc<GraphNode>>>,
+    signers: Arc<DashMap<DABytes, Arc<Mutex<SignerQueue>>>>,
+    // Unpacked nodes ordered by the time their transaction expires
+    expiries: Arc<Mutex<BTreeSet<(u64, NodeId)>>>,
     pack_iv_count: usize,
     pack_min_conf: u32,
     pack_proportion: f32,
@@ -26,6 +51,8 @@ impl<'a> Graph<'a> {
         Self {
             account,
             nodes: Arc::new(DashMap::new()),
+            signers: Arc::new(DashMap::new()),
+            expiries: Arc::new(Mutex::new(BTreeSet::new())),
             pack_iv_count,
             pack_min_conf,
             pack_proportion,
@@ -46,8 +73,17 @@ impl<'a> Graph<'a> {
             return Err(e);
         }
 
-        // Compute random node_id of 48 characters
-        let node_id = input.create_id()?;
+        self.accept(input, true).await
+    }
+
+    async fn insert_genesis(&self, input: &Transaction) -> Result<(), GraphError> {
+        // Nodes are keyed by the transaction id
+        let node_id = input.id()?;
+        if self.nodes.contains_key(&node_id) {
+            return Err(GraphError::DuplicateNode);
+        }
+        input.write().await?;
+
         let node = GraphNode {
             id: node_id.clone(),
             instructions: input.instructions.clone(),
@@ -57,6 +93,10 @@ impl<'a> Graph<'a> {
         let node_arc: Arc<GraphNode> = Arc::new(node);
 
         // Add node to the graph
+        self.expiries
+            .lock()
+            .await
+            .insert((input.expires_at, node_id.clone()));
         self.nodes.insert(node_id, node_arc);
         Ok(())
     }
@@ -66,9 +106,100 @@ impl<'a> Graph<'a> {
             return Err(e);
         }
 
-        // Compute random node_id of 48 characters
-        let node_id = input.create_id()?;
+        self.accept(input, false).await
+    }
+
+    /// Adds a burst of transactions, their signatures are verified as one
+    /// batch. Returns the outcome for each transaction in order.
+    pub async fn add_items(&self, inputs: &[Transaction]) -> Vec<Result<(), GraphError>> {
+        let validated = self.validate_items(&inputs.iter().collect::<Vec<_>>());
+
+        let mut results = Vec::with_capacity(inputs.len());
+        for (input, result) in inputs.iter().zip(validated) {
+            results.push(match result {
+                Ok(()) => self.accept(input, false).await,
+                Err(e) => Err(e),
+            });
+        }
+        results
+    }
 
+    /// Inserts a validated transaction once it is the signer's turn. A stale
+    /// nonce is rejected, a future one is held back until the nonces before
+    /// it are used.
+    async fn accept(&self, input: &Transaction, genesis: bool) -> Result<(), GraphError> {
+        let signer = input.signer_id().ok_or(GraphError::MissingSignature)?;
+        let queue = self.signer_queue(signer).await?;
+        let mut queue = queue.lock().await;
+
+        let expected = queue.account.nonce();
+        if input.nonce < expected {
+            return Err(GraphError::StaleNonce);
+        }
+        if input.nonce > expected {
+            if input.nonce - expected > MAX_NONCE_GAP {
+                return Err(GraphError::NonceTooFarAhead);
+            }
+            if queue.held.contains_key(&input.nonce) {
+                return Err(GraphError::DuplicateNonce);
+            }
+            queue.held.insert(input.nonce, input.clone());
+            return Ok(());
+        }
+
+        if genesis {
+            self.insert_genesis(input).await?;
+        } else {
+            self.insert_item(input).await?;
+        }
+
+        // Held transactions were validated when they arrived. One that fails
+        // or expired while waiting is dropped and leaves its nonce open for a
+        // replacement.
+        let mut next = expected + 1;
+        while let Some(held) = queue.held.remove(&next) {
+            if held.is_expired() || self.insert_item(&held).await.is_err() {
+                break;
+            }
+            next += 1;
+        }
+
+        queue.account.set_nonce(next);
+        queue
+            .account
+            .write()
+            .await
+            .map_err(AccountError::StorageError)?;
+        Ok(())
+    }
+
+    async fn signer_queue(&self, signer: DABytes) -> Result<Arc<Mutex<SignerQueue>>, GraphError> {
+        let existing = self.signers.get(&signer).map(|queue| queue.clone());
+        if let Some(queue) = existing {
+            return Ok(queue);
+        }
+
+        let account = NonceAccount::from_id(signer).await?;
+        Ok(self
+            .signers
+            .entry(signer)
+            .or_insert_with(|| {
+                Arc::new(Mutex::new(SignerQueue {
+                    account,
+                    held: BTreeMap::new(),
+                }))
+            })
+            .clone())
+    }
+
+    async fn insert_item(&self, input: &Transaction) -> Result<(), GraphError> {
+        // Nodes are keyed by the transaction id
+        let node_id = input.id()?;
+        if self.nodes.contains_key(&node_id) {
+            return Err(GraphError::DuplicateNode);
+        }
+
+        self.prune_expired(unix_now()).await;
         let ref_nodes = self.get_pending_nodes().await;
         if ref_nodes.is_empty() {
             return Err(GraphError::MissingGenesisNode);
@@ -86,8 +217,18 @@ impl<'a> Graph<'a> {
         };
         let node_arc = Arc::new(node);
 
-        // Add node to the graph
-        self.nodes.insert(node_id, node_arc);
+        // Add node to the graph, the transaction is kept for lookups by id
+        input.write().await?;
+        match self.nodes.entry(node_id.clone()) {
+            Entry::Occupied(_) => return Err(GraphError::DuplicateNode),
+            Entry::Vacant(entry) => {
+                entry.insert(node_arc);
+            }
+        }
+        self.expiries
+            .lock()
+            .await
+            .insert((input.expires_at, node_id));
         // TODO: Gossip the node to other validators
 
         // if nodes length
@@ -110,28 +251,48 @@ impl<'a> Graph<'a> {
         self.pack_proportion = proportion;
     }
 
-    async fn pack_history(&self) -> Result<(), GraphError> {
+    /// Drops nodes whose transaction expired before it was packed and
+    /// returns how many were dropped
+    async fn prune_expired(&self, now: u64) -> usize {
+        let mut expiries = self.expiries.lock().await;
+        let mut pruned = 0;
+        while let Some((expires_at, _)) = expiries.first() {
+            if *expires_at >= now {
+                break;
+            }
+            let (_, id) = expiries.pop_first().unwrap();
+            // Packed nodes are already gone from the graph
+            if self.nodes.remove(&id).is_some() {
+                pruned += 1;
+            }
+        }
+        pruned
+    }
+
+    /// Packs confirmed nodes into a checkpoint and returns the key it is
+    /// stored under
+    async fn pack_history(&self) -> Result<Vec<u8>, GraphError> {
+        self.prune_expired(unix_now()).await;
+
         // Get all nodes with 5 or more confirmed references
         let nodes = self.get_packable_nodes().await;
 
-        // Convert the nodes to bytes
-        let msg = futures::future::join_all(
+        // Convert the nodes to bytes, each node is a leaf of the checkpoint
+        let leaves = futures::future::join_all(
             nodes
                 .iter()
                 .map(|node: &Arc<GraphNode>| async { node.to_bytes().await }),
         )
-        .await
-        .concat();
+        .await;
+        let checkpoint = Checkpoint::new(leaves);
 
-        // Sign the message
-        let sig = self.account.sign(&msg);
+        // Sign the transaction root, the checkpoint is stored under its detached signature
+        let sig = self.account.sign_detached(&checkpoint.root());
 
         // TODO: This
         // Broadcast the checkpoint to other validators
 
-        if let Err(e) = cesium_storage::RocksDBStore::instance().put(&sig, &msg) {
-            return Err(GraphError::PutCheckpointError(e));
-        }
+        checkpoint.save(&sig)?;
 
         // TODO: Update the balances of the accounts in this checkpoint
 
@@ -140,7 +301,7 @@ impl<'a> Graph<'a> {
             self.nodes.remove(&node.id);
         }
 
-        Ok(())
+        Ok(sig)
     }
 
     async fn get_packable_nodes(&self) -> Vec<Arc<GraphNode>> {
@@ -153,7 +314,39 @@ impl<'a> Graph<'a> {
     }
 
     fn validate_item(&self, input: &Transaction) -> Result<(), GraphError> {
-        if input.digest.is_none() {
+        self.validate_items(&[input]).pop().unwrap()
+    }
+
+    fn validate_items(&self, inputs: &[&Transaction]) -> Vec<Result<(), GraphError>> {
+        let mut batch = BatchVerifier::new();
+        let queued: Vec<Result<Range<usize>, GraphError>> = inputs
+            .iter()
+            .map(|input| {
+                self.check_item(input)?;
+                // Multisig transactions also need enough member signatures to
+                // meet the threshold of the account
+                input.add_to_batch(&mut batch).map_err(|e| match e {
+                    TransactionError::InvalidSignature => GraphError::InvalidSignature,
+                    e => e.into(),
+                })
+            })
+            .collect();
+
+        let verified = batch.verify();
+        queued
+            .into_iter()
+            .map(|range| {
+                if range?.all(|i| verified.is_valid(i)) {
+                    Ok(())
+                } else {
+                    Err(GraphError::InvalidSignature)
+                }
+            })
+            .collect()
+    }
+
+    fn check_item(&self, input: &Transaction) -> Result<(), GraphError> {
+        if !input.is_signed() {
             return Err(GraphError::MissingSignature);
         }
 
@@ -162,12 +355,16 @@ impl<'a> Graph<'a> {
             return Err(GraphError::InvalidNodeInput);
         }
 
-        let sig = input.digest.as_ref().unwrap();
-        if sig.len() < SIG_BYTE_LEN {
-            return Err(GraphError::InvalidNodeInput);
+        if input.is_expired() {
+            return Err(GraphError::TransactionExpired(input.expires_at));
         }
 
-        // TODO: Signature check
+        if let Some(sig) = &input.digest {
+            match sig.scheme().sig_byte_len() {
+                Ok(sig_byte_len) if sig.as_bytes().len() == sig_byte_len => {}
+                _ => return Err(GraphError::InvalidNodeInput),
+            }
+        }
 
         // TODO: Instruction validity check (balances, enough reserved gas, etc.)
 
@@ -227,7 +424,14 @@ impl<'a> Graph<'a> {
 #[cfg(test)]
 mod tests {
     use super::*;
-    use cesium_nebula::instructions::instruction::{Instruction, InstructionType};
+    use cesium_crypto::{
+        scheme::{AnySignature, Scheme, Signer},
+        sphincsplus,
+    };
+    use cesium_nebula::{
+        accounts::MultisigAccount,
+        instructions::instruction::{Instruction, InstructionType},
+    };
     use std::sync::Arc;
     use tokio::task;
 
@@ -236,13 +440,128 @@ mod tests {
         let acc = SignerPair::create();
         let dag = Graph::default(&acc);
 
-        let tx = create_valid_transaction(&acc);
+        let tx = create_valid_transaction(&acc, 0);
         dag.add_genesis(&tx).await.unwrap();
 
         assert_eq!(dag.nodes.len(), 1);
         assert_valid_node(&dag, tx.instructions.clone());
     }
 
+    #[tokio::test]
+    async fn test_add_replayed_transaction() {
+        let acc = SignerPair::create();
+        let dag = Graph::default(&acc);
+
+        let tx = create_valid_transaction(&acc, 0);
+        dag.add_genesis(&tx).await.unwrap();
+        let second = create_valid_transaction(&acc, 1);
+        dag.add_item(&second).await.unwrap();
+
+        // Neither a copy nor a signature over the same transaction gets in again
+        let mut resigned = Transaction::from_bytes(&second.to_bytes().unwrap()).unwrap();
+        resigned.sign(&acc).unwrap();
+        for replay in [&second, &resigned] {
+            assert!(matches!(
+                dag.add_item(replay).await,
+                Err(GraphError::StaleNonce)
+            ));
+        }
+        assert_eq!(dag.nodes.len(), 2);
+
+        // The node id is the transaction id and the transaction is stored under it
+        let id = second.id().unwrap();
+        assert!(dag.nodes.contains_key(&id));
+        let stored = Transaction::from_id(&second.id_bytes().unwrap())
+            .await
+            .unwrap();
+        assert_eq!(stored.to_bytes().unwrap(), second.to_bytes().unwrap());
+    }
+
+    #[tokio::test]
+    async fn test_nonce_order() {
+        let acc = SignerPair::create();
+        let dag = Graph::default(&acc);
+        dag.add_genesis(&create_valid_transaction(&acc, 0))
+            .await
+            .unwrap();
+
+        // A future nonce is held back until the gap is filled
+        let third = create_valid_transaction(&acc, 2);
+        dag.add_item(&third).await.unwrap();
+        assert_eq!(dag.nodes.len(), 1);
+
+        let mut other_third = create_valid_transaction(&acc, 2);
+        other_third.priority_fee = 1;
+        other_third.sign(&acc).unwrap();
+        assert!(matches!(
+            dag.add_item(&other_third).await,
+            Err(GraphError::DuplicateNonce)
+        ));
+        assert!(matches!(
+            dag.add_item(&create_valid_transaction(&acc, 2 + MAX_NONCE_GAP))
+                .await,
+            Err(GraphError::NonceTooFarAhead)
+        ));
+
+        dag.add_item(&create_valid_transaction(&acc, 1))
+            .await
+            .unwrap();
+        assert_eq!(dag.nodes.len(), 3);
+        assert!(dag.nodes.contains_key(&third.id().unwrap()));
+
+        // The next nonce is kept with the account state
+        let signer = third.signer_id().unwrap();
+        assert_eq!(NonceAccount::from_id(signer).await.unwrap().nonce(), 3);
+        assert!(matches!(
+            dag.add_item(&create_valid_transaction(&acc, 1)).await,
+            Err(GraphError::StaleNonce)
+        ));
+
+        // SPHINCS+ signers have longer addresses, their nonces work the same
+        let sphincs = sphincsplus::keypair::SignerPair::create();
+        let third = create_valid_transaction(&sphincs, 2);
+        dag.add_item(&third).await.unwrap();
+        dag.add_item(&create_valid_transaction(&sphincs, 0))
+            .await
+            .unwrap();
+        dag.add_item(&create_valid_transaction(&sphincs, 1))
+            .await
+            .unwrap();
+        assert!(dag.nodes.contains_key(&third.id().unwrap()));
+        let signer = third.signer_id().unwrap();
+        assert_eq!(NonceAccount::from_id(signer).await.unwrap().nonce(), 3);
+    }
+
+    #[tokio::test]
+    async fn test_transaction_expiry() {
+        let acc = SignerPair::create();
+        let dag = Graph::default(&acc);
+        dag.add_genesis(&create_valid_transaction(&acc, 0))
+            .await
+            .unwrap();
+
+        // Rejected at ingress without using up the nonce
+        let mut expired = create_valid_transaction(&acc, 1);
+        expired.expires_at = expired.timestamp - 1;
+        expired.sign(&acc).unwrap();
+        assert!(matches!(
+            dag.add_item(&expired).await,
+            Err(GraphError::TransactionExpired(_))
+        ));
+
+        let mut short_lived = create_valid_transaction(&acc, 1);
+        short_lived.expires_at = short_lived.timestamp + 1;
+        short_lived.sign(&acc).unwrap();
+        dag.add_item(&short_lived).await.unwrap();
+        assert_eq!(dag.nodes.len(), 2);
+
+        // Dropped from the graph once it expires unpacked
+        assert_eq!(dag.prune_expired(short_lived.expires_at).await, 0);
+        assert_eq!(dag.prune_expired(short_lived.expires_at + 1).await, 1);
+        assert_eq!(dag.nodes.len(), 1);
+        assert!(!dag.nodes.contains_key(&short_lived.id().unwrap()));
+    }
+
     #[tokio::test]
     async fn test_add_transaction_with_missing_signature() {
         let acc = SignerPair::create();
@@ -260,21 +579,87 @@ mod tests {
         assert_eq!(dag.nodes.len(), 0);
     }
 
+    #[tokio::test]
+    async fn test_add_transaction_with_invalid_signature() {
+        let acc = SignerPair::create();
+        let dag = Graph::default(&acc);
+
+        let mut tx = create_valid_transaction(&acc, 0);
+        tx.priority_fee = 1;
+
+        let result = dag.add_genesis(&tx).await;
+        assert!(matches!(result, Err(GraphError::InvalidSignature)));
+        assert_eq!(dag.nodes.len(), 0);
+    }
+
+    #[tokio::test]
+    async fn test_add_multisig_transaction() {
+        let acc = SignerPair::create();
+        let dag = Graph::default(&acc);
+
+        let members = [
+            SignerPair::create(),
+            SignerPair::create(),
+            SignerPair::create(),
+        ];
+        let account =
+            MultisigAccount::new(members.iter().map(|m| m.public_key()).collect(), 2).unwrap();
+
+        let mut tx = Transaction::new(18000, 0);
+        tx.add_instruction(Instruction::new(
+            InstructionType::CurrencyTransfer,
+            Vec::new(),
+        ))
+        .unwrap();
+
+        // Below the threshold
+        tx.sign_multisig(&account, &members[0]).unwrap();
+        let result = dag.add_genesis(&tx).await;
+        assert!(matches!(result, Err(GraphError::InvalidSignature)));
+        assert_eq!(dag.nodes.len(), 0);
+
+        tx.sign_multisig(&account, &members[2]).unwrap();
+        dag.add_genesis(&tx).await.unwrap();
+        assert_eq!(dag.nodes.len(), 1);
+    }
+
+    #[tokio::test]
+    async fn test_add_items_batch() {
+        let acc = SignerPair::create();
+        let dag = Graph::default(&acc);
+        dag.add_genesis(&create_valid_transaction(&acc, 0))
+            .await
+            .unwrap();
+
+        let mut txs: Vec<Transaction> = (0..8)
+            .map(|_| create_valid_transaction(&SignerPair::create(), 0))
+            .collect();
+        txs[3].priority_fee = 1;
+        txs[5].digest = None;
+
+        let results = dag.add_items(&txs).await;
+        assert!(matches!(results[3], Err(GraphError::InvalidSignature)));
+        assert!(matches!(results[5], Err(GraphError::MissingSignature)));
+        assert_eq!(results.iter().filter(|r| r.is_ok()).count(), 6);
+        assert_eq!(dag.nodes.len(), 7);
+    }
+
     #[tokio::test]
     async fn test_concurrent_transaction_addition() {
         let acc = Arc::new(SignerPair::create());
         let dag: Arc<Graph<'_>> = Arc::new(Graph::default(Box::leak(Box::new(acc.clone()))));
 
-        dag.add_genesis(&create_valid_transaction(&acc))
+        dag.add_genesis(&create_valid_transaction(acc.as_ref(), 0))
             .await
             .unwrap();
 
+        // Transactions that overtake each other wait for their turn
         let mut handles = Vec::with_capacity(10);
-        for _ in 0..10 {
+        for nonce in 1..=10 {
             let dag = Arc::clone(&dag);
             let acc = Arc::clone(&acc);
             handles.push(task::spawn(async move {
-                let tx = create_valid_transaction(&acc);
+                let tx = create_valid_transaction(acc.as_ref(), nonce);
                 dag.add_item(&tx).await.unwrap();
             }));
         }
@@ -299,12 +684,12 @@ mod tests {
         dag.set_proportion(0.4);
 
         // Add genesis node
-        dag.add_genesis(&create_valid_transaction(&acc))
+        dag.add_genesis(&create_valid_transaction(&acc, 0))
             .await
             .unwrap();
 
-        for _ in 0..10 {
-            let tx = create_valid_transaction(&acc);
+        for nonce in 1..=10 {
+            let tx = create_valid_transaction(&acc, nonce);
             dag.add_item(&tx).await.unwrap();
         }
 
@@ -314,17 +699,35 @@ mod tests {
             *lock = 3;
         }
 
-        dag.pack_history().await.unwrap();
+        let ids: Vec<NodeId> = dag.nodes.iter().map(|n| n.key().clone()).collect();
+        let key = dag.pack_history().await.unwrap();
         assert_eq!(dag.nodes.len(), 1);
+
+        // The checkpoint is signed over its root and proves each packed node
+        let checkpoint = Checkpoint::load(&key).unwrap().unwrap();
+        let sig = AnySignature::new(Scheme::MlDsa, &key).unwrap();
+        assert!(acc.public_key().verify(&checkpoint.root(), &sig).unwrap());
+
+        let packed: Vec<&NodeId> = ids
+            .iter()
+            .filter(|id| !dag.nodes.contains_key(*id))
+            .collect();
+        assert_eq!(packed.len(), checkpoint.len());
+        for id in packed {
+            let proof = checkpoint.proof(id).unwrap();
+            let leaf = &checkpoint.nodes()[proof.index as usize];
+            assert!(proof.verify(&checkpoint.root(), leaf));
+        }
     }
 
-    fn create_valid_transaction(acc: &SignerPair) -> Transaction {
+    fn create_valid_transaction(acc: &impl Signer, nonce: u64) -> Transaction {
         let mut tx = Transaction::new(18000, 0);
         tx.add_instruction(Instruction::new(
             InstructionType::CurrencyTransfer,
             Vec::new(),
         ))
         .unwrap();
+        tx.nonce = nonce;
         tx.sign(acc).unwrap();
         tx
     }
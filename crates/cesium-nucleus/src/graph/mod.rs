@@ -1,3 +1,4 @@
+pub mod checkpoint;
 pub mod errors;
 pub mod mempool;
 pub mod node;
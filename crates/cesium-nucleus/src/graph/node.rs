@@ -2,12 +2,19 @@ use cesium_nebula::instructions::{
     errors::InstructionError,
     instruction::{Instruction, InstructionType},
 };
+use cesium_standards::CHAIN_ID;
 use tokio::sync::RwLock;
 
 use super::errors::GraphError;
 
 pub type NodeId = String;
 
+// Nodes follow the transaction wire format, a version byte and the chain ID
+// come first
+pub const NODE_VERSION_1: u8 = 1;
+pub const NODE_VERSION: u8 = NODE_VERSION_1;
+pub const NODE_HEADER_BYTE_LEN: usize = 5;
+
 #[derive(Debug)]
 pub struct GraphNode {
     pub id: NodeId,
@@ -18,7 +25,8 @@ pub struct GraphNode {
 
 impl GraphNode {
     pub async fn to_bytes(&self) -> Vec<u8> {
-        let mut bytes = vec![];
+        let mut bytes = vec![NODE_VERSION];
+        bytes.extend(CHAIN_ID.to_le_bytes());
         // add the size of the id
         bytes.push(self.id.len() as u8);
         bytes.extend(self.id.as_bytes());
@@ -41,10 +49,25 @@ impl GraphNode {
     }
 
     pub fn from_bytes(bytes: &[u8]) -> Result<GraphNode, GraphError> {
+        match bytes.first() {
+            Some(&NODE_VERSION_1) => Self::from_bytes_v1(&bytes[1..]),
+            Some(&version) => Err(GraphError::UnsupportedNodeVersion(version)),
+            None => Err(GraphError::InvalidNodeInput),
+        }
+    }
+
+    fn from_bytes_v1(bytes: &[u8]) -> Result<GraphNode, GraphError> {
         // TODO: Rework this function like in cesium-nebula->transactions
         // add bound checks etc.
         let mut cursor = 0;
 
+        // Read the chain id, nodes of other networks are refused
+        let chain_id = u32::from_le_bytes(bytes[cursor..cursor + 4].try_into()?);
+        cursor += 4;
+        if chain_id != CHAIN_ID {
+            return Err(GraphError::WrongNetwork(chain_id));
+        }
+
         // Read id length and id
         let id_len = bytes[cursor] as usize;
         cursor += 1;
@@ -158,4 +181,29 @@ mod tests {
             *node2.references.read().await
         );
     }
+
+    #[tokio::test]
+    async fn test_graph_node_version_and_network() {
+        let node = GraphNode {
+            id: "node1".to_string(),
+            instructions: vec![],
+            prev_nodes: vec![],
+            references: RwLock::new(0),
+        };
+
+        let mut bytes = node.to_bytes().await;
+        assert_eq!(bytes[0], NODE_VERSION);
+        bytes[0] = 9;
+        assert!(matches!(
+            GraphNode::from_bytes(&bytes),
+            Err(GraphError::UnsupportedNodeVersion(9))
+        ));
+
+        bytes[0] = NODE_VERSION;
+        bytes[1..5].copy_from_slice(&(CHAIN_ID + 1).to_le_bytes());
+        assert!(matches!(
+            GraphNode::from_bytes(&bytes),
+            Err(GraphError::WrongNetwork(id)) if id == CHAIN_ID + 1
+        ));
+    }
 }
@@ -1,7 +1,12 @@
 use std::sync::Arc;
 
-use cesium_nebula::transaction::{Transaction, TransactionError};
-use cesium_nucleus::graph::mempool::Graph;
+use cesium_crypto::{errors::CryptoError, mldsa::da::DisplayAddress};
+use cesium_nebula::{
+    accounts::{AccountError, DataAccount, NFTOwnerIndex, UserAccount},
+    data::nft::NFTHolderData,
+    transaction::{parse_id, Transaction, TransactionError},
+};
+use cesium_nucleus::graph::{checkpoint::Checkpoint, mempool::Graph};
 use hex::FromHexError;
 use jsonrpsee::{
     core::{async_trait, SubscriptionResult},
@@ -20,6 +25,8 @@ pub enum RpcError {
     RpcError(String),
     TxError(TransactionError),
     GraphError(cesium_nucleus::graph::errors::GraphError),
+    CryptoError(CryptoError),
+    AccountError(AccountError),
 }
 
 impl From<RpcError> for ErrorObject<'static> {
@@ -29,7 +36,15 @@ impl From<RpcError> for ErrorObject<'static> {
             RpcError::HexError(e) => ErrorObject::owned(2, "Hex Error", Some(e.to_string())),
             RpcError::RpcError(e) => ErrorObject::owned(2, "RPC Error", Some(e)),
             RpcError::TxError(e) => ErrorObject::owned(2, "Transaction Error", Some(e.to_string())),
+            // Expired transactions get their own code, resending will not help
+            RpcError::GraphError(
+                e @ cesium_nucleus::graph::errors::GraphError::TransactionExpired(_),
+            ) => ErrorObject::owned(3, "Transaction Expired", Some(e.to_string())),
             RpcError::GraphError(e) => ErrorObject::owned(2, "Graph Error", Some(e.to_string())),
+            RpcError::CryptoError(e) => ErrorObject::owned(2, "Crypto Error", Some(e.to_string())),
+            RpcError::AccountError(e) => {
+                ErrorObject::owned(2, "Account Error", Some(e.to_string()))
+            }
         }
     }
 }
@@ -58,6 +73,18 @@ impl From<cesium_nucleus::graph::errors::GraphError> for RpcError {
     }
 }
 
+impl From<CryptoError> for RpcError {
+    fn from(e: CryptoError) -> Self {
+        RpcError::CryptoError(e)
+    }
+}
+
+impl From<AccountError> for RpcError {
+    fn from(e: AccountError) -> Self {
+        RpcError::AccountError(e)
+    }
+}
+
 #[rpc(server)]
 pub trait Rpc {
     #[method(name = "getVersion")]
@@ -68,18 +95,43 @@ pub trait Rpc {
     #[method(name = "getCheckpoint")]
     async fn get_checkpoint(&self, index: Option<u64>) -> Result<String, RpcError>;
 
-    // getTransaction is a method that returns the transaction data given a transaction hash.
+    // getTransaction is a method that returns the transaction data as JSON given a transaction
+    // hash, the hash is the hex transaction id returned by sendTransaction.
     #[method(name = "getTransaction")]
-    async fn get_transaction(&self, hash: String) -> Result<String, RpcError>;
+    async fn get_transaction(&self, hash: String) -> Result<serde_json::Value, RpcError>;
+
+    // getCheckpointRoot returns the transaction root of the checkpoint stored under the given
+    // (hex encoded) validator signature.
+    #[method(name = "getCheckpointRoot")]
+    async fn get_checkpoint_root(&self, checkpoint: String) -> Result<String, RpcError>;
 
-    // sendTransaction is a method that sends a transaction to the network.
+    // getTransactionProof returns a merkle proof that the node with the given id is part of the
+    // checkpoint, it is verified against the checkpoint root with the serialized node as leaf.
+    #[method(name = "getTransactionProof")]
+    async fn get_transaction_proof(
+        &self,
+        checkpoint: String,
+        id: String,
+    ) -> Result<String, RpcError>;
+
+    // sendTransaction is a method that sends a transaction to the network and returns its id.
     #[method(name = "sendTransaction")]
     async fn send_transaction(&self, tx: String) -> Result<String, RpcError>;
 
-    // getAccountInfo is a method that returns the account information for a given account.
-    // This can be called on base accounts, as well as on data accounts
+    // sendTransactions sends a burst of transactions, their signatures are verified as one batch.
+    // Returns null for every accepted transaction and the error for every rejected one, in order.
+    #[method(name = "sendTransactions")]
+    async fn send_transactions(&self, txs: Vec<String>) -> Result<Vec<Option<String>>, RpcError>;
+
+    // getAccountInfo is a method that returns the account information for a given account
+    // address as JSON, addresses in it are bech32m and binary data is hex.
     #[method(name = "getAccountInfo")]
-    async fn get_account_info(&self, account: String) -> Result<String, RpcError>;
+    async fn get_account_info(&self, account: String) -> Result<serde_json::Value, RpcError>;
+
+    // getNfts returns the NFTs held by the given owner address as JSON, each with its address,
+    // name, uri, creators and the bundle it belongs to.
+    #[method(name = "getNfts")]
+    async fn get_nfts(&self, owner: String) -> Result<Vec<serde_json::Value>, RpcError>;
 
     // checkpointsSub is a subscription method that broadcasts the latest checkpoint information.
     #[subscription(name = "subscribeCheckpoints", item = usize, with_extensions)]
@@ -114,8 +166,28 @@ impl RpcServer for RpcServerImpl {
         Ok("todo".to_string())
     }
 
-    async fn get_transaction(&self, _hash: String) -> Result<String, RpcError> {
-        Ok("todo".to_string())
+    async fn get_transaction(&self, hash: String) -> Result<serde_json::Value, RpcError> {
+        let tx = Transaction::from_id(&parse_id(&hash)?).await?;
+        let mut json = serde_json::to_value(&tx).map_err(|e| RpcError::RpcError(e.to_string()))?;
+        json["id"] = tx.id()?.into();
+        Ok(json)
+    }
+
+    async fn get_checkpoint_root(&self, checkpoint: String) -> Result<String, RpcError> {
+        let checkpoint = load_checkpoint(&checkpoint)?;
+        Ok(hex::encode(checkpoint.root()))
+    }
+
+    async fn get_transaction_proof(
+        &self,
+        checkpoint: String,
+        id: String,
+    ) -> Result<String, RpcError> {
+        let checkpoint = load_checkpoint(&checkpoint)?;
+        let proof = checkpoint
+            .proof(&id)
+            .ok_or_else(|| RpcError::RpcError("Transaction not in checkpoint".to_string()))?;
+        Ok(hex::encode(proof.to_bytes()))
     }
 
     async fn send_transaction(&self, tx: String) -> Result<String, RpcError> {
@@ -124,18 +196,75 @@ impl RpcServer for RpcServerImpl {
         if !tx.is_signed() {
             return Err(TransactionError::NotSigned.into());
         }
-        if !tx.verify()? {
-            return Err(TransactionError::InvalidSignature.into());
-        }
 
         // TODO: May still need to do some things here?
+        // The graph verifies the signatures before accepting the transaction
         self.dag.add_item(&tx).await?;
 
-        Ok("todo".to_string())
+        Ok(tx.id()?)
     }
 
-    async fn get_account_info(&self, _account: String) -> Result<String, RpcError> {
-        Ok("todo".to_string())
+    async fn send_transactions(&self, txs: Vec<String>) -> Result<Vec<Option<String>>, RpcError> {
+        let mut results: Vec<Option<String>> = vec![None; txs.len()];
+        let mut parsed = Vec::with_capacity(txs.len());
+        let mut positions = Vec::with_capacity(txs.len());
+        for (i, tx) in txs.iter().enumerate() {
+            let tx = hex::decode(tx)
+                .map_err(RpcError::from)
+                .and_then(|bytes| Ok(Transaction::from_bytes(&bytes)?));
+            match tx {
+                Ok(tx) => {
+                    parsed.push(tx);
+                    positions.push(i);
+                }
+                Err(e) => results[i] = Some(ErrorObject::from(e).to_string()),
+            }
+        }
+
+        let added = self.dag.add_items(&parsed).await;
+        for (i, result) in positions.into_iter().zip(added) {
+            if let Err(e) = result {
+                results[i] = Some(ErrorObject::from(RpcError::from(e)).to_string());
+            }
+        }
+
+        Ok(results)
+    }
+
+    async fn get_account_info(&self, account: String) -> Result<serde_json::Value, RpcError> {
+        let da: DisplayAddress = account.parse()?;
+        let account = UserAccount::from_id(*da.as_bytes()).await?;
+        serde_json::to_value(&account).map_err(|e| RpcError::RpcError(e.to_string()))
+    }
+
+    async fn get_nfts(&self, owner: String) -> Result<Vec<serde_json::Value>, RpcError> {
+        let da: DisplayAddress = owner.parse()?;
+        let index = NFTOwnerIndex::from_id(*da.as_bytes()).await?;
+
+        let mut nfts = Vec::with_capacity(index.nfts().len());
+        for id in index.nfts() {
+            let account = DataAccount::from_id(*id).await?;
+            let metadata = NFTHolderData::try_from_bytes(account.data())
+                .map_err(|e| RpcError::RpcError(e.to_string()))?;
+            let creators = metadata
+                .creators()
+                .iter()
+                .map(|creator| Ok(DisplayAddress::from_bytes(creator)?.as_str()))
+                .collect::<Result<Vec<String>, RpcError>>()?;
+            let bundle = match metadata.bundle() {
+                Some(bundle) => Some(DisplayAddress::from_bytes(bundle)?.as_str()),
+                None => None,
+            };
+            nfts.push(serde_json::json!({
+                "address": account.address(),
+                "owner": account.owner_address(),
+                "name": metadata.name(),
+                "uri": metadata.uri(),
+                "creators": creators,
+                "bundle": bundle,
+            }));
+        }
+        Ok(nfts)
     }
 
     async fn checkpoints_sub(
@@ -195,6 +324,11 @@ impl RpcServer for RpcServerImpl {
     }
 }
 
+fn load_checkpoint(key: &str) -> Result<Checkpoint, RpcError> {
+    Checkpoint::load(&hex::decode(key)?)?
+        .ok_or_else(|| RpcError::RpcError("Checkpoint not found".to_string()))
+}
+
 pub async fn start_rpc(dag: &Arc<Graph<'static>>) -> Result<String, RpcError> {
     let rpc_middleware = jsonrpsee::server::middleware::rpc::RpcServiceBuilder::new();
     let server = jsonrpsee::server::Server::builder()
@@ -214,10 +348,19 @@ pub async fn start_rpc(dag: &Arc<Graph<'static>>) -> Result<String, RpcError> {
 
 #[cfg(test)]
 mod tests {
-    use std::sync::Arc;
+    use std::{rc::Rc, sync::Arc};
 
-    use cesium_crypto::mldsa::keypair::SignerPair;
-    use cesium_nucleus::graph::mempool::Graph;
+    use cesium_crypto::{
+        merkle::{Hash, MerkleProof},
+        mldsa::{da::DisplayAddress, keypair::SignerPair},
+    };
+    use cesium_nebula::{
+        accounts::{DataAccount, NFTOwnerIndex, UserAccount},
+        data::nft::NFTHolderData,
+        instructions::instruction::{Instruction, InstructionType},
+        transaction::Transaction,
+    };
+    use cesium_nucleus::graph::{checkpoint::Checkpoint, mempool::Graph, node::GraphNode};
     use jsonrpsee::{
         core::{client::ClientT, ClientError},
         rpc_params,
@@ -238,4 +381,250 @@ mod tests {
         let env_version = env!("CARGO_PKG_VERSION");
         assert_eq!(result.unwrap(), env_version.to_string());
     }
+
+    #[tokio::test]
+    async fn test_send_transactions() {
+        let acc = Box::leak(Box::new(SignerPair::create()));
+        let dag = Arc::new(Graph::default(acc));
+
+        let mut genesis = Transaction::new(18000, 100);
+        genesis
+            .add_instruction(Instruction::new(InstructionType::CurrencyTransfer, vec![]))
+            .unwrap();
+        genesis.sign(acc).unwrap();
+        dag.add_genesis(&genesis).await.unwrap();
+
+        let url = super::start_rpc(&dag).await.unwrap();
+        let client = WsClientBuilder::default().build(&url).await.unwrap();
+
+        let mut txs = Vec::new();
+        for fee in 0..3 {
+            let mut tx = Transaction::new(18000, fee);
+            tx.add_instruction(Instruction::new(InstructionType::CurrencyTransfer, vec![]))
+                .unwrap();
+            // The tampered transaction is rejected and leaves its nonce open
+            tx.nonce = if fee == 0 { 1 } else { 2 };
+            tx.sign(acc).unwrap();
+            // Tamper with the second transaction after signing
+            if fee == 1 {
+                tx.reserved_gas += 1;
+            }
+            txs.push(hex::encode(tx.to_bytes().unwrap()));
+        }
+        txs.push("not hex".to_string());
+
+        let results: Vec<Option<String>> = client
+            .request("sendTransactions", rpc_params!(txs))
+            .await
+            .unwrap();
+        assert_eq!(results.len(), 4);
+        assert!(results[0].is_none());
+        assert!(results[1].as_ref().unwrap().contains("Invalid signature"));
+        assert!(results[2].is_none());
+        assert!(results[3].is_some());
+    }
+
+    #[tokio::test]
+    async fn test_transaction_proof() {
+        let acc = Box::leak(Box::new(SignerPair::create()));
+        let dag = Arc::new(Graph::default(acc));
+
+        let mut nodes: Vec<Vec<u8>> = Vec::new();
+        for id in ["node1", "node2", "node3"] {
+            let node = GraphNode {
+                id: id.to_string(),
+                instructions: vec![],
+                prev_nodes: vec![],
+                references: 0.into(),
+            };
+            nodes.push(node.to_bytes().await);
+        }
+        let checkpoint = Checkpoint::new(nodes.clone());
+        let key = acc.sign_detached(&checkpoint.root());
+        checkpoint.save(&key).unwrap();
+
+        let url = super::start_rpc(&dag).await.unwrap();
+        let client = WsClientBuilder::default().build(&url).await.unwrap();
+
+        let root: String = client
+            .request("getCheckpointRoot", rpc_params!(hex::encode(&key)))
+            .await
+            .unwrap();
+        let root: Hash = hex::decode(root).unwrap().try_into().unwrap();
+        assert_eq!(root, checkpoint.root());
+
+        let proof: String = client
+            .request(
+                "getTransactionProof",
+                rpc_params!(hex::encode(&key), "node2"),
+            )
+            .await
+            .unwrap();
+        let proof = MerkleProof::from_bytes(&hex::decode(proof).unwrap()).unwrap();
+        assert!(proof.verify(&root, &nodes[1]));
+
+        let missing: Result<String, ClientError> = client
+            .request(
+                "getTransactionProof",
+                rpc_params!(hex::encode(&key), "node4"),
+            )
+            .await;
+        assert!(missing.is_err());
+    }
+
+    #[tokio::test]
+    async fn test_get_nfts() {
+        let acc = Box::leak(Box::new(SignerPair::create()));
+        let dag = Arc::new(Graph::default(acc));
+
+        let owner = DisplayAddress::new();
+        let creator = DisplayAddress::new();
+        let nft = DisplayAddress::new();
+        let metadata = NFTHolderData::new(
+            "Test NFT".to_string(),
+            "https://127.0.0.1".to_string(),
+            vec![*creator.as_bytes()],
+            None,
+        );
+        DataAccount::new(
+            *nft.as_bytes(),
+            *owner.as_bytes(),
+            *creator.as_bytes(),
+            metadata.to_bytes(),
+        )
+        .write()
+        .await
+        .unwrap();
+        NFTOwnerIndex::new(*owner.as_bytes(), vec![*nft.as_bytes()])
+            .write()
+            .await
+            .unwrap();
+
+        let url = super::start_rpc(&dag).await.unwrap();
+        let client = WsClientBuilder::default().build(&url).await.unwrap();
+
+        let nfts: Vec<serde_json::Value> = client
+            .request("getNfts", rpc_params!(owner.as_str()))
+            .await
+            .unwrap();
+        assert_eq!(nfts.len(), 1);
+        assert_eq!(nfts[0]["address"], nft.as_str());
+        assert_eq!(nfts[0]["name"], "Test NFT");
+        assert_eq!(nfts[0]["creators"][0], creator.as_str());
+        assert!(nfts[0]["bundle"].is_null());
+
+        let empty: Vec<serde_json::Value> = client
+            .request("getNfts", rpc_params!(DisplayAddress::new().as_str()))
+            .await
+            .unwrap();
+        assert!(empty.is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_get_account_info() {
+        let acc = Box::leak(Box::new(SignerPair::create()));
+        let dag = Arc::new(Graph::default(acc));
+
+        let id = DisplayAddress::new();
+        let data_account = DisplayAddress::new();
+        UserAccount::new(*id.as_bytes(), Rc::new(vec![*data_account.as_bytes()]))
+            .write()
+            .await
+            .unwrap();
+
+        let url = super::start_rpc(&dag).await.unwrap();
+        let client = WsClientBuilder::default().build(&url).await.unwrap();
+
+        let info: serde_json::Value = client
+            .request("getAccountInfo", rpc_params!(id.as_str()))
+            .await
+            .unwrap();
+        assert_eq!(info["address"], id.as_str());
+        assert_eq!(info["data_accounts"][0], data_account.as_str());
+
+        let missing: Result<serde_json::Value, ClientError> = client
+            .request(
+                "getAccountInfo",
+                rpc_params!(DisplayAddress::new().as_str()),
+            )
+            .await;
+        assert!(missing.is_err());
+        let invalid: Result<serde_json::Value, ClientError> = client
+            .request("getAccountInfo", rpc_params!("not an address"))
+            .await;
+        assert!(invalid.is_err());
+    }
+
+    #[tokio::test]
+    async fn test_get_transaction() {
+        let acc = Box::leak(Box::new(SignerPair::create()));
+        let dag = Arc::new(Graph::default(acc));
+
+        let mut genesis = Transaction::new(18000, 100);
+        genesis
+            .add_instruction(Instruction::new(InstructionType::CurrencyTransfer, vec![]))
+            .unwrap();
+        genesis.sign(acc).unwrap();
+        dag.add_genesis(&genesis).await.unwrap();
+
+        let url = super::start_rpc(&dag).await.unwrap();
+        let client = WsClientBuilder::default().build(&url).await.unwrap();
+
+        let mut tx = Transaction::new(18000, 1);
+        tx.add_instruction(Instruction::new(
+            InstructionType::CurrencyTransfer,
+            vec![1, 2, 3],
+        ))
+        .unwrap();
+        tx.nonce = 1;
+        tx.sign(acc).unwrap();
+
+        // The returned id is the content address every node derives
+        let id: String = client
+            .request(
+                "sendTransaction",
+                rpc_params!(hex::encode(tx.to_bytes().unwrap())),
+            )
+            .await
+            .unwrap();
+        assert_eq!(id, tx.id().unwrap());
+
+        let info: serde_json::Value = client
+            .request("getTransaction", rpc_params!(&id))
+            .await
+            .unwrap();
+        assert_eq!(info["id"], id);
+        assert_eq!(info["instructions"][0]["data"], "010203");
+        assert_eq!(info["priority_fee"], 1);
+
+        let unknown: Result<serde_json::Value, ClientError> = client
+            .request("getTransaction", rpc_params!(hex::encode([0u8; 32])))
+            .await;
+        assert!(unknown.is_err());
+        let invalid: Result<serde_json::Value, ClientError> = client
+            .request("getTransaction", rpc_params!("not an id"))
+            .await;
+        assert!(invalid.is_err());
+
+        let mut expired = Transaction::new(18000, 1);
+        expired
+            .add_instruction(Instruction::new(InstructionType::CurrencyTransfer, vec![]))
+            .unwrap();
+        expired.nonce = 2;
+        expired.expires_at = expired.timestamp - 1;
+        expired.sign(acc).unwrap();
+        let result: Result<String, ClientError> = client
+            .request(
+                "sendTransaction",
+                rpc_params!(hex::encode(expired.to_bytes().unwrap())),
+            )
+            .await;
+        match result {
+            Err(ClientError::Call(e)) => {
+                assert_eq!(e.code(), 3);
+                assert_eq!(e.message(), "Transaction Expired");
+            }
+            _ => panic!("expected an expired transaction error"),
+        }
+    }
 }
@@ -1,4 +1,4 @@
-use cesium_crypto::mldsa::da::DA_BYTE_LEN;
+use cesium_crypto::mldsa::da::{DABytes, DA_BYTE_LEN};
 use once_cell::sync::Lazy;
 use std::collections::HashMap;
 
@@ -12,6 +12,7 @@ pub enum StandardToken {
 
 pub struct TokenMetadata {
     address: &'static str,
+    address_bytes: &'static DABytes,
     decimals: u8,
     short_name: &'static str,
     full_name: &'static str,
@@ -19,8 +20,21 @@ pub struct TokenMetadata {
 
 pub const BASE_TX_FEE: u128 = 1000; // 0.000001 Cesium
 
-pub const NATIVE_TOKEN: &str = "cesium11111111111111111111111111111111111111";
-pub const NATIVE_TOKEN_BYTES: &[u8; DA_BYTE_LEN] = b"cesium11111111111111111111111111"; // The bytes for a display address are 32, so we remove some trailing 1s that are on the display address
+// Network identifiers, signed into every transaction so it is only valid on
+// the network it was made for
+pub const MAINNET_CHAIN_ID: u32 = 1;
+pub const TESTNET_CHAIN_ID: u32 = 2;
+
+// The network this build runs on
+#[cfg(not(feature = "testnet"))]
+pub const CHAIN_ID: u32 = MAINNET_CHAIN_ID;
+#[cfg(feature = "testnet")]
+pub const CHAIN_ID: u32 = TESTNET_CHAIN_ID;
+
+// Standard token addresses are readable byte strings padded with 1s, the
+// display address is the regular bech32m encoding of those bytes
+pub const NATIVE_TOKEN: &str = "csm1vdjhx6t4d5cnzvf3xycnzvf3xycnzvf3xycnzvf3xycnzvf3xycsmyxj4w";
+pub const NATIVE_TOKEN_BYTES: &[u8; DA_BYTE_LEN] = b"cesium11111111111111111111111111";
 
 pub const NATIVE_DECIMALS: u8 = 12;
 
@@ -34,6 +48,7 @@ static TOKEN_METADATA: Lazy<HashMap<StandardToken, TokenMetadata>> = Lazy::new(|
         StandardToken::Cesium,
         TokenMetadata {
             address: NATIVE_TOKEN,
+            address_bytes: NATIVE_TOKEN_BYTES,
             decimals: NATIVE_DECIMALS,
             short_name: "CSM",
             full_name: "Cesium",
@@ -42,7 +57,8 @@ static TOKEN_METADATA: Lazy<HashMap<StandardToken, TokenMetadata>> = Lazy::new(|
     m.insert(
         StandardToken::Cesium,
         TokenMetadata {
-            address: "scesium1111111111111111111111111111111111111",
+            address: "csm1wd3k2umfw4knzvf3xycnzvf3xycnzvf3xycnzvf3xycnzvf3xycsef6kxf",
+            address_bytes: b"scesium1111111111111111111111111",
             decimals: NATIVE_DECIMALS,
             short_name: "SCSM",
             full_name: "Staked Cesium",
@@ -51,7 +67,8 @@ static TOKEN_METADATA: Lazy<HashMap<StandardToken, TokenMetadata>> = Lazy::new(|
     m.insert(
         StandardToken::WBTC,
         TokenMetadata {
-            address: "wbtc1111111111111111111111111111111111111111",
+            address: "csm1wa38gce3xycnzvf3xycnzvf3xycnzvf3xycnzvf3xycnzvf3xycsh6288y",
+            address_bytes: b"wbtc1111111111111111111111111111",
             decimals: 8,
             short_name: "wbtc",
             full_name: "Wrapped Bitcoin",
@@ -60,7 +77,8 @@ static TOKEN_METADATA: Lazy<HashMap<StandardToken, TokenMetadata>> = Lazy::new(|
     m.insert(
         StandardToken::WETH,
         TokenMetadata {
-            address: "weth1111111111111111111111111111111111111111",
+            address: "csm1wajhg6p3xycnzvf3xycnzvf3xycnzvf3xycnzvf3xycnzvf3xycs7ljs7f",
+            address_bytes: b"weth1111111111111111111111111111",
             decimals: 18,
             short_name: "weth",
             full_name: "Wrapped Ether",
@@ -70,7 +88,8 @@ static TOKEN_METADATA: Lazy<HashMap<StandardToken, TokenMetadata>> = Lazy::new(|
     m.insert(
         StandardToken::MER,
         TokenMetadata {
-            address: "mer11111111111111111111111111111111111111111",
+            address: "csm1d4jhyvf3xycnzvf3xycnzvf3xycnzvf3xycnzvf3xycnzvf3xycslqr3wl",
+            address_bytes: b"mer11111111111111111111111111111",
             decimals: 18,
             short_name: "mer",
             full_name: "Mero",
@@ -129,7 +148,7 @@ impl StandardToken {
     }
 
     pub fn address_bytes(&self) -> &[u8; DA_BYTE_LEN] {
-        self.address().as_bytes().try_into().unwrap()
+        self.metadata().address_bytes
     }
 
     pub fn decimals(&self) -> u8 {
@@ -147,14 +166,20 @@ impl StandardToken {
 
 #[cfg(test)]
 mod tests {
+    use std::str::FromStr;
+
+    use cesium_crypto::mldsa::da::DisplayAddress;
+
     use super::*;
 
     #[test]
     fn test_standard_token() {
         // loop over the standard tokens
         for token in StandardToken::iter() {
-            // First check the length of the addresses
-            assert_eq!(token.address().len(), 44);
+            // The address must be the display address of the token bytes
+            let da = DisplayAddress::from_str(token.address()).unwrap();
+            assert_eq!(da.as_bytes(), token.address_bytes());
+            assert_eq!(da.as_str(), token.address());
             // Now lets check the decimals
             assert!(token.decimals() >= MIN_DECIMALS);
             assert!(token.decimals() <= MAX_DECIMALS);
@@ -1,42 +1,91 @@
-use std::{fs::File, io::Write, path::PathBuf, sync::Arc};
+use std::{path::Path, sync::Arc};
 
-use cesium_crypto::mldsa::keypair::{SignerPair, ViewOperations};
+use cesium_crypto::{
+    derivation::{derive_key, DerivationPath},
+    mldsa::keypair::{SignerPair, ViewOperations},
+    mnemonic::Mnemonic,
+};
 use cesium_nucleus::graph::mempool::Graph;
 use cesium_rpc::start_rpc;
+use zeroize::Zeroizing;
+
+// Restores the account from this phrase instead of generating a new one
+const MNEMONIC_ENV: &str = "CESIUM_MNEMONIC";
+
+fn create_account() -> SignerPair {
+    let mnemonic = match std::env::var(MNEMONIC_ENV) {
+        Ok(phrase) => match Mnemonic::from_phrase(&phrase) {
+            Ok(mnemonic) => mnemonic,
+            Err(e) => {
+                panic!("Unable to read {}: {}", MNEMONIC_ENV, e);
+            }
+        },
+        Err(_) => {
+            let mnemonic = Mnemonic::generate();
+            println!("Write down this recovery phrase, it is the only backup of your account:");
+            println!("{}", mnemonic.phrase());
+            mnemonic
+        }
+    };
 
-fn handle_account(cesium_dir: &PathBuf) -> SignerPair {
-    let account_sk_path = cesium_dir.join("account.sk");
-    let account_pk_path = cesium_dir.join("account.pk");
+    let seed = Zeroizing::new(mnemonic.to_seed(""));
+    let key = Zeroizing::new(derive_key(seed.as_ref(), &DerivationPath::account(0)));
+    SignerPair::from_seed(&key)
+}
 
-    if !account_sk_path.exists() && !account_pk_path.exists() {
-        let account = SignerPair::create();
+// Unlocks the keystore without prompting, for unattended validators
+const PASSWORD_ENV: &str = "CESIUM_PASSWORD";
 
-        let (pk, sk) = account.to_bytes();
+// Passwords are wiped once the keystore is unlocked or written
+fn read_password(confirm: bool) -> Zeroizing<String> {
+    if let Ok(password) = std::env::var(PASSWORD_ENV) {
+        return Zeroizing::new(password);
+    }
 
-        let mut sk_file = File::create(&account_sk_path).expect("Unable to create secret key file");
-        sk_file
-            .write_all(&sk)
-            .expect("Unable to write secret key to file");
+    let password = Zeroizing::new(
+        rpassword::prompt_password("Keystore password: ")
+            .expect("Unable to read keystore password"),
+    );
+    if confirm {
+        let confirmation = Zeroizing::new(
+            rpassword::prompt_password("Confirm keystore password: ")
+                .expect("Unable to read keystore password"),
+        );
+        if password != confirmation {
+            panic!("Keystore passwords do not match");
+        }
+    }
 
-        let mut pk_file = File::create(&account_pk_path).expect("Unable to create public key file");
-        pk_file
-            .write_all(&pk)
-            .expect("Unable to write public key to file");
+    password
+}
 
-        println!("Account created and saved to disk");
-        return account;
+fn save_account(account: &SignerPair, keystore_path: &Path) {
+    let password = read_password(true);
+    if let Err(e) = account.save_keystore(keystore_path, password.as_bytes()) {
+        panic!("Unable to write account keystore: {}", e);
+    }
+}
+
+// Accounts created before the keystore existed are stored as plaintext
+// account.sk/account.pk files, those are encrypted and then removed
+fn migrate_account(cesium_dir: &Path, keystore_path: &Path) -> Option<SignerPair> {
+    let account_sk_path = cesium_dir.join("account.sk");
+    let account_pk_path = cesium_dir.join("account.pk");
+
+    if !account_sk_path.exists() && !account_pk_path.exists() {
+        return None;
     } else if !account_sk_path.exists() || !account_pk_path.exists() {
         panic!("Account secret key or public key not found");
     }
 
-    let sk_bytes = match std::fs::read(account_sk_path) {
-        Ok(bytes) => bytes,
+    let sk_bytes = match std::fs::read(&account_sk_path) {
+        Ok(bytes) => Zeroizing::new(bytes),
         Err(e) => {
             panic!("Unable to read account secret key file: {}", e);
         }
     };
 
-    let pk_bytes = match std::fs::read(account_pk_path) {
+    let pk_bytes = match std::fs::read(&account_pk_path) {
         Ok(bytes) => bytes,
         Err(e) => {
             panic!("Unable to read account public key file: {}", e);
@@ -50,6 +99,35 @@ fn handle_account(cesium_dir: &PathBuf) -> SignerPair {
         }
     };
 
+    println!("Encrypting plaintext account keys into a keystore");
+    save_account(&account, keystore_path);
+
+    std::fs::remove_file(&account_sk_path).expect("Unable to remove plaintext secret key file");
+    std::fs::remove_file(&account_pk_path).expect("Unable to remove plaintext public key file");
+    Some(account)
+}
+
+fn handle_account(cesium_dir: &Path) -> SignerPair {
+    let keystore_path = cesium_dir.join("account.keystore");
+
+    if keystore_path.exists() {
+        let password = read_password(false);
+        return match SignerPair::load_keystore(&keystore_path, password.as_bytes()) {
+            Ok(account) => account,
+            Err(e) => {
+                panic!("Unable to unlock account keystore: {}", e);
+            }
+        };
+    }
+
+    if let Some(account) = migrate_account(cesium_dir, &keystore_path) {
+        return account;
+    }
+
+    let account = create_account();
+    save_account(&account, &keystore_path);
+
+    println!("Account created and saved to disk");
     account
 }
 